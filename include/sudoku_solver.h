@@ -0,0 +1,42 @@
+/* C interface to sudoku_solver_by_roy, built with the `ffi` feature.
+ * Boards are buffers of 81 bytes, row by row, with 0 for empty cells.
+ * Regenerate with `cbindgen --config cbindgen.toml --output include/sudoku_solver.h` */
+
+#ifndef SUDOKU_SOLVER_H
+#define SUDOKU_SOLVER_H
+
+#include <stdint.h>
+
+/* The board was solved, or is valid */
+#define SUDOKU_OK 0
+/* The buffer pointer was null */
+#define SUDOKU_NULL_POINTER -1
+/* A byte in the buffer was over 9 */
+#define SUDOKU_INVALID_VALUE -2
+/* The board breaks the sudoku rules */
+#define SUDOKU_RULE_VIOLATION -3
+/* The board has no solution */
+#define SUDOKU_UNSOLVABLE -4
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Solve the puzzle, writing the solution into the solution buffer.
+ * puzzle and solution may point to the same buffer to solve in place.
+ * Returns SUDOKU_OK on success, or a negative error code. */
+int32_t sudoku_solve(const uint8_t *puzzle, uint8_t *solution);
+
+/* Check the puzzle against the sudoku rules, without solving it.
+ * Returns SUDOKU_OK if no unit repeats a value, or a negative error code. */
+int32_t sudoku_validate(const uint8_t *puzzle);
+
+/* Count the solutions of the puzzle, stopping once limit are found.
+ * Returns the count, or a negative error code. */
+int32_t sudoku_count_solutions(const uint8_t *puzzle, uint32_t limit);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* SUDOKU_SOLVER_H */
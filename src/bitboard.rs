@@ -0,0 +1,204 @@
+//! This module contains the bitmask board used internally by the solver
+//! Each row, column and subgrid keeps the values it holds as bits of a u32,
+//! so the candidates of a cell are found with a few bit operations
+//! instead of scanning its row, column and subgrid.
+//! Jigsaw boards swap the subgrids for their own regions, which are kept the same way.
+//! The empty cells of each row are kept as a bitset too, so the search visits only those
+//!
+//! Boards of the other sizes, up to 25x25, are kept the same way so they share the solver
+
+use crate::board::{Board, CellSelection, Index};
+use crate::sized::boxes;
+
+/// Board storing the values used in each unit as bitmasks
+/// Bit v is set in a mask if the value v is in that unit
+/// The board is NxN, 9x9 unless given otherwise, so the other sizes share the solver
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct BitBoard<const N: usize = 9> {
+    /// Cell values, 0 for empty cells
+    cells: [[u8; N]; N],
+    rows: [u32; N],
+    columns: [u32; N],
+    subgrids: [u32; N],
+    /// The subgrid of each cell, or the region on jigsaw boards
+    subgrid_of: [[u8; N]; N],
+    /// Bit column of the row's mask is set if the cell is empty
+    empty: [u32; N],
+}
+
+/// Iterator over the empty cells of a bitboard in row order
+/// It holds a copy of the bitsets, so the board can be changed while iterating
+pub(crate) struct EmptyCells<const N: usize> {
+    rows: [u32; N],
+    row: usize,
+}
+
+impl<const N: usize> Iterator for EmptyCells<N> {
+    type Item = Index;
+
+    fn next(&mut self) -> Option<Index> {
+        while self.row < N {
+            let empty = &mut self.rows[self.row];
+            if *empty != 0 {
+                let column = empty.trailing_zeros() as usize;
+                // clear the lowest set bit
+                *empty &= *empty - 1;
+                return Some(Index(self.row, column));
+            }
+            self.row += 1;
+        }
+        None
+    }
+}
+
+impl<const N: usize> BitBoard<N> {
+    /// Mask with the bits of all values 1 to N set
+    const ALL_VALUES: u32 = (1 << (N + 1)) - 2;
+
+    /// Mirror the values of a grid, 0 for empty cells, whose third kind of unit is the given regions
+    /// regions holds the region of each cell, numbered 0 to N - 1
+    pub(crate) fn new(values: &[[u8; N]; N], regions: &[[u8; N]; N]) -> Self {
+        let mut bits = BitBoard {
+            cells: [[0; N]; N],
+            rows: [0; N],
+            columns: [0; N],
+            subgrids: [0; N],
+            subgrid_of: *regions,
+            empty: [(1 << N) - 1; N],
+        };
+        for (row, values) in values.iter().enumerate() {
+            for (column, &value) in values.iter().enumerate() {
+                if value != 0 {
+                    bits.update_cell(&Index(row, column), value);
+                }
+            }
+        }
+        bits
+    }
+    /// Get the subgrid number of a cell, counting left to right, top to bottom
+    fn subgrid(&self, index: &Index) -> usize {
+        self.subgrid_of[index.0][index.1] as usize
+    }
+    /// Update the value of a cell, 0 clears it
+    pub(crate) fn update_cell(&mut self, index: &Index, value: u8) {
+        let subgrid = self.subgrid(index);
+        let old = self.cells[index.0][index.1];
+        if old != 0 {
+            let bit = !(1 << old);
+            self.rows[index.0] &= bit;
+            self.columns[index.1] &= bit;
+            self.subgrids[subgrid] &= bit;
+        }
+        if value != 0 {
+            let bit = 1 << value;
+            self.rows[index.0] |= bit;
+            self.columns[index.1] |= bit;
+            self.subgrids[subgrid] |= bit;
+        }
+        self.cells[index.0][index.1] = value;
+        let cell = 1 << index.1;
+        if value == 0 {
+            self.empty[index.0] |= cell;
+        } else {
+            self.empty[index.0] &= !cell;
+        }
+    }
+    /// Get the empty cells, in row order
+    pub(crate) fn empty_cells(&self) -> EmptyCells<N> {
+        EmptyCells {
+            rows: self.empty,
+            row: 0,
+        }
+    }
+    /// Get the values that can go in the cell without breaking the rules, as a mask
+    fn free_values(&self, index: &Index) -> u32 {
+        Self::ALL_VALUES
+            & !(self.rows[index.0] | self.columns[index.1] | self.subgrids[self.subgrid(index)])
+    }
+    /// Get the values accepted by the filter that can go in the cell without breaking the rules
+    pub(crate) fn candidates(&self, index: &Index, filter: &dyn Fn(&Index, u8) -> bool) -> Vec<u8> {
+        let free = self.free_values(index);
+        (1..=N as u8)
+            .filter(|x| free & (1 << x) != 0 && filter(index, *x))
+            .collect()
+    }
+    /// Get the empty cell to branch on with the given strategy,
+    /// along with its candidates accepted by the filter
+    pub(crate) fn branch_cell(
+        &self,
+        filter: &dyn Fn(&Index, u8) -> bool,
+        selection: CellSelection,
+    ) -> Option<(Index, Vec<u8>)> {
+        let mut best: Option<(Index, Vec<u8>)> = None;
+        for index in self.empty_cells() {
+            let candidates = self.candidates(&index, filter);
+            if selection == CellSelection::FirstEmpty || candidates.len() <= 1 {
+                // can't do better than a forced cell or a dead end
+                return Some((index, candidates));
+            }
+            if best
+                .as_ref()
+                .is_none_or(|(_, fewest)| candidates.len() < fewest.len())
+            {
+                best = Some((index, candidates));
+            }
+        }
+        best
+    }
+}
+
+impl From<&Board> for BitBoard {
+    fn from(board: &Board) -> Self {
+        BitBoard::new(&board.digits(), &boxes())
+    }
+}
+
+impl From<&BitBoard> for Board {
+    fn from(bits: &BitBoard) -> Self {
+        Board::from_digits(&bits.cells)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::solve;
+    use crate::fixtures::wikipedia_board;
+
+    #[test]
+    fn test_conversion_round_trip() {
+        let board = wikipedia_board();
+        let bits = BitBoard::from(&board);
+        assert_eq!(Board::from(&bits), board);
+    }
+
+    #[test]
+    fn test_candidates() {
+        let board = wikipedia_board();
+        let mut bits = BitBoard::from(&board);
+        assert_eq!(bits.candidates(&Index(0, 2), &|_, _| true), vec![1, 2, 4]);
+        assert_eq!(bits.candidates(&Index(0, 2), &|_, x| x != 2), vec![1, 4]);
+
+        bits.update_cell(&Index(0, 2), 4);
+        assert_eq!(bits.cells[0][2], 4);
+        assert_eq!(bits.candidates(&Index(0, 3), &|_, _| true), vec![2, 6]);
+        bits.update_cell(&Index(0, 2), 0);
+        assert_eq!(bits, BitBoard::from(&board));
+    }
+
+    #[test]
+    fn test_empty_cells() {
+        let board = wikipedia_board();
+        let mut bits = BitBoard::from(&board);
+        assert_eq!(bits.empty_cells().count(), 81 - 30);
+        assert_eq!(bits.empty_cells().next(), Some(Index(0, 2)));
+        assert_eq!(bits.empty_cells().last(), Some(Index(8, 6)));
+        bits.update_cell(&Index(0, 2), 4);
+        assert_eq!(bits.empty_cells().next(), Some(Index(0, 3)));
+        bits.update_cell(&Index(0, 2), 0);
+        assert_eq!(bits.empty_cells().next(), Some(Index(0, 2)));
+
+        let solved = BitBoard::from(&solve(&mut wikipedia_board()).unwrap());
+        assert_eq!(solved.empty_cells().next(), None);
+    }
+}
@@ -1,74 +1,252 @@
 //! This module contains the board struct and associated functions
-//! The board struct is a 9x9 array of options
-//! Empty cells are represented by None
-//! Filled cells are represented by Some(u8)
+//! The board struct is a 9x9 array of cells
+//! Each cell is empty, a given clue of the puzzle, or filled in while solving,
+//! so the clues can be told apart from the solved digits after solving
 //!
 //! The module also contains a function to solve the board by backtracking
 
+use crate::bitboard::BitBoard;
+use crate::constraint::{self, Constraint};
+use crate::parity::Parities;
+use crate::sized::{boxes, first_conflict};
 use std::fmt::Display;
-#[derive(Clone, Eq, PartialEq, Debug)]
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+/// Boards are compared by their digits only, so a solved board equals the same grid
+/// read from a file even though its clues and solved cells differ
+/// Boards are ordered by their cells read row by row, with empty cells sorting before digits
+/// This matches comparing their 81 character representations lexicographically
+#[derive(Clone, Debug)]
 pub struct Board {
-    board: [[Option<u8>; 9]; 9],
+    board: [[Cell; 9]; 9],
+    /// Bit 9 * row + column is set if the cell is empty
+    empty: u128,
+}
+
+/// The contents of a cell of a board
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, Default)]
+pub enum Cell {
+    #[default]
+    Empty,
+    /// A clue of the puzzle
+    Given(u8),
+    /// A digit placed while solving
+    Filled(u8),
+}
+
+impl Cell {
+    /// Get the digit in the cell, whether given or filled
+    pub fn value(&self) -> Option<u8> {
+        match *self {
+            Cell::Empty => None,
+            Cell::Given(x) | Cell::Filled(x) => Some(x),
+        }
+    }
+    /// Check if the cell is a clue of the puzzle
+    pub fn is_given(&self) -> bool {
+        matches!(self, Cell::Given(_))
+    }
+}
+/// The position of a cell, as a 0-based row and column
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub struct Index(pub(crate) usize, pub(crate) usize);
+
+impl Index {
+    /// Make the index of the cell at a 0-based row and column
+    /// # Errors
+    /// Returns BoardError::OutOfBounds if the row or column is over 8
+    pub fn new(row: usize, column: usize) -> Result<Index, BoardError> {
+        if row > 8 || column > 8 {
+            return Err(BoardError::OutOfBounds { row, column });
+        }
+        Ok(Index(row, column))
+    }
+    /// Get the 0-based row of the cell
+    pub fn row(&self) -> usize {
+        self.0
+    }
+    /// Get the 0-based column of the cell
+    pub fn column(&self) -> usize {
+        self.1
+    }
+    /// Read an index in the 1-based notation it is displayed in, eg r1c1
+    pub(crate) fn from_r1c1(cell: &str) -> Option<Index> {
+        let cell = cell.to_ascii_lowercase();
+        let (row, column) = cell.strip_prefix('r')?.split_once('c')?;
+        let (row, column): (usize, usize) = (row.parse().ok()?, column.parse().ok()?);
+        Index::new(row.checked_sub(1)?, column.checked_sub(1)?).ok()
+    }
+    /// Pair two cells sharing an edge, such as the cells either side of a kropki dot or XV mark
+    /// Returns the reason if the cells aren't orthogonally adjacent
+    pub(crate) fn edge(first: Index, second: Index) -> Result<[Index; 2], String> {
+        if first.0.abs_diff(second.0) + first.1.abs_diff(second.1) != 1 {
+            return Err(format!(
+                "{} and {} aren't next to each other",
+                first, second
+            ));
+        }
+        Ok([first, second])
+    }
+}
+
+/// A cheap copy of the cells of a board, used to restore it later (eg for undo)
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub struct Snapshot {
+    board: [[Cell; 9]; 9],
+}
+
+/// A group of 9 cells that must contain each digit once
+/// Subgrids are numbered 0-8 row by row from the top left
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum Unit {
+    Row(usize),
+    Column(usize),
+    Subgrid(usize),
+}
+
+impl Unit {
+    /// Get the indices of the cells in the unit, in the order get_unit returns them
+    pub fn cells(&self) -> [Index; 9] {
+        let mut cells = [Index(0, 0); 9];
+        for (k, cell) in cells.iter_mut().enumerate() {
+            *cell = match *self {
+                Unit::Row(row) => Index(row, k),
+                Unit::Column(column) => Index(k, column),
+                Unit::Subgrid(subgrid) => Index(subgrid / 3 * 3 + k / 3, subgrid % 3 * 3 + k % 3),
+            };
+        }
+        cells
+    }
 }
-pub struct Index(usize, usize);
 
 impl Board {
     /// Create a new board from a 9x9 array of u8
     /// 0 represents an empty cell
-    /// Any other number represents a filled cell
-    /// # Example
-    /// ```
-    /// use sudoku_solver_by_roy::board::{Board, Index};
-    /// let board = Board::new(&[
-    /// [0, 0, 3, 4, 0, 7, 0, 6, 0],
-    /// [7, 0, 0, 0, 0, 0, 0, 4, 0],
-    /// [0, 0, 0, 0, 1, 0, 2, 5, 0],
-    /// [4, 8, 0, 3, 0, 0, 1, 0, 0],
-    /// [0, 5, 0, 0, 0, 0, 0, 0, 2],
-    /// [0, 6, 0, 0, 2, 0, 0, 0, 0],
-    /// [0, 9, 0, 1, 0, 5, 0, 0, 8],
-    /// [1, 0, 0, 6, 0, 0, 0, 0, 5],
-    /// [0, 0, 0, 0, 0, 0, 4, 0, 0],
-    /// ]);
-    ///
-    /// ```
+    /// Any other number represents a given clue
     /// # Panics
-    /// 1. Array not 9x9.
-    /// 2. Array contains numbers other than 0-9.
-    /// 3. Array contains non integers.
+    /// Panics if the array contains numbers over 9.
+    /// The sudoku rules are not checked
+    #[deprecated(
+        note = "use Board::try_new or Board::try_from, which return an error instead of panicking"
+    )]
     pub fn new(board: &[[u8; 9]; 9]) -> Self {
-        // convert to options
-        let mut new_board = [[None; 9]; 9];
+        assert!(
+            board.iter().flatten().all(|cell| *cell <= 9),
+            "cells must be numbers from 0 to 9"
+        );
+        Board::from_digits(board)
+    }
+    /// Create a board from a 9x9 array of digits, with 0 for empty cells, without any checks
+    pub(crate) fn from_digits(board: &[[u8; 9]; 9]) -> Self {
+        // convert to cells
+        let mut new_board = [[Cell::Empty; 9]; 9];
         for (i, row) in board.iter().enumerate() {
             for (j, cell) in row.iter().enumerate() {
                 new_board[i][j] = match cell {
-                    0 => None,
-                    _ => Some(*cell),
+                    0 => Cell::Empty,
+                    _ => Cell::Given(*cell),
                 };
             }
         }
-        Board { board: new_board }
+        Board {
+            empty: empty_cells(&new_board),
+            board: new_board,
+        }
     }
     /// Get the value of a cell at a given index
-    fn get_cell(&self, index: &Index) -> Option<u8> {
-        self.board[index.0][index.1]
+    pub(crate) fn get_cell(&self, index: &Index) -> Option<u8> {
+        self.board[index.0][index.1].value()
     }
-    /// Update the value of a cell at a given index
-    fn update_cell(&mut self, index: &Index, value: u8) {
+    /// Update the value of a cell at a given index, marking it as filled
+    pub(crate) fn update_cell(&mut self, index: &Index, value: u8) {
+        let bit = 1 << (9 * index.0 + index.1);
         self.board[index.0][index.1] = match value {
-            0 => None,
-            _ => Some(value),
+            0 => {
+                self.empty |= bit;
+                Cell::Empty
+            }
+            _ => {
+                self.empty &= !bit;
+                Cell::Filled(value)
+            }
         };
     }
+    /// Get the digit at a 0-based row and column, or None if the cell is empty
+    /// # Errors
+    /// Returns BoardError::OutOfBounds if the row or column is over 8
+    pub fn get(&self, row: usize, column: usize) -> Result<Option<u8>, BoardError> {
+        Ok(self.get_cell(&Index::new(row, column)?))
+    }
+    /// Fill the cell at a 0-based row and column with a digit, or clear it with 0
+    /// The sudoku rules are not checked, so the board can be filled in any order
+    /// # Example
+    /// ```
+    /// use sudoku_solver_by_roy::board::{Board, BoardError};
+    /// let mut board = Board::try_new(&[[0; 9]; 9]).unwrap();
+    /// board.set(0, 0, 5).unwrap();
+    /// assert_eq!(board.get(0, 0), Ok(Some(5)));
+    /// assert_eq!(board.set(0, 9, 5), Err(BoardError::OutOfBounds { row: 0, column: 9 }));
+    /// ```
+    /// # Errors
+    /// 1. BoardError::OutOfBounds if the row or column is over 8
+    /// 2. BoardError::InvalidValue if the value is over 9
+    /// 3. BoardError::GivenCell if the cell is a clue of the puzzle
+    pub fn set(&mut self, row: usize, column: usize, value: u8) -> Result<(), BoardError> {
+        let index = Index::new(row, column)?;
+        if value > 9 {
+            return Err(BoardError::InvalidValue(value));
+        }
+        if self.cell(&index).is_given() {
+            return Err(BoardError::GivenCell(index));
+        }
+        self.update_cell(&index, value);
+        Ok(())
+    }
+    /// Get the cell at a given index, telling clues apart from solved digits
+    pub fn cell(&self, index: &Index) -> Cell {
+        self.board[index.0][index.1]
+    }
+    /// Get the board holding only the clues, undoing everything filled in since
+    pub fn clues(&self) -> Board {
+        let mut clues = self.clone();
+        for cell in clues.board.iter_mut().flatten() {
+            if !cell.is_given() {
+                *cell = Cell::Empty;
+            }
+        }
+        clues.empty = empty_cells(&clues.board);
+        clues
+    }
+    /// Get a copy of the board with every digit marked as a clue
+    /// Used to turn a partly solved board into a new puzzle
+    pub fn as_puzzle(&self) -> Board {
+        let mut puzzle = self.clone();
+        for cell in puzzle.board.iter_mut().flatten() {
+            if let Cell::Filled(x) = *cell {
+                *cell = Cell::Given(x);
+            }
+        }
+        puzzle
+    }
+    /// Get the digits of every cell, with 0 for empty cells
+    pub(crate) fn digits(&self) -> [[u8; 9]; 9] {
+        self.board
+            .map(|row| row.map(|cell| cell.value().unwrap_or(0)))
+    }
+    /// Get the digits of every cell, ignoring whether they were given or filled
+    fn values(&self) -> [[Option<u8>; 9]; 9] {
+        self.board.map(|row| row.map(|cell| cell.value()))
+    }
     /// Get a row of the board
     fn get_row(&self, row: usize) -> [Option<u8>; 9] {
-        self.board[row]
+        self.board[row].map(|cell| cell.value())
     }
     /// Get a column of the board
     fn get_column(&self, column: usize) -> [Option<u8>; 9] {
         let mut column_array = [None; 9];
         for (i, row) in self.board.iter().enumerate() {
-            column_array[i] = row[column];
+            column_array[i] = row[column].value();
         }
         column_array
     }
@@ -84,153 +262,1274 @@ impl Board {
         }
         subgrid
     }
-    /// Get the possible valid entries for a given index
-    fn valid_entries(&self, index: &Index) -> [bool; 9] {
-        let mut possible_entries = [true; 9];
-        self.get_row(index.0).iter().for_each(|x| match x {
-            None => {}
-            Some(x) => possible_entries[(x - 1) as usize] = false,
-        });
-
-        self.get_column(index.1).iter().for_each(|x| match x {
-            None => {}
-            Some(x) => possible_entries[(x - 1) as usize] = false,
-        });
-
-        self.get_subgrid(index).iter().for_each(|x| match x {
-            None => {}
-            Some(x) => possible_entries[(x - 1) as usize] = false,
-        });
-
-        possible_entries
+    /// Get the contents of a row, column or subgrid
+    fn get_unit(&self, unit: Unit) -> [Option<u8>; 9] {
+        match unit {
+            Unit::Row(row) => self.get_row(row),
+            Unit::Column(column) => self.get_column(column),
+            Unit::Subgrid(subgrid) => self.get_subgrid(&Index(subgrid / 3 * 3, subgrid % 3 * 3)),
+        }
+    }
+    /// Iterate over all 27 units (rows, then columns, then subgrids) with their contents
+    pub fn units(&self) -> impl Iterator<Item = (Unit, [Option<u8>; 9])> + '_ {
+        (0..9)
+            .map(Unit::Row)
+            .chain((0..9).map(Unit::Column))
+            .chain((0..9).map(Unit::Subgrid))
+            .map(|unit| (unit, self.get_unit(unit)))
+    }
+    /// Iterate over all 81 cells row by row, with their positions and values
+    pub fn cells(&self) -> impl Iterator<Item = (Index, Option<u8>)> + '_ {
+        (0..81).map(|x| {
+            let index = Index(x / 9, x % 9);
+            (index, self.get_cell(&index))
+        })
+    }
+    /// Iterate over the rows from top to bottom, with the positions and values of their cells
+    pub fn rows(&self) -> impl Iterator<Item = [(Index, Option<u8>); 9]> + '_ {
+        (0..9).map(|row| self.unit_cells(Unit::Row(row)))
+    }
+    /// Iterate over the columns from left to right, with the positions and values of their cells
+    pub fn columns(&self) -> impl Iterator<Item = [(Index, Option<u8>); 9]> + '_ {
+        (0..9).map(|column| self.unit_cells(Unit::Column(column)))
+    }
+    /// Iterate over the 3x3 boxes row by row from the top left,
+    /// with the positions and values of their cells
+    /// # Example
+    /// ```
+    /// use sudoku_solver_by_roy::io::example_puzzle;
+    /// let board = example_puzzle("easy").unwrap();
+    /// let clues_per_box: Vec<usize> = board
+    ///     .boxes()
+    ///     .map(|cells| cells.iter().filter(|(_, value)| value.is_some()).count())
+    ///     .collect();
+    /// assert_eq!(clues_per_box, vec![5, 6, 6, 3, 2, 4, 4, 4, 2]);
+    /// ```
+    pub fn boxes(&self) -> impl Iterator<Item = [(Index, Option<u8>); 9]> + '_ {
+        (0..9).map(|subgrid| self.unit_cells(Unit::Subgrid(subgrid)))
     }
-    /// Check if a given entry is valid
+    /// Get the positions and values of the cells of a unit
+    fn unit_cells(&self, unit: Unit) -> [(Index, Option<u8>); 9] {
+        unit.cells().map(|index| (index, self.get_cell(&index)))
+    }
+    /// Get the digits 1-9 that are not yet present in a unit
+    pub fn missing_in_unit(&self, unit: Unit) -> Vec<u8> {
+        let present = self.get_unit(unit);
+        (1..=9).filter(|x| !present.contains(&Some(*x))).collect()
+    }
+    /// Get the 20 peers of a given index
+    /// Peers are the other cells sharing a row, column or 3x3 subgrid with the index
+    pub fn peers(index: Index) -> [Index; 20] {
+        let mut peers = [Index(0, 0); 20];
+        let mut count = 0;
+        for i in 0..9 {
+            for j in 0..9 {
+                let same_row = i == index.0;
+                let same_column = j == index.1;
+                let same_subgrid = i / 3 == index.0 / 3 && j / 3 == index.1 / 3;
+                if (same_row && same_column) || !(same_row || same_column || same_subgrid) {
+                    continue;
+                }
+                peers[count] = Index(i, j);
+                count += 1;
+            }
+        }
+        peers
+    }
+    /// Check if a given entry is valid, ie no peer holds the same digit
     fn is_valid_entry(&self, index: &Index) -> bool {
         let element = self.get_cell(index);
         if element.is_none() {
             // you can have none anywhere
             return true;
         }
-        let elements = [
-            self.get_row(index.0),
-            self.get_column(index.1),
-            self.get_subgrid(index),
-        ]
-        .concat();
-
-        if elements.iter().filter(|x| *x == &element).count() > 3 {
-            return false;
+        Board::peers(*index)
+            .iter()
+            .all(|peer| self.get_cell(peer) != element)
+    }
+    /// Get the first cell, in row order, that shares its value with a peer
+    pub(crate) fn first_conflict(&self) -> Option<Index> {
+        (0..81)
+            .map(|x| Index(x / 9, x % 9))
+            .find(|index| !self.is_valid_entry(index))
+    }
+    /// Get every pair of cells that hold the same digit while sharing a row, column or box
+    /// Each pair is listed once, earlier cell first, in row order of the earlier cell
+    /// # Example
+    /// ```
+    /// use sudoku_solver_by_roy::board::Board;
+    /// let mut board = Board::try_new(&[[0; 9]; 9]).unwrap();
+    /// board.set(0, 0, 4).unwrap();
+    /// board.set(0, 8, 4).unwrap();
+    /// board.set(2, 2, 4).unwrap();
+    /// let conflicts = board.conflicts();
+    /// assert_eq!(conflicts.len(), 2);
+    /// assert_eq!(conflicts[0].0.to_string(), "r1c1");
+    /// assert_eq!(conflicts[0].1.to_string(), "r1c9");
+    /// ```
+    pub fn conflicts(&self) -> Vec<(Index, Index)> {
+        let mut conflicts = Vec::new();
+        for (index, value) in self.cells() {
+            if value.is_none() {
+                continue;
+            }
+            let mut peers: Vec<Index> = Board::peers(index)
+                .into_iter()
+                .filter(|peer| (peer.0, peer.1) > (index.0, index.1))
+                .filter(|peer| self.get_cell(peer) == value)
+                .collect();
+            peers.sort_by_key(|peer| (peer.0, peer.1));
+            conflicts.extend(peers.into_iter().map(|peer| (index, peer)));
         }
-        return true;
+        conflicts
     }
-    /// Check if the board is valid
+    /// Check if the board is valid, ie no digit repeats within a row, column or subgrid
     pub fn is_valid_board(&self) -> bool {
+        self.units().all(|(_, cells)| {
+            let mut seen: u16 = 0;
+            cells.iter().flatten().all(|x| {
+                let bit = 1 << x;
+                let first = seen & bit == 0;
+                seen |= bit;
+                first
+            })
+        })
+    }
+    /// Check if no digit repeats within a unit and the constraints added by the variant hold
+    pub fn is_valid_for(&self, variant: Variant) -> bool {
+        self.is_valid_board()
+            && variant
+                .constraints()
+                .iter()
+                .all(|constraint| constraint.is_satisfied(self))
+    }
+    /// Check if no digit repeats within a row or within a column
+    /// Subgrids are ignored, as some puzzle types relax the box rule
+    pub fn is_latin_square(&self) -> bool {
+        self.units()
+            .filter(|(unit, _)| !matches!(unit, Unit::Subgrid(_)))
+            .all(|(_, cells)| {
+                let mut seen = [false; 9];
+                cells.iter().flatten().all(|x| {
+                    let first = !seen[(x - 1) as usize];
+                    seen[(x - 1) as usize] = true;
+                    first
+                })
+            })
+    }
+    /// Check if the board is complete
+    pub(crate) fn is_complete(&self) -> bool {
+        self.empty == 0 && self.is_valid_board()
+    }
+    /// Get the board as a single line of 81 characters read row by row
+    /// Empty cells are written as `.`
+    pub fn to_line(&self) -> String {
+        self.to_line_with('.')
+    }
+    /// Get the board as a single line of 81 characters, writing empty cells as the given character
+    pub fn to_line_with(&self, empty: char) -> String {
+        self.board
+            .iter()
+            .flatten()
+            .map(|cell| match cell.value() {
+                Some(x) => char::from(b'0' + x),
+                None => empty,
+            })
+            .collect()
+    }
+    /// Get the board as a grid drawn with box-drawing characters
+    /// Heavy lines separate the 3x3 boxes and light lines separate the cells
+    /// # Example
+    /// ```
+    /// use sudoku_solver_by_roy::io::example_puzzle;
+    /// let pretty = example_puzzle("easy").unwrap().pretty();
+    /// assert!(pretty.starts_with("┏━━━┯━━━┯━━━┳"));
+    /// assert_eq!(pretty.lines().count(), 19);
+    /// ```
+    pub fn pretty(&self) -> String {
+        self.pretty_with(plain_cell)
+    }
+    /// Get the board as the grid printed by Display, with the clues
+    /// and the solved cells in different colors, using ANSI escape codes
+    pub fn colored(&self) -> String {
+        self.grid_with(colored_cell)
+    }
+    /// Get the board as the grid drawn by pretty, with the clues
+    /// and the solved cells in different colors, using ANSI escape codes
+    pub fn pretty_colored(&self) -> String {
+        self.pretty_with(colored_cell)
+    }
+    /// Draw the board with dashes and bars, rendering each cell with the given function
+    fn grid_with(&self, render: impl Fn(Cell) -> String) -> String {
+        let mut board_string = String::new();
         for i in 0..9 {
+            if i % 3 == 0 {
+                board_string.push_str("-------------------------\n");
+            }
             for j in 0..9 {
-                if !self.is_valid_entry(&Index(i, j)) {
-                    return false;
+                if j % 3 == 0 {
+                    board_string.push_str("| ");
                 }
+                board_string.push_str(&render(self.board[i][j]));
+                board_string.push(' ');
             }
+            board_string.push_str("|\n");
         }
-        return true;
+        board_string.push_str("-------------------------\n");
+        board_string
     }
-    /// Check if the board is complete
-    fn is_complete(&self) -> bool {
-        self.is_valid_board() && (self.next_empty().is_none())
+    /// Draw the board with box-drawing characters, rendering each cell with the given function
+    fn pretty_with(&self, render: impl Fn(Cell) -> String) -> String {
+        // left end, fill, light joint, heavy joint and right end of each kind of horizontal line
+        let border = |[left, fill, light, heavy, right]: [char; 5]| {
+            let mut line = String::new();
+            line.push(left);
+            for j in 0..9 {
+                line.extend([fill; 3]);
+                line.push(match j {
+                    8 => right,
+                    2 | 5 => heavy,
+                    _ => light,
+                });
+            }
+            line.push('\n');
+            line
+        };
+        let mut pretty = border(['┏', '━', '┯', '┳', '┓']);
+        for i in 0..9 {
+            pretty.push('┃');
+            for j in 0..9 {
+                pretty.push(' ');
+                pretty.push_str(&render(self.board[i][j]));
+                pretty.push(' ');
+                pretty.push(if j % 3 == 2 { '┃' } else { '│' });
+            }
+            pretty.push('\n');
+            pretty.push_str(&match i {
+                8 => border(['┗', '━', '┷', '┻', '┛']),
+                2 | 5 => border(['┣', '━', '┿', '╋', '┫']),
+                _ => border(['┠', '─', '┼', '╂', '┨']),
+            });
+        }
+        pretty
     }
-    /// Get the next empty cell
-    fn next_empty(&self) -> Option<Index> {
+    /// Get the cells that differ between this board and another
+    /// Each entry holds the index along with the value in self and in other
+    pub fn diff(&self, other: &Board) -> Vec<(Index, Option<u8>, Option<u8>)> {
+        let mut differences = Vec::new();
+        for i in 0..9 {
+            for j in 0..9 {
+                let index = Index(i, j);
+                let (ours, theirs) = (self.get_cell(&index), other.get_cell(&index));
+                if ours != theirs {
+                    differences.push((index, ours, theirs));
+                }
+            }
+        }
+        differences
+    }
+    /// Estimate how hard the board is by running a search capped at a thousand nodes
+    /// This is much cheaper than solving when sorting large collections of puzzles
+    pub fn estimated_hardness(&self) -> Hardness {
+        let mut search = Search::new().with_node_limit(HARDNESS_NODE_CAP);
+        search.run(&mut self.clone());
+        let nodes = search.nodes;
+        if nodes > HARDNESS_NODE_CAP {
+            Hardness::Exceeds(HARDNESS_NODE_CAP)
+        } else if nodes > TRIVIAL_NODE_LIMIT {
+            Hardness::Moderate
+        } else {
+            Hardness::Trivial
+        }
+    }
+    /// Get the boards made by filling the cell with the fewest candidates with each of them
+    /// Used to split a search into independent branches
+    #[cfg(feature = "rayon")]
+    pub(crate) fn branches(&self) -> Vec<Board> {
+        let Some((next_empty, candidates)) = self.next_branch_cell() else {
+            return Vec::new();
+        };
+        candidates
+            .into_iter()
+            .map(|x| {
+                let mut branch = self.clone();
+                branch.update_cell(&next_empty, x);
+                branch
+            })
+            .collect()
+    }
+    /// Check that every filled cell of this board matches the same cell of the solution
+    /// Empty cells are ignored, so a partially solved board agrees with its solution
+    pub fn agrees_with(&self, solution: &Board) -> bool {
+        self.diff(solution)
+            .iter()
+            .all(|(_, ours, _)| ours.is_none())
+    }
+    /// Check that this board is a complete valid grid keeping every digit of the original
+    /// Useful as a sanity check on solutions coming from a solver or a user
+    pub fn is_solution_of(&self, original: &Board) -> bool {
+        self.is_complete() && original.agrees_with(self)
+    }
+    /// Get the complement puzzle, where the current clues are emptied and the
+    /// empty cells hold their solved values as the clues of the new puzzle
+    /// Returns None if the board has no solution
+    pub fn invert_clues(&self) -> Option<Board> {
+        let solution = Search::new().run(&mut self.clone())?;
+        let mut inverted = solution.clone();
         for (i, row) in self.board.iter().enumerate() {
             for (j, cell) in row.iter().enumerate() {
-                if cell == &None {
-                    return Some(Index(i, j));
+                if *cell != Cell::Empty {
+                    inverted.update_cell(&Index(i, j), 0);
                 }
             }
         }
-        return None;
+        Some(inverted.as_puzzle())
+    }
+    /// Make the board satisfy the sudoku rules by clearing conflicting cells
+    /// Cells are scanned row by row, and a cell is cleared if an earlier peer holds the same value
+    /// Returns the cleared cells in the order they were cleared
+    pub fn repair(&mut self) -> Vec<Index> {
+        let mut cleared = Vec::new();
+        for i in 0..9 {
+            for j in 0..9 {
+                let index = Index(i, j);
+                let Some(value) = self.get_cell(&index) else {
+                    continue;
+                };
+                let conflicts = Board::peers(index)
+                    .iter()
+                    .any(|peer| (peer.0, peer.1) < (i, j) && self.get_cell(peer) == Some(value));
+                if conflicts {
+                    self.update_cell(&index, 0);
+                    cleared.push(index);
+                }
+            }
+        }
+        cleared
+    }
+    /// Capture the state of every cell
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot { board: self.board }
+    }
+    /// Restore every cell to the state captured in a snapshot
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.board = snapshot.board;
+        self.empty = empty_cells(&self.board);
+    }
+    /// Get the solution of the board, leaving the board itself unchanged
+    /// Returns None if the board has no solution
+    /// # Example
+    /// ```
+    /// use sudoku_solver_by_roy::io::example_puzzle;
+    /// let puzzle = example_puzzle("easy").unwrap();
+    /// let solution = puzzle.solved().unwrap();
+    /// assert!(puzzle.agrees_with(&solution));
+    /// assert_ne!(puzzle, solution);
+    /// ```
+    pub fn solved(&self) -> Option<Board> {
+        solve(&mut self.clone())
+    }
+    /// Check if the board has exactly one solution
+    /// The search stops at the second solution, so this is much cheaper than counting them all
+    pub fn has_unique_solution(&self) -> bool {
+        count_solutions(self, Some(2)) == 1
+    }
+    /// Iterate over every solution of the board, finding them lazily one at a time
+    /// # Example
+    /// ```
+    /// use sudoku_solver_by_roy::board::Board;
+    /// let board = Board::try_new(&[[0; 9]; 9]).unwrap();
+    /// let first_three: Vec<Board> = board.solutions().take(3).collect();
+    /// assert_eq!(first_three.len(), 3);
+    /// ```
+    pub fn solutions(&self) -> Solutions {
+        let mut search = Search::new();
+        search.start(self);
+        Solutions {
+            board: self.clone(),
+            search,
+        }
+    }
+    /// Get the cell the solver would branch on next, along with its candidates
+    /// The solver picks the empty cell with the fewest candidates (minimum remaining values),
+    /// taking the first in row order on ties. Returns None if the board is full
+    pub fn next_branch_cell(&self) -> Option<(Index, Vec<u8>)> {
+        BitBoard::from(self).branch_cell(&|_, _| true, CellSelection::default())
+    }
+    /// Get the next empty cell
+    fn next_empty(&self) -> Option<Index> {
+        if self.empty == 0 {
+            return None;
+        }
+        let cell = self.empty.trailing_zeros() as usize;
+        Some(Index(cell / 9, cell % 9))
+    }
+}
+
+/// Get the bitset of the empty cells, with bit 9 * row + column set if the cell is empty
+fn empty_cells(board: &[[Cell; 9]; 9]) -> u128 {
+    board
+        .iter()
+        .flatten()
+        .enumerate()
+        .filter(|(_, cell)| **cell == Cell::Empty)
+        .fold(0, |empty, (i, _)| empty | 1 << i)
+}
+
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.values() == other.values()
+    }
+}
+
+impl Eq for Board {}
+
+impl PartialOrd for Board {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Board {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.values().cmp(&other.values())
+    }
+}
+
+impl Display for Index {
+    /// Display the index in 1-based row/column notation, eg r1c1
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "r{}c{}", self.0 + 1, self.1 + 1)
+    }
+}
+
+impl Display for Unit {
+    /// Display the unit in 1-based notation, eg row 1, column 3, box 9
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Unit::Row(row) => write!(f, "row {}", row + 1),
+            Unit::Column(column) => write!(f, "column {}", column + 1),
+            Unit::Subgrid(subgrid) => write!(f, "box {}", subgrid + 1),
+        }
+    }
+}
+
+/// ANSI escape code for the clues of a puzzle: bold blue
+const GIVEN_COLOR: &str = "\x1b[1;34m";
+/// ANSI escape code for the cells filled in by solving: green
+const FILLED_COLOR: &str = "\x1b[32m";
+/// ANSI escape code going back to the default style
+const RESET_COLOR: &str = "\x1b[0m";
+
+/// Render a cell as its digit, or a space if it is empty
+fn plain_cell(cell: Cell) -> String {
+    cell.value()
+        .map_or(' ', |x| char::from(b'0' + x))
+        .to_string()
+}
+
+/// Render a cell in the color of a clue or of a solved cell
+fn colored_cell(cell: Cell) -> String {
+    match cell {
+        Cell::Given(_) => format!("{}{}{}", GIVEN_COLOR, plain_cell(cell), RESET_COLOR),
+        Cell::Filled(_) => format!("{}{}{}", FILLED_COLOR, plain_cell(cell), RESET_COLOR),
+        Cell::Empty => plain_cell(cell),
     }
 }
 
 impl Display for Board {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut board_string = String::new();
-        for (i, row) in self.board.iter().enumerate() {
-            if i % 3 == 0 {
-                board_string.push_str("-------------------------\n");
+        write!(f, "{}", self.grid_with(plain_cell))
+    }
+}
+
+/// Errors from operations on a board
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum BoardError {
+    /// The board is not a complete grid satisfying the sudoku rules
+    NotASolution,
+    /// The solve ran past its time limit
+    TimedOut,
+    /// The solve was cancelled through its cancel token
+    Cancelled,
+    /// A 0-based row or column is past the edge of the board
+    OutOfBounds { row: usize, column: usize },
+    /// A cell was set to a number too large for the board
+    InvalidValue(u8),
+    /// A clue of the puzzle was about to be overwritten
+    GivenCell(Index),
+}
+
+impl Display for BoardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BoardError::NotASolution => write!(f, "Board is not a complete valid solution"),
+            BoardError::TimedOut => write!(f, "Solve ran out of time"),
+            BoardError::Cancelled => write!(f, "Solve was cancelled"),
+            BoardError::OutOfBounds { row, column } => write!(
+                f,
+                "Row {}, column {} is outside the board",
+                row + 1,
+                column + 1
+            ),
+            BoardError::InvalidValue(value) => {
+                write!(f, "Invalid value {} is too large for a cell", value)
             }
-            for (j, cell) in row.iter().enumerate() {
-                if j % 3 == 0 {
-                    board_string.push_str("| ");
+            BoardError::GivenCell(index) => write!(f, "{} is a clue and can't be changed", index),
+        }
+    }
+}
+
+impl std::error::Error for BoardError {}
+
+/// Nodes a search may visit and still be considered trivial
+/// Nodes are only counted for guesses, as naked singles are filled in between,
+/// so a moderate puzzle takes a few dozen nodes rather than a few hundred
+const TRIVIAL_NODE_LIMIT: usize = 20;
+/// Nodes a search may visit before estimated_hardness gives up
+/// Hard puzzles still take a few thousand nodes with naked singles filled, so the cap sits below them
+const HARDNESS_NODE_CAP: usize = 1000;
+
+/// Rough classification of how much search a board needs
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum Hardness {
+    /// Finished within twenty nodes
+    Trivial,
+    /// Finished within the node cap
+    Moderate,
+    /// Did not finish within the given node cap
+    Exceeds(usize),
+}
+
+impl Display for Hardness {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Hardness::Trivial => write!(f, "trivial"),
+            Hardness::Moderate => write!(f, "moderate"),
+            Hardness::Exceeds(cap) => write!(f, "hard (over {} nodes)", cap),
+        }
+    }
+}
+
+/// Parity restriction for a cell, as used in odd/even sudoku variants
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum Parity {
+    Odd,
+    Even,
+}
+
+impl Parity {
+    /// Check if a value satisfies the parity
+    pub(crate) fn allows(&self, value: u8) -> bool {
+        match self {
+            Parity::Odd => !value.is_multiple_of(2),
+            Parity::Even => value.is_multiple_of(2),
+        }
+    }
+}
+
+/// Strategy for choosing which empty cell the solver branches on next
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub enum CellSelection {
+    /// The first empty cell in row order
+    FirstEmpty,
+    /// The empty cell with the fewest candidates, which keeps the search tree small
+    #[default]
+    MinimumRemainingValues,
+}
+
+/// The rule set a board is checked and solved under
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, Default)]
+pub enum Variant {
+    /// Each row, column and subgrid holds each digit once
+    #[default]
+    Classic,
+    /// X-Sudoku, where both main diagonals also hold each digit once
+    Diagonal,
+    /// Hyper sudoku or windoku, where four more 3x3 windows also hold each digit once
+    /// The windows are one cell in from the corners, eg rows and columns 2 to 4
+    Hyper,
+    /// Cells a knight's move apart can't hold the same digit
+    AntiKnight,
+    /// Cells a king's move apart, including diagonally, can't hold the same digit
+    AntiKing,
+}
+
+impl Variant {
+    /// Get the constraints the variant adds to rows, columns and boxes
+    pub fn constraints(self) -> Vec<Box<dyn Constraint>> {
+        match self {
+            Variant::Classic => Vec::new(),
+            Variant::Diagonal => vec![Box::new(constraint::Diagonals)],
+            Variant::Hyper => vec![Box::new(constraint::Windows)],
+            Variant::AntiKnight => vec![Box::new(constraint::AntiKnight)],
+            Variant::AntiKing => vec![Box::new(constraint::AntiKing)],
+        }
+    }
+}
+
+/// Solve the board by backtracking
+pub fn solve(board: &mut Board) -> Option<Board> {
+    Search::new().run(board)
+}
+
+/// Solve the board by backtracking, choosing cells to branch on with the given strategy
+pub fn solve_with_selection(board: &mut Board, selection: CellSelection) -> Option<Board> {
+    Search::new().with_selection(selection).run(board)
+}
+
+/// Solve the board by backtracking under the rules of the variant
+/// # Example
+/// ```
+/// use sudoku_solver_by_roy::board::{solve_with_variant, Board, Index, Variant};
+/// let mut board = Board::try_new(&[[0; 9]; 9]).unwrap();
+/// let solved = solve_with_variant(&mut board, Variant::Diagonal).unwrap();
+/// assert!(solved.is_valid_for(Variant::Diagonal));
+/// ```
+pub fn solve_with_variant(board: &mut Board, variant: Variant) -> Option<Board> {
+    Search::new().with_variant(variant).run(board)
+}
+
+/// Solve the board by backtracking, following the constraints on top of rows, columns and boxes
+pub fn solve_with_constraints(board: &mut Board, constraints: &[&dyn Constraint]) -> Option<Board> {
+    constraints
+        .iter()
+        .fold(Search::new(), |search, constraint| {
+            search.with_constraint(*constraint)
+        })
+        .run(board)
+}
+
+/// Shared flag for cancelling a solve from another thread
+/// Clones share the same flag, so keep one and hand a clone to the solver
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Ask every solve using this token to stop
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Limits on a solve, so a long running search can be aborted cleanly
+#[derive(Clone, Debug, Default)]
+pub struct SolverOptions {
+    /// Give up once the solve has run for this long
+    pub time_limit: Option<Duration>,
+    /// Give up once this token is cancelled
+    pub cancel_token: Option<CancelToken>,
+}
+
+/// Solve the board by backtracking, giving up when the options say to
+/// Returns Ok(None) if the board has no solution, and
+/// BoardError::TimedOut or BoardError::Cancelled if the solve was aborted,
+/// in which case the board is left as it was
+pub fn solve_with_options(
+    board: &mut Board,
+    options: &SolverOptions,
+) -> Result<Option<Board>, BoardError> {
+    let mut search = Search::new().with_options(options);
+    let solution = search.run(board);
+    match search.interrupted {
+        Some(reason) => Err(reason),
+        None => Ok(solution),
+    }
+}
+
+/// Statistics about the work done by a solve
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub struct SolveStats {
+    /// Placements expanded by the search
+    pub nodes: usize,
+    /// Cells whose candidates all failed, so the search had to back up past them
+    pub backtracks: usize,
+    /// Cells filled by propagating naked singles, counting refills after backtracking
+    pub propagation_fills: usize,
+    /// Wall clock time taken by the solve
+    pub elapsed: Duration,
+}
+
+/// Solve the board by backtracking, also reporting how much work the solve took
+/// Useful for measuring the difficulty of puzzles empirically
+pub fn solve_with_stats(board: &mut Board) -> (Option<Board>, SolveStats) {
+    let start = Instant::now();
+    let mut search = Search::new();
+    let solution = search.run(board);
+    let stats = SolveStats {
+        nodes: search.nodes,
+        backtracks: search.backtracks,
+        propagation_fills: search.fills,
+        elapsed: start.elapsed(),
+    };
+    (solution, stats)
+}
+
+/// Solve the board by backtracking, calling on_step after every placement
+/// The callback receives the cell, the value placed and whether it was a guess between candidates
+/// Cells cleared while backtracking are reported with the value 0
+pub fn solve_with_callback(
+    board: &mut Board,
+    on_step: impl FnMut(&Index, u8, bool),
+) -> Option<Board> {
+    Search::new().on_step(on_step).run(board)
+}
+
+/// Solve the board by backtracking, restricting the marked cells to odd or even digits
+/// Returns None if a given clue already breaks its parity restriction
+pub fn solve_with_parity(board: &mut Board, parity: &[(Index, Parity)]) -> Option<Board> {
+    Search::new()
+        .with_constraint(Parities::new(parity))
+        .run(board)
+}
+
+/// A single placement made while solving
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub struct Step {
+    pub index: Index,
+    /// The placed value, 0 when the cell was cleared while backtracking
+    pub value: u8,
+    /// Whether the value was a guess between several candidates
+    pub is_guess: bool,
+}
+
+/// Solve a copy of the board by backtracking and record every step taken
+/// Replaying the steps in order on the board gives the solution if there is one
+pub fn solve_steps(board: &Board) -> Vec<Step> {
+    let mut steps = Vec::new();
+    Search::new()
+        .on_step(|index, value, is_guess| {
+            steps.push(Step {
+                index: *index,
+                value,
+                is_guess,
+            })
+        })
+        .run(&mut board.clone());
+    steps
+}
+
+type StepCallback<'a> = Box<dyn FnMut(&Index, u8, bool) + 'a>;
+
+/// An NxN grid of values the search fills in place
+/// Board is the 9x9 grid, and the other sizes share the search through SizedBoard
+pub(crate) trait Grid<const N: usize>: Clone {
+    /// Get the value of every cell, with 0 for empty cells
+    fn digits(&self) -> [[u8; N]; N];
+    /// Set the value of a cell, 0 clears it
+    fn update_cell(&mut self, index: &Index, value: u8);
+}
+
+impl Grid<9> for Board {
+    fn digits(&self) -> [[u8; 9]; 9] {
+        Board::digits(self)
+    }
+    fn update_cell(&mut self, index: &Index, value: u8) {
+        Board::update_cell(self, index, value)
+    }
+}
+
+/// A rule the search checks on every placement, besides the units kept by its bits
+/// Every constraint is a rule of a Board, while the other sizes only have their units
+pub(crate) trait Rule<G> {
+    /// Check whether the value can go in the cell
+    fn allows_in(&self, grid: &G, index: &Index, value: u8) -> bool;
+    /// Check that no filled cell of the grid breaks the rule
+    fn holds_in(&self, grid: &G) -> bool;
+}
+
+impl<C: Constraint + ?Sized> Rule<Board> for C {
+    fn allows_in(&self, board: &Board, index: &Index, value: u8) -> bool {
+        self.allows(board, index, value)
+    }
+    fn holds_in(&self, board: &Board) -> bool {
+        self.is_satisfied(board)
+    }
+}
+
+/// Settings, counters and state for a single backtracking search
+/// The search is a depth first search with an explicit stack rather than recursion,
+/// so near empty boards can't overflow the call stack
+/// It searches a 9x9 Board unless given another size of grid
+pub(crate) struct Search<'a, const N: usize = 9, G = Board> {
+    /// Called with each placement and whether it was a guess between several candidates
+    /// Cells cleared while backtracking are reported with the value 0
+    on_step: StepCallback<'a>,
+    /// Nodes visited so far, one per placement expanded
+    pub(crate) nodes: usize,
+    /// Cells whose candidates all failed, so the search had to back up past them
+    pub(crate) backtracks: usize,
+    /// Cells filled by propagation, counting refills after backtracking
+    pub(crate) fills: usize,
+    /// The search gives up once more than node_limit nodes are visited
+    node_limit: usize,
+    /// How the next cell to branch on is chosen
+    selection: CellSelection,
+    /// Constraints placements must follow besides rows, columns and boxes,
+    /// which are kept track of by bits
+    constraints: Vec<Box<dyn Rule<G> + 'a>>,
+    /// The region of each cell kept track of by bits,
+    /// the boxes unless a jigsaw constraint gives its own
+    regions: [[u8; N]; N],
+    /// The search gives up once this instant has passed
+    deadline: Option<Instant>,
+    /// The search gives up once this token is cancelled
+    cancel_token: Option<CancelToken>,
+    /// Why the search gave up early, if it was timed out or cancelled
+    pub(crate) interrupted: Option<BoardError>,
+    /// The cells being branched on
+    stack: Vec<Frame>,
+    /// Cells filled by propagation before the first branch
+    root_forced: Vec<Index>,
+    /// Mirror of the board being searched, kept in step with it for fast candidate lookups
+    bits: BitBoard<N>,
+    /// Whether the last placement should be expanded before trying other candidates
+    descend: bool,
+}
+
+impl<'a> Search<'a> {
+    pub(crate) fn new() -> Self {
+        Search::blank()
+    }
+    pub(crate) fn with_variant(self, variant: Variant) -> Self {
+        variant
+            .constraints()
+            .into_iter()
+            .fold(self, Search::with_constraint)
+    }
+    pub(crate) fn with_constraint(mut self, constraint: impl Constraint + 'a) -> Self {
+        // regions, rows, columns and boxes are kept by the bits,
+        // so they don't need checking on every placement
+        if let Some(regions) = constraint.regions() {
+            self.regions = *regions.map();
+        }
+        if !constraint.is_built_in() {
+            self.constraints.push(Box::new(constraint));
+        }
+        self
+    }
+}
+
+impl<'a, const N: usize, G: Grid<N>> Search<'a, N, G> {
+    /// Make a search of an NxN grid with no limits, constraints or callback
+    pub(crate) fn blank() -> Self {
+        let regions = boxes();
+        Search {
+            on_step: Box::new(|_, _, _| {}),
+            nodes: 0,
+            backtracks: 0,
+            fills: 0,
+            node_limit: usize::MAX,
+            selection: CellSelection::default(),
+            constraints: Vec::new(),
+            regions,
+            deadline: None,
+            cancel_token: None,
+            interrupted: None,
+            stack: Vec::new(),
+            root_forced: Vec::new(),
+            bits: BitBoard::new(&[[0; N]; N], &regions),
+            descend: false,
+        }
+    }
+    pub(crate) fn with_node_limit(mut self, node_limit: usize) -> Self {
+        self.node_limit = node_limit;
+        self
+    }
+    pub(crate) fn with_selection(mut self, selection: CellSelection) -> Self {
+        self.selection = selection;
+        self
+    }
+    pub(crate) fn with_options(mut self, options: &SolverOptions) -> Self {
+        self.deadline = options.time_limit.map(|limit| Instant::now() + limit);
+        self.cancel_token = options.cancel_token.clone();
+        self
+    }
+    pub(crate) fn on_step(mut self, on_step: impl FnMut(&Index, u8, bool) + 'a) -> Self {
+        self.on_step = Box::new(on_step);
+        self
+    }
+    /// Solve the board by backtracking
+    /// On success the board is left holding the solution
+    pub(crate) fn run(&mut self, board: &mut G) -> Option<G> {
+        let mut solution = None;
+        self.explore(board, &mut |solved| {
+            solution = Some(solved.clone());
+            true
+        });
+        solution
+    }
+    /// Visit the solutions of the board one by one until visit returns true
+    /// Returns true if the search was stopped early, either by visit or the node limit
+    pub(crate) fn explore(&mut self, board: &mut G, visit: &mut dyn FnMut(&G) -> bool) -> bool {
+        self.start(board);
+        while self.advance(board) {
+            if visit(board) {
+                return true;
+            }
+        }
+        self.nodes > self.node_limit
+    }
+    /// Reset the search to start from the given board
+    pub(crate) fn start(&mut self, board: &G) {
+        self.stack.clear();
+        self.root_forced.clear();
+        let digits = board.digits();
+        self.bits = BitBoard::new(&digits, &self.regions);
+        self.descend = first_conflict(&digits, &self.regions).is_none()
+            && self
+                .constraints
+                .iter()
+                .all(|constraint| constraint.holds_in(board));
+    }
+    /// Continue the search until the board holds the next solution
+    /// The same board must be passed every time, as the search edits it in place
+    /// Returns false once every solution has been found or the search gives up,
+    /// leaving the board as it was before the search started
+    pub(crate) fn advance(&mut self, board: &mut G) -> bool {
+        loop {
+            if self.descend {
+                self.descend = false;
+                self.nodes += 1;
+                if self.nodes > self.node_limit {
+                    self.unwind(board);
+                    return false;
+                }
+                if let Some(reason) = self.check_interrupt() {
+                    self.interrupted = Some(reason);
+                    self.unwind(board);
+                    return false;
+                }
+                let forced = self.propagate(board);
+                match self.stack.last_mut() {
+                    Some(frame) => frame.forced = forced,
+                    None => self.root_forced = forced,
                 }
-                match cell {
-                    Some(x) => board_string.push_str(&format!("{} ", x)),
-                    None => board_string.push_str("  "),
+                match self.bits.branch_cell(
+                    &|index, value| self.allows(board, index, value),
+                    self.selection,
+                ) {
+                    Some((index, mut candidates)) => {
+                        let is_guess = candidates.len() > 1;
+                        // candidates are popped from the back, so reverse to try them in order
+                        candidates.reverse();
+                        self.stack.push(Frame {
+                            index,
+                            candidates,
+                            is_guess,
+                            forced: Vec::new(),
+                        });
+                    }
+                    None => return true,
+                }
+            }
+            let Some(frame) = self.stack.last_mut() else {
+                for index in std::mem::take(&mut self.root_forced).iter().rev() {
+                    self.place(board, index, 0, false);
+                }
+                return false;
+            };
+            let forced = std::mem::take(&mut frame.forced);
+            let (index, is_guess, next) = (frame.index, frame.is_guess, frame.candidates.pop());
+            // undo whatever the last candidate forced before trying the next one
+            for forced_index in forced.iter().rev() {
+                self.place(board, forced_index, 0, false);
+            }
+            match next {
+                Some(value) => {
+                    self.place(board, &index, value, is_guess);
+                    self.descend = true;
+                }
+                None => {
+                    self.place(board, &index, 0, false);
+                    self.stack.pop();
+                    self.backtracks += 1;
                 }
             }
-            board_string.push_str("|\n");
         }
-        board_string.push_str("-------------------------\n");
-        write!(f, "{}", board_string)
+    }
+    /// Undo every placement still on the stack and everything propagated before the first branch,
+    /// so a search that gives up leaves the board as it started
+    fn unwind(&mut self, board: &mut G) {
+        while let Some(frame) = self.stack.pop() {
+            for index in frame.forced.iter().rev() {
+                self.place(board, index, 0, false);
+            }
+            self.place(board, &frame.index, 0, false);
+        }
+        for index in std::mem::take(&mut self.root_forced).iter().rev() {
+            self.place(board, index, 0, false);
+        }
+    }
+    /// Check whether the search has run out of time or been cancelled
+    fn check_interrupt(&self) -> Option<BoardError> {
+        if self
+            .cancel_token
+            .as_ref()
+            .is_some_and(|token| token.is_cancelled())
+        {
+            Some(BoardError::Cancelled)
+        } else if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            Some(BoardError::TimedOut)
+        } else {
+            None
+        }
+    }
+    /// Check whether the value passes the constraints kept outside the bits
+    fn allows(&self, board: &G, index: &Index, value: u8) -> bool {
+        self.constraints
+            .iter()
+            .all(|constraint| constraint.allows_in(board, index, value))
+    }
+    /// Set a cell on both the board and its mirror, and report the step
+    fn place(&mut self, board: &mut G, index: &Index, value: u8, is_guess: bool) {
+        board.update_cell(index, value);
+        self.bits.update_cell(index, value);
+        (self.on_step)(index, value, is_guess);
+    }
+    /// Fill naked singles, cells with only one candidate left, until none remain
+    /// Stops early at a cell with no candidates, leaving the dead end for branching to find
+    /// Returns the cells filled, in the order they were filled
+    fn propagate(&mut self, board: &mut G) -> Vec<Index> {
+        let mut forced = Vec::new();
+        let mut progress = true;
+        while progress {
+            progress = false;
+            for index in self.bits.empty_cells() {
+                match self
+                    .bits
+                    .candidates(&index, &|index, value| self.allows(board, index, value))[..]
+                {
+                    [] => return forced,
+                    [value] => {
+                        self.place(board, &index, value, false);
+                        forced.push(index);
+                        self.fills += 1;
+                        progress = true;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        forced
     }
 }
 
-/// Solve the board by backtracking
-pub fn solve(board: &mut Board) -> Option<Board> {
-    if board.is_complete() {
-        return Some(board.clone());
+/// A cell being branched on by the search
+struct Frame {
+    index: Index,
+    /// Candidates still left to try, in reverse order
+    candidates: Vec<u8>,
+    /// Whether choosing between the candidates is a guess
+    is_guess: bool,
+    /// Cells filled by propagation after placing the current candidate
+    forced: Vec<Index>,
+}
+
+/// Get the solutions of the board, stopping once limit solutions are found
+/// The limit is required because a sparse board has an astronomical number of solutions.
+/// When the limit is hit the first limit solutions in search order are returned,
+/// so getting exactly limit solutions back means there may be more
+pub fn solve_all(board: &Board, limit: usize) -> Vec<Board> {
+    let mut solutions = Vec::new();
+    if limit > 0 {
+        Search::new().explore(&mut board.clone(), &mut |solved| {
+            solutions.push(solved.clone());
+            solutions.len() >= limit
+        });
     }
+    solutions
+}
 
-    let next_empty = board.next_empty().unwrap_or_else(|| {
-        panic!(" this should not happen because we checked completeness earlier")
-    }); // we know this is not none because we checked in is_complete()
+/// Lazily iterate over the solutions of the board, one at a time
+/// Nothing is searched until the next solution is asked for, so callers can
+/// take a few solutions from a board with a huge number of them
+pub fn solutions_iter(board: &Board) -> Solutions {
+    board.solutions()
+}
+
+/// Iterator over the solutions of a board, created by Board::solutions
+/// The search is paused between solutions, so they are found one at a time as needed
+pub struct Solutions {
+    board: Board,
+    search: Search<'static>,
+}
 
-    let possible_entries = board.valid_entries(&next_empty);
+impl Iterator for Solutions {
+    type Item = Board;
 
-    for (i, is_valid) in possible_entries.iter().enumerate() {
-        if !is_valid {
-            continue;
+    fn next(&mut self) -> Option<Board> {
+        if self.search.advance(&mut self.board) {
+            Some(self.board.clone())
+        } else {
+            None
         }
-        board.update_cell(&next_empty, (i + 1) as u8);
-        if board.is_valid_entry(&next_empty) {
-            if let Some(board) = solve(board) {
-                return Some(board.clone());
+    }
+}
+
+impl std::iter::FusedIterator for Solutions {}
+
+/// Count the solutions of the board, stopping once limit solutions are found
+/// With no limit every solution is counted, which can take a very long time on sparse boards.
+/// A limit of Some(2) is enough to check that a puzzle has exactly one solution
+pub fn count_solutions(board: &Board, limit: Option<usize>) -> usize {
+    count_solutions_with_variant(board, Variant::Classic, limit)
+}
+
+/// Count the solutions of the board under the rules of the variant, stopping once limit are found
+pub fn count_solutions_with_variant(
+    board: &Board,
+    variant: Variant,
+    limit: Option<usize>,
+) -> usize {
+    let limit = limit.unwrap_or(usize::MAX);
+    let mut count = 0;
+    if limit > 0 {
+        Search::new()
+            .with_variant(variant)
+            .explore(&mut board.clone(), &mut |_| {
+                count += 1;
+                count >= limit
+            });
+    }
+    count
+}
+
+/// Count the solutions of the board up to 2 and get the first one, in a single search
+/// A proper puzzle gives (1, Some(solution)), an unsolvable one (0, None)
+/// and one with several solutions (2, Some(first solution))
+pub fn solution_signature(board: &Board) -> (usize, Option<Board>) {
+    let (count, first, _) = solution_signature_with_stats(board);
+    (count, first)
+}
+
+/// Get the solution signature of the board, also reporting how much work the search took
+/// The statistics cover the whole search, including looking for a second solution
+pub fn solution_signature_with_stats(board: &Board) -> (usize, Option<Board>, SolveStats) {
+    let start = Instant::now();
+    let mut count = 0;
+    let mut first = None;
+    let mut search = Search::new();
+    search.explore(&mut board.clone(), &mut |solved| {
+        count += 1;
+        if first.is_none() {
+            first = Some(solved.clone());
+        }
+        count >= 2
+    });
+    let stats = SolveStats {
+        nodes: search.nodes,
+        backtracks: search.backtracks,
+        propagation_fills: search.fills,
+        elapsed: start.elapsed(),
+    };
+    (count, first, stats)
+}
+
+/// Solutions enumerated by ambiguous_cells before it stops
+const AMBIGUITY_SOLUTION_CAP: usize = 100;
+
+/// Get the cells that take different values across the solutions of the board
+/// Puzzle authors can add a clue in one of these cells to make the solution unique
+/// At most 100 solutions are compared. Returns an empty list for puzzles with 0 or 1 solutions
+pub fn ambiguous_cells(board: &Board) -> Vec<Index> {
+    let mut first: Option<Board> = None;
+    let mut ambiguous = [[false; 9]; 9];
+    let mut count = 0;
+    Search::new().explore(&mut board.clone(), &mut |solved| {
+        match &first {
+            None => first = Some(solved.clone()),
+            Some(first) => {
+                for (index, _, _) in first.diff(solved) {
+                    ambiguous[index.0][index.1] = true;
+                }
             }
         }
+        count += 1;
+        count >= AMBIGUITY_SOLUTION_CAP
+    });
+    (0..81)
+        .map(|x| Index(x / 9, x % 9))
+        .filter(|index| ambiguous[index.0][index.1])
+        .collect()
+}
+
+/// Find the solution of the board that differs from the reference in the fewest cells
+/// Useful for correcting a nearly complete grid that contains mistakes
+/// Returns None if the board has no solution
+pub fn nearest_solution(board: &Board, reference: &Board) -> Option<Board> {
+    if !board.is_valid_board() {
+        return None;
+    }
+    let mismatches = board
+        .diff(reference)
+        .iter()
+        .filter(|(_, ours, _)| ours.is_some())
+        .count();
+    let mut best = None;
+    let mut bits = BitBoard::from(board);
+    nearest_search(
+        &mut board.clone(),
+        &mut bits,
+        reference,
+        mismatches,
+        &mut best,
+    );
+    best.map(|(_, solution)| solution)
+}
+
+/// Branch and bound search for nearest_solution
+/// Branches that can't beat the best solution found so far are pruned
+/// The bitboard mirrors the board, so candidates come from its unit masks instead of rescanning units,
+/// and as only candidates are placed a full board is always a solution
+fn nearest_search(
+    board: &mut Board,
+    bits: &mut BitBoard,
+    reference: &Board,
+    mismatches: usize,
+    best: &mut Option<(usize, Board)>,
+) {
+    if let Some((best_mismatches, _)) = best {
+        if mismatches >= *best_mismatches {
+            return;
+        }
+    }
+    let next_empty = match board.next_empty() {
+        Some(index) => index,
+        None => {
+            *best = Some((mismatches, board.clone()));
+            return;
+        }
+    };
+
+    let wanted = reference.get_cell(&next_empty);
+    let mut values = bits.candidates(&next_empty, &|_, _| true);
+    // try the reference value first so good solutions are found early
+    values.sort_by_key(|x| Some(*x) != wanted);
+
+    for value in values {
+        board.update_cell(&next_empty, value);
+        bits.update_cell(&next_empty, value);
+        let cost = usize::from(Some(value) != wanted);
+        nearest_search(board, bits, reference, mismatches + cost, best);
     }
     board.update_cell(&next_empty, 0);
-    return None;
+    bits.update_cell(&next_empty, 0);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    fn make_board() -> Board {
-        Board::new(&[
-            [0, 0, 3, 4, 0, 7, 0, 6, 0],
-            [7, 0, 0, 0, 0, 0, 0, 4, 0],
-            [0, 0, 0, 0, 1, 0, 2, 5, 0],
-            [4, 8, 0, 3, 0, 0, 1, 0, 0],
-            [0, 5, 0, 0, 0, 0, 0, 0, 2],
-            [0, 6, 0, 0, 2, 0, 0, 0, 0],
-            [0, 9, 0, 1, 0, 5, 0, 0, 8],
-            [1, 0, 0, 6, 0, 0, 0, 0, 5],
-            [0, 0, 0, 0, 0, 0, 4, 0, 0],
-        ])
-    }
-    fn make_solved_board() -> Board {
-        Board::new(&[
-            [5, 2, 3, 4, 8, 7, 9, 6, 1],
-            [7, 1, 9, 5, 6, 2, 8, 4, 3],
-            [8, 4, 6, 9, 1, 3, 2, 5, 7],
-            [4, 8, 2, 3, 5, 9, 1, 7, 6],
-            [9, 5, 1, 7, 4, 6, 3, 8, 2],
-            [3, 6, 7, 8, 2, 1, 5, 9, 4],
-            [2, 9, 4, 1, 7, 5, 6, 3, 8],
-            [1, 3, 8, 6, 9, 4, 7, 2, 5],
-            [6, 7, 5, 2, 3, 8, 4, 1, 9],
-        ])
-    }
+    use crate::fixtures::{make_board, make_solved_board};
 
     #[test]
     fn test_get_cell() {
@@ -297,6 +1596,293 @@ mod tests {
             ]
         )
     }
+    #[test]
+    fn test_units() {
+        let board = make_board();
+        assert_eq!(board.units().count(), 27);
+        let (unit, row) = board.units().nth(3).unwrap();
+        assert_eq!(unit, Unit::Row(3));
+        assert_eq!(row, board.get_row(3));
+        let (unit, subgrid) = board.units().last().unwrap();
+        assert_eq!(unit, Unit::Subgrid(8));
+        assert_eq!(subgrid, board.get_subgrid(&Index(8, 8)));
+        for (unit, contents) in board.units() {
+            assert_eq!(unit.cells().map(|cell| board.get_cell(&cell)), contents);
+        }
+        assert_eq!(Unit::Subgrid(5).cells()[4], Index(4, 7));
+        assert_eq!(Unit::Subgrid(5).to_string(), "box 6");
+    }
+
+    #[test]
+    fn test_conflicts() {
+        assert!(make_board().conflicts().is_empty());
+        assert!(make_solved_board().conflicts().is_empty());
+        let mut board = make_board();
+        // the 3 at r2c4 clashes with the 3 at r4c4 in its column
+        board.update_cell(&Index(1, 3), 3);
+        assert_eq!(board.conflicts(), vec![(Index(1, 3), Index(3, 3))]);
+        // the 3 at r2c2 clashes with the 3 at r1c3 in its box, and with r2c4 in its row
+        board.update_cell(&Index(1, 1), 3);
+        assert_eq!(
+            board.conflicts(),
+            vec![
+                (Index(0, 2), Index(1, 1)),
+                (Index(1, 1), Index(1, 3)),
+                (Index(1, 3), Index(3, 3))
+            ]
+        );
+        assert_eq!(board.first_conflict(), Some(Index(0, 2)));
+    }
+
+    #[test]
+    fn test_cell_iterators() {
+        let board = make_board();
+        assert_eq!(board.cells().count(), 81);
+        assert_eq!(
+            board.cells().filter(|(_, value)| value.is_some()).count(),
+            25
+        );
+        assert_eq!(board.cells().nth(2), Some((Index(0, 2), Some(3))));
+        assert_eq!(board.cells().last(), Some((Index(8, 8), None)));
+
+        let rows: Vec<_> = board.rows().collect();
+        assert_eq!(rows.len(), 9);
+        assert_eq!(rows[3].map(|(_, value)| value), board.get_row(3));
+        assert_eq!(rows[3][8].0, Index(3, 8));
+        let columns: Vec<_> = board.columns().collect();
+        assert_eq!(columns[5].map(|(_, value)| value), board.get_column(5));
+        assert_eq!(columns[5][8].0, Index(8, 5));
+        let boxes: Vec<_> = board.boxes().collect();
+        assert_eq!(
+            boxes[5].map(|(_, value)| value),
+            board.get_subgrid(&Index(4, 7))
+        );
+        assert_eq!(boxes[5][0].0, Index(3, 6));
+    }
+
+    #[test]
+    fn test_missing_in_unit() {
+        let board = make_board();
+        assert_eq!(board.missing_in_unit(Unit::Row(3)), vec![2, 5, 6, 7, 9]);
+        assert_eq!(
+            board.missing_in_unit(Unit::Subgrid(4)),
+            vec![1, 4, 5, 6, 7, 8, 9]
+        );
+        assert!(make_solved_board()
+            .missing_in_unit(Unit::Column(0))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_peers() {
+        let peers = Board::peers(Index(0, 0));
+        let unique: std::collections::HashSet<Index> = peers.iter().copied().collect();
+        assert_eq!(unique.len(), 20);
+        assert!(!unique.contains(&Index(0, 0)));
+        assert!(unique.contains(&Index(0, 8)));
+        assert!(unique.contains(&Index(8, 0)));
+        assert!(unique.contains(&Index(2, 2)));
+        assert!(!unique.contains(&Index(3, 3)));
+    }
+
+    #[test]
+    fn test_to_line() {
+        let line = make_board().to_line();
+        assert_eq!(line.len(), 81);
+        assert!(line.starts_with("..34.7.6.7......4."));
+        assert_eq!(make_solved_board().to_line()[..9], *"523487961");
+    }
+
+    #[test]
+    fn test_pretty() {
+        let pretty = make_board().pretty();
+        let lines: Vec<&str> = pretty.lines().collect();
+        assert_eq!(lines.len(), 19);
+        assert_eq!(lines[0], "┏━━━┯━━━┯━━━┳━━━┯━━━┯━━━┳━━━┯━━━┯━━━┓");
+        assert_eq!(lines[1], "┃   │   │ 3 ┃ 4 │   │ 7 ┃   │ 6 │   ┃");
+        assert_eq!(lines[2], "┠───┼───┼───╂───┼───┼───╂───┼───┼───┨");
+        assert_eq!(lines[6], "┣━━━┿━━━┿━━━╋━━━┿━━━┿━━━╋━━━┿━━━┿━━━┫");
+        assert_eq!(lines[18], "┗━━━┷━━━┷━━━┻━━━┷━━━┷━━━┻━━━┷━━━┷━━━┛");
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    #[should_panic(expected = "cells must be numbers from 0 to 9")]
+    fn test_new_panics_on_invalid_values() {
+        Board::new(&[[10; 9]; 9]);
+    }
+
+    #[test]
+    fn test_get_and_set() {
+        let mut board = make_board();
+        assert_eq!(board.get(0, 2), Ok(Some(3)));
+        assert_eq!(board.get(0, 0), Ok(None));
+        assert_eq!(
+            board.get(9, 0),
+            Err(BoardError::OutOfBounds { row: 9, column: 0 })
+        );
+
+        assert_eq!(board.set(0, 0, 5), Ok(()));
+        assert_eq!(board.get(0, 0), Ok(Some(5)));
+        assert_eq!(board.cell(&Index::new(0, 0).unwrap()), Cell::Filled(5));
+        assert_eq!(board.set(0, 0, 0), Ok(()));
+        assert_eq!(board.get(0, 0), Ok(None));
+        assert_eq!(board.set(0, 0, 10), Err(BoardError::InvalidValue(10)));
+        assert_eq!(board.set(0, 2, 1), Err(BoardError::GivenCell(Index(0, 2))));
+        assert_eq!(
+            board.set(0, 2, 1).unwrap_err().to_string(),
+            "r1c3 is a clue and can't be changed"
+        );
+        assert_eq!(board, make_board());
+
+        let index = Index::new(4, 7).unwrap();
+        assert_eq!((index.row(), index.column()), (4, 7));
+        assert!(Index::new(0, 9).is_err());
+    }
+
+    #[test]
+    fn test_cells() {
+        let puzzle = make_board();
+        assert_eq!(puzzle.cell(&Index(0, 0)), Cell::Empty);
+        assert_eq!(puzzle.cell(&Index(0, 2)), Cell::Given(3));
+        let solved = solve(&mut make_board()).unwrap();
+        assert_eq!(solved.cell(&Index(0, 0)), Cell::Filled(5));
+        assert_eq!(solved.cell(&Index(0, 2)), Cell::Given(3));
+        assert_eq!(solved, make_solved_board());
+        assert_eq!(solved.cell(&Index(0, 0)).value(), Some(5));
+        assert!(!solved.cell(&Index(0, 0)).is_given());
+
+        let clues = solved.clues();
+        assert_eq!(clues, puzzle);
+        assert_eq!(clues.cell(&Index(0, 2)), Cell::Given(3));
+        let puzzle = solved.as_puzzle();
+        assert_eq!(puzzle.cell(&Index(0, 0)), Cell::Given(5));
+        assert_eq!(puzzle.clues(), solved);
+    }
+
+    #[test]
+    fn test_colored() {
+        let puzzle = make_board();
+        let solved = solve(&mut make_board()).unwrap();
+        assert_eq!(
+            puzzle
+                .colored()
+                .replace(GIVEN_COLOR, "")
+                .replace(RESET_COLOR, ""),
+            puzzle.to_string()
+        );
+        let colored = solved.colored();
+        assert!(colored.starts_with(
+            "-------------------------\n| \x1b[32m5\x1b[0m \x1b[32m2\x1b[0m \x1b[1;34m3\x1b[0m |"
+        ));
+        assert_eq!(colored.matches(GIVEN_COLOR).count(), 25);
+        assert_eq!(colored.matches(FILLED_COLOR).count(), 81 - 25);
+        let pretty = solved.pretty_colored();
+        assert_eq!(
+            pretty
+                .replace(GIVEN_COLOR, "")
+                .replace(FILLED_COLOR, "")
+                .replace(RESET_COLOR, ""),
+            solved.pretty()
+        );
+    }
+
+    #[test]
+    fn test_diff() {
+        let board = make_board();
+        assert!(board.diff(&make_board()).is_empty());
+        let solved = make_solved_board();
+        let differences = board.diff(&solved);
+        assert_eq!(differences.len(), 81 - 25);
+        assert_eq!(differences[0], (Index(0, 0), None, Some(5)));
+    }
+
+    #[test]
+    fn test_agrees_with() {
+        let solved = make_solved_board();
+        let mut partial = make_board();
+        assert!(partial.agrees_with(&solved));
+        partial.update_cell(&Index(0, 0), 5);
+        assert!(partial.agrees_with(&solved));
+        partial.update_cell(&Index(0, 1), 1);
+        assert!(!partial.agrees_with(&solved));
+        assert!(solved.agrees_with(&solved));
+    }
+
+    #[test]
+    fn test_is_solution_of() {
+        let board = make_board();
+        let solved = make_solved_board();
+        assert!(solved.is_solution_of(&board));
+        assert!(solved.is_solution_of(&solved));
+        assert!(!board.is_solution_of(&board));
+        // a different complete grid that doesn't keep the clues
+        let other = Board::from_digits(&[
+            [1, 2, 3, 4, 5, 6, 7, 8, 9],
+            [4, 5, 6, 7, 8, 9, 1, 2, 3],
+            [7, 8, 9, 1, 2, 3, 4, 5, 6],
+            [2, 3, 4, 5, 6, 7, 8, 9, 1],
+            [5, 6, 7, 8, 9, 1, 2, 3, 4],
+            [8, 9, 1, 2, 3, 4, 5, 6, 7],
+            [3, 4, 5, 6, 7, 8, 9, 1, 2],
+            [6, 7, 8, 9, 1, 2, 3, 4, 5],
+            [9, 1, 2, 3, 4, 5, 6, 7, 8],
+        ]);
+        assert!(other.is_complete());
+        assert!(!other.is_solution_of(&board));
+        // keeping the clues is not enough if the grid breaks the rules
+        let mut broken = solved.clone();
+        broken.update_cell(&Index(0, 0), 1);
+        assert!(!broken.is_solution_of(&board));
+    }
+
+    #[test]
+    fn test_invert_clues() {
+        let board = make_board();
+        let inverted = board.invert_clues().unwrap();
+        for i in 0..9 {
+            for j in 0..9 {
+                let index = Index(i, j);
+                assert_ne!(
+                    board.get_cell(&index).is_some(),
+                    inverted.get_cell(&index).is_some()
+                );
+            }
+        }
+        assert_eq!(inverted.solved(), Some(make_solved_board()));
+        // the complementary digits are the clues of the inverted puzzle
+        assert_eq!(inverted.clues(), inverted);
+
+        let mut unsolvable = make_board();
+        unsolvable.update_cell(&Index(0, 0), 9);
+        unsolvable.update_cell(&Index(0, 1), 1);
+        assert_eq!(unsolvable.invert_clues(), None);
+    }
+
+    #[test]
+    fn test_ordering() {
+        let board = make_board();
+        let solved = make_solved_board();
+        // the first cell of board is empty, which sorts before the 5 in solved
+        assert!(board < solved);
+        let mut smaller = solved.clone();
+        smaller.update_cell(&Index(0, 0), 1);
+        assert!(smaller < solved);
+
+        let sorted: std::collections::BTreeSet<Board> = [
+            solved.clone(),
+            smaller.clone(),
+            board.clone(),
+            solved.clone(),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            sorted.into_iter().collect::<Vec<_>>(),
+            vec![board, smaller, solved]
+        );
+    }
+
     #[test]
     fn test_valid_entry() {
         let mut board = make_board();
@@ -305,18 +1891,557 @@ mod tests {
         assert!(!board.is_valid_entry(&Index(0, 0)));
     }
 
+    /// Check the board the slow and obvious way, comparing every pair of cells
+    fn naive_is_valid(board: &Board) -> bool {
+        let cells: Vec<(Index, Option<u8>)> = board.cells().collect();
+        cells.iter().all(|(a, x)| {
+            cells.iter().all(|(b, y)| {
+                let shares_unit =
+                    a.0 == b.0 || a.1 == b.1 || (a.0 / 3 == b.0 / 3 && a.1 / 3 == b.1 / 3);
+                a == b || x.is_none() || x != y || !shares_unit
+            })
+        })
+    }
+
+    #[test]
+    fn test_validity_matches_naive_checker() {
+        let mut rng = crate::generator::Rng::new(539);
+        for round in 0..300 {
+            // fill between none and all of the cells, so sparse and dense boards are both tried
+            let density = round % 10;
+            let mut board = Board::from_digits(&[[0; 9]; 9]);
+            for x in 0..81 {
+                if rng.next_u64() % 10 < density {
+                    board.update_cell(&Index(x / 9, x % 9), (rng.next_u64() % 9) as u8 + 1);
+                }
+            }
+            let valid = naive_is_valid(&board);
+            assert_eq!(board.is_valid_board(), valid, "{}", board.to_line());
+            assert_eq!(board.first_conflict().is_none(), valid);
+            assert_eq!(board.conflicts().is_empty(), valid);
+        }
+        // a single wrong digit in a solved board is always caught
+        let solved = make_solved_board();
+        for x in 0..81 {
+            let mut board = solved.clone();
+            let index = Index(x / 9, x % 9);
+            let value = board.get_cell(&index).unwrap() % 9 + 1;
+            board.update_cell(&index, value);
+            assert!(!board.is_valid_board());
+            assert!(!naive_is_valid(&board));
+        }
+    }
+
+    #[test]
+    fn test_repair() {
+        let mut board = make_board();
+        assert!(board.repair().is_empty());
+        assert_eq!(board, make_board());
+
+        board.update_cell(&Index(0, 0), 3);
+        assert!(!board.is_valid_board());
+        assert_eq!(board.repair(), vec![Index(0, 2)]);
+        assert!(board.is_valid_board());
+        assert_eq!(board.get_cell(&Index(0, 0)), Some(3));
+        assert_eq!(board.get_cell(&Index(0, 2)), None);
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let mut board = make_board();
+        board.update_cell(&Index(0, 0), 5);
+        let snapshot = board.snapshot();
+        let expected = board.clone();
+
+        board.update_cell(&Index(0, 1), 2);
+        board.update_cell(&Index(0, 0), 0);
+        assert_ne!(board, expected);
+
+        board.restore(&snapshot);
+        assert_eq!(board, expected);
+        assert_eq!(board.get_cell(&Index(0, 0)), Some(5));
+        assert_eq!(board.get_cell(&Index(0, 1)), None);
+    }
+
+    #[test]
+    fn test_empty_cells() {
+        let mut board = Board::from_digits(&[[0; 9]; 9]);
+        assert_eq!(board.next_empty(), Some(Index(0, 0)));
+        board.update_cell(&Index(0, 0), 1);
+        assert_eq!(board.next_empty(), Some(Index(0, 1)));
+        board.update_cell(&Index(0, 0), 0);
+        assert_eq!(board.next_empty(), Some(Index(0, 0)));
+
+        let solution = make_board().solved().unwrap();
+        assert_eq!(solution.next_empty(), None);
+        assert!(solution.is_complete());
+        // clearing the filled cells and restoring them keeps the bitset in step
+        let clues = solution.clues();
+        assert_eq!(clues.next_empty(), make_board().next_empty());
+        assert!(!clues.is_complete());
+        let mut board = solution.clone();
+        let snapshot = board.snapshot();
+        board.update_cell(&Index(8, 8), 0);
+        assert_eq!(board.next_empty(), Some(Index(8, 8)));
+        board.restore(&snapshot);
+        assert!(board.is_complete());
+    }
+
     #[test]
     fn test_valid_board() {
         let board = make_board();
         assert!(board.is_valid_board());
     }
 
+    #[test]
+    fn test_latin_square() {
+        assert!(make_board().is_latin_square());
+        assert!(make_solved_board().is_latin_square());
+
+        // each row is the previous one shifted by one, so boxes repeat digits
+        let mut grid = [[0; 9]; 9];
+        for (i, row) in grid.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                *cell = ((i + j) % 9 + 1) as u8;
+            }
+        }
+        let shifted = Board::from_digits(&grid);
+        assert!(shifted.is_latin_square());
+        assert!(!shifted.is_valid_board());
+
+        let mut board = make_board();
+        board.update_cell(&Index(0, 0), 3);
+        assert!(!board.is_latin_square());
+    }
+
     #[test]
     fn test_complete() {
         let complete = make_solved_board();
         assert!(complete.is_complete());
     }
 
+    /// make_solved_board with every 1 and 2 removed, which has several solutions
+    fn make_multi_solution_board() -> Board {
+        Board::from_digits(&[
+            [5, 0, 3, 4, 8, 7, 9, 6, 0],
+            [7, 0, 9, 5, 6, 0, 8, 4, 3],
+            [8, 4, 6, 9, 0, 3, 0, 5, 7],
+            [4, 8, 0, 3, 5, 9, 0, 7, 6],
+            [9, 5, 0, 7, 4, 6, 3, 8, 0],
+            [3, 6, 7, 8, 0, 0, 5, 9, 4],
+            [0, 9, 4, 0, 7, 5, 6, 3, 8],
+            [0, 3, 8, 6, 9, 4, 7, 0, 5],
+            [6, 7, 5, 0, 3, 8, 4, 0, 9],
+        ])
+    }
+
+    #[test]
+    fn test_solve_with_parity() {
+        let unconstrained = solve(&mut make_multi_solution_board()).unwrap();
+        assert_eq!(unconstrained.get_cell(&Index(0, 1)), Some(1));
+
+        let parity = [(Index(0, 1), Parity::Even)];
+        let constrained = solve_with_parity(&mut make_multi_solution_board(), &parity).unwrap();
+        assert_eq!(constrained.get_cell(&Index(0, 1)), Some(2));
+        assert!(constrained.is_complete());
+        assert_ne!(constrained, unconstrained);
+
+        let broken = [(Index(0, 0), Parity::Even)];
+        assert_eq!(solve_with_parity(&mut make_board(), &broken), None);
+    }
+
+    #[test]
+    fn test_estimated_hardness() {
+        let easy = Board::from_csv("tests/test_board_pass.csv").unwrap();
+        assert_eq!(easy.estimated_hardness(), Hardness::Trivial);
+        assert_eq!(make_board().estimated_hardness(), Hardness::Moderate);
+        // Arto Inkala's "world's hardest sudoku"
+        let hard = Board::from_digits(&[
+            [8, 0, 0, 0, 0, 0, 0, 0, 0],
+            [0, 0, 3, 6, 0, 0, 0, 0, 0],
+            [0, 7, 0, 0, 9, 0, 2, 0, 0],
+            [0, 5, 0, 0, 0, 7, 0, 0, 0],
+            [0, 0, 0, 0, 4, 5, 7, 0, 0],
+            [0, 0, 0, 1, 0, 0, 0, 3, 0],
+            [0, 0, 1, 0, 0, 0, 0, 6, 8],
+            [0, 0, 8, 5, 0, 0, 0, 1, 0],
+            [0, 9, 0, 0, 0, 0, 4, 0, 0],
+        ]);
+        assert_eq!(
+            hard.estimated_hardness(),
+            Hardness::Exceeds(HARDNESS_NODE_CAP)
+        );
+    }
+
+    #[test]
+    fn test_nearest_solution() {
+        let board = make_multi_solution_board();
+        let solved = make_solved_board();
+        assert_eq!(nearest_solution(&board, &solved), Some(solved.clone()));
+
+        // a reference with mistakes still leads to the closest solution
+        let mut reference = solved.clone();
+        reference.update_cell(&Index(0, 1), 1);
+        reference.update_cell(&Index(4, 4), 9);
+        assert_eq!(nearest_solution(&board, &reference), Some(solved));
+
+        let mut invalid = make_board();
+        invalid.update_cell(&Index(0, 0), 3);
+        assert_eq!(nearest_solution(&invalid, &make_solved_board()), None);
+    }
+
+    #[test]
+    fn test_solve_steps() {
+        let mut board = make_board();
+        let steps = solve_steps(&board);
+        assert!(steps.iter().any(|step| step.is_guess));
+        assert!(steps.iter().any(|step| step.value == 0));
+        for step in steps {
+            board.update_cell(&step.index, step.value);
+        }
+        assert_eq!(board, make_solved_board());
+    }
+
+    #[test]
+    fn test_propagation_fills_singles() {
+        // every cleared cell is a naked single, so no branching is needed
+        let mut board = make_solved_board();
+        for j in 0..9 {
+            board.update_cell(&Index(0, j), 0);
+            board.update_cell(&Index(j, 0), 0);
+        }
+        let steps = solve_steps(&board);
+        assert_eq!(steps.len(), 17);
+        assert!(steps.iter().all(|step| !step.is_guess));
+        let mut search = Search::new();
+        assert_eq!(search.run(&mut board), Some(make_solved_board()));
+        assert_eq!(search.nodes, 1);
+
+        // a search that runs out of solutions clears everything it placed
+        let mut board = make_multi_solution_board();
+        let mut search = Search::new();
+        search.explore(&mut board, &mut |_| false);
+        assert_eq!(board, make_multi_solution_board());
+    }
+
+    #[test]
+    fn test_count_solutions() {
+        assert_eq!(count_solutions(&make_board(), None), 1);
+        assert_eq!(count_solutions(&make_multi_solution_board(), None), 4);
+        assert_eq!(count_solutions(&make_multi_solution_board(), Some(10)), 4);
+        assert_eq!(count_solutions(&make_multi_solution_board(), Some(2)), 2);
+        assert_eq!(count_solutions(&make_multi_solution_board(), Some(0)), 0);
+        assert_eq!(
+            count_solutions(&Board::from_digits(&[[0; 9]; 9]), Some(50)),
+            50
+        );
+    }
+
+    #[test]
+    fn test_diagonal_variant() {
+        let solved = make_solved_board();
+        assert!(solved.is_valid_for(Variant::Classic));
+        // the classic solution repeats digits on its diagonals, so it breaks the diagonal rules
+        assert!(!solved.is_valid_for(Variant::Diagonal));
+        assert_eq!(
+            count_solutions_with_variant(&make_board(), Variant::Diagonal, Some(2)),
+            0
+        );
+        assert_eq!(
+            solve_with_variant(&mut make_board(), Variant::Diagonal),
+            None
+        );
+        assert_eq!(
+            solve_with_variant(&mut make_board(), Variant::Classic),
+            Some(solved)
+        );
+
+        let mut empty = Board::from_digits(&[[0; 9]; 9]);
+        let diagonal = solve_with_variant(&mut empty, Variant::Diagonal).unwrap();
+        assert!(diagonal.is_complete());
+        assert!(diagonal.is_valid_for(Variant::Diagonal));
+    }
+
+    #[test]
+    fn test_hyper_variant() {
+        // r2c2 and r3c4 are in different boxes but the same window
+        let mut board = Board::from_digits(&[[0; 9]; 9]);
+        board.update_cell(&Index(1, 1), 5);
+        board.update_cell(&Index(2, 3), 5);
+        assert!(board.is_valid_for(Variant::Classic));
+        assert!(!board.is_valid_for(Variant::Hyper));
+        assert_eq!(solve_with_variant(&mut board.clone(), Variant::Hyper), None);
+
+        let hyper =
+            solve_with_variant(&mut Board::from_digits(&[[0; 9]; 9]), Variant::Hyper).unwrap();
+        assert!(hyper.is_complete());
+        assert!(hyper.is_valid_for(Variant::Hyper));
+        assert_eq!(
+            count_solutions_with_variant(&hyper, Variant::Hyper, Some(2)),
+            1
+        );
+    }
+
+    #[test]
+    fn test_chess_variants() {
+        // r1c3 and r2c5 are a knight's move apart, r3c3 and r4c4 a king's move
+        let mut knight = Board::from_digits(&[[0; 9]; 9]);
+        knight.update_cell(&Index(0, 2), 5);
+        knight.update_cell(&Index(1, 4), 5);
+        let mut king = Board::from_digits(&[[0; 9]; 9]);
+        king.update_cell(&Index(2, 2), 5);
+        king.update_cell(&Index(3, 3), 5);
+        assert!(knight.is_valid_for(Variant::AntiKing));
+        assert!(!knight.is_valid_for(Variant::AntiKnight));
+        assert!(king.is_valid_for(Variant::AntiKnight));
+        assert!(!king.is_valid_for(Variant::AntiKing));
+
+        for variant in [Variant::AntiKnight, Variant::AntiKing] {
+            let solved =
+                solve_with_variant(&mut Board::from_digits(&[[0; 9]; 9]), variant).unwrap();
+            assert!(solved.is_complete());
+            assert!(solved.is_valid_for(variant));
+        }
+    }
+
+    #[test]
+    fn test_solution_signature() {
+        assert_eq!(
+            solution_signature(&make_board()),
+            (1, Some(make_solved_board()))
+        );
+        let (count, first) = solution_signature(&make_multi_solution_board());
+        assert_eq!(count, 2);
+        assert!(first.unwrap().is_complete());
+
+        let mut unsolvable = make_board();
+        unsolvable.update_cell(&Index(0, 0), 9);
+        unsolvable.update_cell(&Index(0, 1), 1);
+        assert!(unsolvable.is_valid_board());
+        assert_eq!(solution_signature(&unsolvable), (0, None));
+    }
+
+    #[test]
+    fn test_ambiguous_cells() {
+        assert!(ambiguous_cells(&make_board()).is_empty());
+
+        // the 4 and 8 in these cells can be swapped, giving 2 solutions
+        let mut board = make_solved_board();
+        let rectangle = [Index(2, 0), Index(2, 1), Index(3, 0), Index(3, 1)];
+        for index in &rectangle {
+            board.update_cell(index, 0);
+        }
+        assert_eq!(count_solutions(&board, None), 2);
+        assert_eq!(ambiguous_cells(&board), rectangle.to_vec());
+    }
+
+    #[test]
+    fn test_solve_all() {
+        assert_eq!(solve_all(&make_board(), 10), vec![make_solved_board()]);
+        let solutions = solve_all(&make_multi_solution_board(), 10);
+        assert_eq!(solutions.len(), 4);
+        assert!(solutions.contains(&make_solved_board()));
+
+        // an empty board has far too many solutions to enumerate
+        let sparse = Board::from_digits(&[[0; 9]; 9]);
+        let solutions = solve_all(&sparse, 1000);
+        assert_eq!(solutions.len(), 1000);
+        assert!(solutions.iter().all(|solution| solution.is_complete()));
+        assert!(solve_all(&sparse, 0).is_empty());
+    }
+
+    #[test]
+    fn test_solutions_iter() {
+        let sparse = Board::from_digits(&[[0; 9]; 9]);
+        let solutions: Vec<Board> = solutions_iter(&sparse).take(3).collect();
+        assert_eq!(solutions.len(), 3);
+        assert!(solutions.iter().all(|solution| solution.is_complete()));
+        assert_ne!(solutions[0], solutions[1]);
+        assert_ne!(solutions[1], solutions[2]);
+        assert_eq!(solutions, solve_all(&sparse, 3));
+
+        assert_eq!(solutions_iter(&make_multi_solution_board()).count(), 4);
+        assert_eq!(
+            solutions_iter(&make_solved_board()).collect::<Vec<_>>(),
+            vec![make_solved_board()]
+        );
+        let mut invalid = make_board();
+        invalid.update_cell(&Index(0, 0), 3);
+        assert_eq!(solutions_iter(&invalid).next(), None);
+    }
+
+    #[test]
+    fn test_has_unique_solution() {
+        assert!(make_board().has_unique_solution());
+        assert!(make_solved_board().has_unique_solution());
+        assert!(!make_multi_solution_board().has_unique_solution());
+        assert!(!Board::from_digits(&[[0; 9]; 9]).has_unique_solution());
+
+        let mut unsolvable = make_board();
+        unsolvable.update_cell(&Index(0, 0), 9);
+        unsolvable.update_cell(&Index(0, 1), 1);
+        assert!(!unsolvable.has_unique_solution());
+    }
+
+    #[test]
+    fn test_solutions() {
+        let board = make_multi_solution_board();
+        let mut solutions = board.solutions();
+        let mut streamed = Vec::new();
+        for solution in solutions.by_ref() {
+            assert!(solution.is_complete());
+            assert!(board.agrees_with(&solution));
+            streamed.push(solution);
+        }
+        assert_eq!(streamed, solve_all(&board, 10));
+        assert_eq!(solutions.next(), None);
+        assert_eq!(
+            make_board().solutions().collect::<Vec<_>>(),
+            vec![make_solved_board()]
+        );
+    }
+
+    #[test]
+    fn test_solve_with_callback() {
+        let mut board = Board::from_csv("tests/test_board_pass.csv").unwrap();
+        let empty_cells = board
+            .board
+            .iter()
+            .flatten()
+            .filter(|x| **x == Cell::Empty)
+            .count();
+        let steps = solve_steps(&board);
+
+        let mut placements = 0;
+        let mut guesses = 0;
+        let solution = solve_with_callback(&mut board, |_, _, is_guess| {
+            placements += 1;
+            guesses += usize::from(is_guess);
+        });
+        assert!(solution.is_some());
+        assert!(placements >= empty_cells);
+        assert_eq!(placements, steps.len());
+        assert_eq!(guesses, steps.iter().filter(|step| step.is_guess).count());
+    }
+
+    #[test]
+    fn test_next_branch_cell() {
+        let mut board = make_solved_board();
+        assert_eq!(board.next_branch_cell(), None);
+
+        // (4, 4) is the only cell with a single candidate, the others can hold 4 or 8
+        board.update_cell(&Index(2, 0), 0);
+        board.update_cell(&Index(2, 1), 0);
+        board.update_cell(&Index(3, 0), 0);
+        board.update_cell(&Index(3, 1), 0);
+        board.update_cell(&Index(4, 4), 0);
+        assert_eq!(board.next_branch_cell(), Some((Index(4, 4), vec![4])));
+
+        board.update_cell(&Index(4, 4), 4);
+        assert_eq!(board.next_branch_cell(), Some((Index(2, 0), vec![4, 8])));
+    }
+
+    #[test]
+    fn test_solve_with_selection() {
+        let solved = make_solved_board();
+        for selection in [
+            CellSelection::FirstEmpty,
+            CellSelection::MinimumRemainingValues,
+        ] {
+            assert_eq!(
+                solve_with_selection(&mut make_board(), selection),
+                Some(solved.clone())
+            );
+        }
+
+        let mut first_empty = Search::new().with_selection(CellSelection::FirstEmpty);
+        first_empty.run(&mut make_board());
+        let mut mrv = Search::new().with_selection(CellSelection::MinimumRemainingValues);
+        mrv.run(&mut make_board());
+        assert!(mrv.nodes < first_empty.nodes);
+    }
+
+    #[test]
+    fn test_solve_with_stats() {
+        let (solution, stats) = solve_with_stats(&mut make_board());
+        assert_eq!(solution, Some(make_solved_board()));
+        assert!(stats.nodes > 1);
+        assert!(stats.backtracks > 0);
+        assert!(stats.propagation_fills > 0);
+
+        let (solution, stats) = solve_with_stats(&mut make_solved_board());
+        assert_eq!(solution, Some(make_solved_board()));
+        assert_eq!(
+            (stats.nodes, stats.backtracks, stats.propagation_fills),
+            (1, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_solve_with_options() {
+        let options = SolverOptions::default();
+        assert_eq!(
+            solve_with_options(&mut make_board(), &options),
+            Ok(Some(make_solved_board()))
+        );
+
+        let token = CancelToken::new();
+        let options = SolverOptions {
+            time_limit: None,
+            cancel_token: Some(token.clone()),
+        };
+        token.cancel();
+        assert_eq!(
+            solve_with_options(&mut make_board(), &options),
+            Err(BoardError::Cancelled)
+        );
+
+        let options = SolverOptions {
+            time_limit: Some(Duration::ZERO),
+            cancel_token: None,
+        };
+        assert_eq!(
+            solve_with_options(&mut Board::from_digits(&[[0; 9]; 9]), &options),
+            Err(BoardError::TimedOut)
+        );
+    }
+
+    #[test]
+    fn test_interrupted_solve_leaves_board() {
+        let original = Board::from_csv("tests/hard_puzzle.csv").unwrap();
+        // cancel partway through, once the search has guessed and propagated
+        let token = CancelToken::new();
+        let options = SolverOptions {
+            time_limit: None,
+            cancel_token: Some(token.clone()),
+        };
+        let mut placements = 0;
+        let mut board = original.clone();
+        let mut search = Search::new().with_options(&options).on_step(|_, value, _| {
+            placements += usize::from(value != 0);
+            if placements == 50 {
+                token.cancel();
+            }
+        });
+        assert_eq!(search.run(&mut board), None);
+        assert_eq!(search.interrupted, Some(BoardError::Cancelled));
+        assert_eq!(board, original);
+        assert_eq!(board.clues(), board);
+
+        let mut board = original.clone();
+        let mut search = Search::new().with_node_limit(10);
+        assert_eq!(search.run(&mut board), None);
+        assert_eq!(board, original);
+    }
+
+    #[test]
+    fn test_solve_empty_board() {
+        let mut board = Board::from_digits(&[[0; 9]; 9]);
+        let solution = solve(&mut board).unwrap();
+        assert!(solution.is_complete());
+        assert_eq!(board, solution);
+    }
+
     #[test]
     fn test_solve_mut() {
         let mut board = make_board();
@@ -327,4 +2452,15 @@ mod tests {
 
         assert_eq!(experimental_solution, solved_board);
     }
+
+    #[test]
+    fn test_solved() {
+        let board = make_board();
+        assert_eq!(board.solved(), Some(make_solved_board()));
+        assert_eq!(board, make_board());
+        let mut unsolvable = make_board();
+        unsolvable.update_cell(&Index(0, 0), 9);
+        unsolvable.update_cell(&Index(0, 1), 1);
+        assert_eq!(unsolvable.solved(), None);
+    }
 }
@@ -0,0 +1,151 @@
+//! This module contains the pencil mark grid, tracking the values still possible
+//! for every empty cell of a board
+//! Candidates are only ever removed, either by filling a cell or by eliminating
+//! a value through reasoning, so the grid can be kept in step with a board as it is solved
+
+use crate::board::{Board, Index, Variant};
+
+/// Mask with the bits of all values 1 to 9 set
+const ALL_VALUES: u16 = 0b11_1111_1110;
+
+/// The candidates of every cell of a board
+/// Filled cells have no candidates
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Candidates {
+    /// Bit v of a mask is set if v is still a candidate for the cell
+    masks: [[u16; 9]; 9],
+    /// Bit 9 * row + column of the set for a cell is set if that cell sees it,
+    /// under the usual rules and those of the variant.
+    /// Worked out once, so placing a value doesn't ask the variant's constraints again
+    peers: [u128; 81],
+}
+
+impl Candidates {
+    /// Pencil in every value that doesn't clash with a filled peer
+    pub fn new(board: &Board) -> Self {
+        Candidates::with_variant(board, Variant::Classic)
+    }
+    /// Pencil in every value that doesn't clash with a filled peer under the rules of the variant
+    /// The variant's constraints prune the candidates after the usual peers do
+    pub fn with_variant(board: &Board, variant: Variant) -> Self {
+        let mut masks = [[0; 9]; 9];
+        for (i, row) in masks.iter_mut().enumerate() {
+            for (j, mask) in row.iter_mut().enumerate() {
+                if board.get_cell(&Index(i, j)).is_some() {
+                    continue;
+                }
+                *mask = ALL_VALUES;
+                for peer in Board::peers(Index(i, j)) {
+                    if let Some(value) = board.get_cell(&peer) {
+                        *mask &= !(1 << value);
+                    }
+                }
+            }
+        }
+        let constraints = variant.constraints();
+        let mut peers = [0; 81];
+        for (cell, peers) in peers.iter_mut().enumerate() {
+            let index = Index(cell / 9, cell % 9);
+            let variant_peers = constraints
+                .iter()
+                .flat_map(|constraint| constraint.peers(&index));
+            for peer in Board::peers(index).into_iter().chain(variant_peers) {
+                *peers |= 1 << (peer.0 * 9 + peer.1);
+            }
+        }
+        let mut candidates = Candidates { masks, peers };
+        for constraint in &constraints {
+            constraint.prune(board, &mut candidates);
+        }
+        candidates
+    }
+    /// Get the candidates of a cell in increasing order
+    pub fn get(&self, index: &Index) -> Vec<u8> {
+        (1..=9)
+            .filter(|value| self.contains(index, *value))
+            .collect()
+    }
+    /// Check whether the value is still a candidate for the cell
+    pub fn contains(&self, index: &Index, value: u8) -> bool {
+        self.mask(index) & (1 << value) != 0
+    }
+    /// Count the candidates of a cell
+    pub fn count(&self, index: &Index) -> usize {
+        self.mask(index).count_ones() as usize
+    }
+    /// Remove a candidate from a cell, returning whether it was a candidate
+    pub fn eliminate(&mut self, index: &Index, value: u8) -> bool {
+        let was_candidate = self.contains(index, value);
+        self.masks[index.0][index.1] &= !(1 << value);
+        was_candidate
+    }
+    /// Record that a cell was filled with the value, clearing its candidates
+    /// and removing the value from the candidates of its peers, including those of the variant
+    pub fn place(&mut self, index: &Index, value: u8) {
+        self.masks[index.0][index.1] = 0;
+        let mut peers = self.peers[index.0 * 9 + index.1];
+        while peers != 0 {
+            let cell = peers.trailing_zeros() as usize;
+            // clear the lowest set bit
+            peers &= peers - 1;
+            self.masks[cell / 9][cell % 9] &= !(1 << value);
+        }
+    }
+    /// Get the candidates of a cell as a mask, with bit v set if v is a candidate
+    pub(crate) fn mask(&self, index: &Index) -> u16 {
+        self.masks[index.0][index.1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidates() {
+        let board: Board =
+            ".738.4216...2.95..2856.3.97...3...7475....3.1..4.2.....9756.......7..1..42..3..6."
+                .parse()
+                .unwrap();
+        let mut candidates = Candidates::new(&board);
+        assert_eq!(candidates.get(&Index(0, 0)), vec![9]);
+        assert_eq!(candidates.get(&Index(0, 4)), vec![5]);
+        assert_eq!(candidates.count(&Index(0, 1)), 0);
+
+        assert_eq!(candidates.get(&Index(1, 0)), vec![1, 6]);
+        assert!(candidates.eliminate(&Index(1, 0), 6));
+        assert!(!candidates.eliminate(&Index(1, 0), 6));
+        assert_eq!(candidates.get(&Index(1, 0)), vec![1]);
+
+        candidates.place(&Index(1, 0), 1);
+        assert_eq!(candidates.count(&Index(1, 0)), 0);
+        assert!(!candidates.contains(&Index(1, 1), 1));
+        assert!(!candidates.contains(&Index(8, 0), 1));
+    }
+
+    #[test]
+    fn test_diagonal_candidates() {
+        let mut board = Board::try_new(&[[0; 9]; 9]).unwrap();
+        board.set(0, 0, 5).unwrap();
+        let classic = Candidates::new(&board);
+        let mut diagonal = Candidates::with_variant(&board, Variant::Diagonal);
+        assert!(classic.contains(&Index(8, 8), 5));
+        assert!(!diagonal.contains(&Index(8, 8), 5));
+        assert!(diagonal.contains(&Index(2, 6), 5));
+
+        diagonal.place(&Index(2, 6), 7);
+        assert!(!diagonal.contains(&Index(4, 4), 7));
+        assert!(!diagonal.contains(&Index(8, 0), 7));
+        assert!(diagonal.contains(&Index(7, 7), 7));
+    }
+
+    #[test]
+    fn test_anti_knight_candidates() {
+        let mut board = Board::try_new(&[[0; 9]; 9]).unwrap();
+        board.set(4, 4, 5).unwrap();
+        let candidates = Candidates::with_variant(&board, Variant::AntiKnight);
+        assert!(!candidates.contains(&Index(2, 3), 5));
+        assert!(!candidates.contains(&Index(5, 6), 5));
+        assert!(candidates.contains(&Index(2, 2), 5));
+    }
+}
@@ -0,0 +1,432 @@
+//! This module contains the rules of sudoku as constraints on which digits can go where
+//! Rows, columns and boxes are the three built-in constraints every board follows.
+//! Variants add more, such as the diagonals of X-Sudoku or the cages of killer sudoku,
+//! and anything implementing Constraint can be handed to the solver alongside them
+//!
+//! The solver keeps bitmasks for rows, columns and boxes, so only the extra constraints
+//! are asked whether a value is allowed
+
+use crate::board::{Board, Index, Unit};
+use crate::candidates::Candidates;
+use crate::jigsaw::Regions;
+
+/// A rule restricting which digits can go in which cells
+pub trait Constraint {
+    /// Check whether the value can go in the cell, ignoring whatever the cell holds now
+    fn allows(&self, board: &Board, index: &Index, value: u8) -> bool;
+    /// Get the cells that can never hold the same digit as the cell
+    /// Placing a digit removes it from the candidates of these cells. Defaults to none
+    fn peers(&self, _index: &Index) -> Vec<Index> {
+        Vec::new()
+    }
+    /// Remove the candidates of the empty cells that the constraint rules out,
+    /// returning whether any were removed
+    fn prune(&self, board: &Board, candidates: &mut Candidates) -> bool {
+        let mut removed = false;
+        for (index, value) in board.cells() {
+            if value.is_some() {
+                continue;
+            }
+            for candidate in candidates.get(&index) {
+                if !self.allows(board, &index, candidate) {
+                    removed |= candidates.eliminate(&index, candidate);
+                }
+            }
+        }
+        removed
+    }
+    /// Check that no filled cell of the board breaks the constraint
+    fn is_satisfied(&self, board: &Board) -> bool {
+        board
+            .cells()
+            .all(|(index, value)| value.is_none_or(|value| self.allows(board, &index, value)))
+    }
+    /// Get the regions that take the place of the 3x3 boxes, as in jigsaw sudoku
+    /// The solver then keeps bitmasks for these regions instead of the boxes. Defaults to None
+    fn regions(&self) -> Option<Regions> {
+        None
+    }
+    /// Check whether the solver's bitmasks already keep the constraint, as they do rows,
+    /// columns and boxes, so it needn't be asked about each placement. Defaults to false
+    fn is_built_in(&self) -> bool {
+        false
+    }
+}
+
+impl<T: Constraint + ?Sized> Constraint for &T {
+    fn allows(&self, board: &Board, index: &Index, value: u8) -> bool {
+        (**self).allows(board, index, value)
+    }
+    fn peers(&self, index: &Index) -> Vec<Index> {
+        (**self).peers(index)
+    }
+    fn prune(&self, board: &Board, candidates: &mut Candidates) -> bool {
+        (**self).prune(board, candidates)
+    }
+    fn is_satisfied(&self, board: &Board) -> bool {
+        (**self).is_satisfied(board)
+    }
+    fn regions(&self) -> Option<Regions> {
+        (**self).regions()
+    }
+    fn is_built_in(&self) -> bool {
+        (**self).is_built_in()
+    }
+}
+
+impl<T: Constraint + ?Sized> Constraint for Box<T> {
+    fn allows(&self, board: &Board, index: &Index, value: u8) -> bool {
+        (**self).allows(board, index, value)
+    }
+    fn peers(&self, index: &Index) -> Vec<Index> {
+        (**self).peers(index)
+    }
+    fn prune(&self, board: &Board, candidates: &mut Candidates) -> bool {
+        (**self).prune(board, candidates)
+    }
+    fn is_satisfied(&self, board: &Board) -> bool {
+        (**self).is_satisfied(board)
+    }
+    fn regions(&self) -> Option<Regions> {
+        (**self).regions()
+    }
+    fn is_built_in(&self) -> bool {
+        (**self).is_built_in()
+    }
+}
+
+/// A list of constraints holds when each of them does
+impl<T: Constraint> Constraint for [T] {
+    fn allows(&self, board: &Board, index: &Index, value: u8) -> bool {
+        self.iter()
+            .all(|constraint| constraint.allows(board, index, value))
+    }
+    fn peers(&self, index: &Index) -> Vec<Index> {
+        self.iter()
+            .flat_map(|constraint| constraint.peers(index))
+            .collect()
+    }
+    fn regions(&self) -> Option<Regions> {
+        self.iter().find_map(|constraint| constraint.regions())
+    }
+    fn is_built_in(&self) -> bool {
+        !self.is_empty() && self.iter().all(|constraint| constraint.is_built_in())
+    }
+}
+
+/// The constraints of classic sudoku
+pub const CLASSIC: [&dyn Constraint; 3] = [&Rows, &Columns, &Boxes];
+
+/// Check that no cell of the units holding the cell, other than the cell itself, holds the value
+fn units_allow(units: &[[Index; 9]], board: &Board, index: &Index, value: u8) -> bool {
+    units
+        .iter()
+        .filter(|unit| unit.contains(index))
+        .flatten()
+        .all(|cell| cell == index || board.get_cell(cell) != Some(value))
+}
+
+/// Check whether count distinct digits whose bits are set in the mask add up to sum
+pub(crate) fn sums_to(digits: u16, count: usize, sum: usize) -> bool {
+    if count == 0 {
+        return sum == 0;
+    }
+    // take the digits in increasing order, so each combination is tried once
+    (1..=9.min(sum)).any(|digit| {
+        digits & (1 << digit) != 0 && sums_to(digits & !((2 << digit) - 1), count - 1, sum - digit)
+    })
+}
+
+/// Get the cells sharing one of the units with the cell, not counting the cell itself
+/// A cell in two units, like the centre cell of X-Sudoku, has its peers listed once per unit
+fn unit_peers(units: &[[Index; 9]], index: &Index) -> Vec<Index> {
+    units
+        .iter()
+        .filter(|unit| unit.contains(index))
+        .flatten()
+        .filter(|&cell| cell != index)
+        .copied()
+        .collect()
+}
+
+/// Each row holds each digit once
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub struct Rows;
+
+/// Each column holds each digit once
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub struct Columns;
+
+/// Each 3x3 box holds each digit once
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub struct Boxes;
+
+/// Both main diagonals hold each digit once, as in X-Sudoku
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub struct Diagonals;
+
+/// Four 3x3 windows, one cell in from the corners, hold each digit once, as in hyper sudoku
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub struct Windows;
+
+impl Rows {
+    fn units(index: &Index) -> [[Index; 9]; 1] {
+        [Unit::Row(index.0).cells()]
+    }
+}
+
+impl Columns {
+    fn units(index: &Index) -> [[Index; 9]; 1] {
+        [Unit::Column(index.1).cells()]
+    }
+}
+
+impl Boxes {
+    fn units(index: &Index) -> [[Index; 9]; 1] {
+        [Unit::Subgrid(index.0 / 3 * 3 + index.1 / 3).cells()]
+    }
+}
+
+impl Diagonals {
+    fn units() -> [[Index; 9]; 2] {
+        [
+            std::array::from_fn(|i| Index(i, i)),
+            std::array::from_fn(|i| Index(i, 8 - i)),
+        ]
+    }
+}
+
+impl Windows {
+    fn units() -> [[Index; 9]; 4] {
+        [(1, 1), (1, 5), (5, 1), (5, 5)]
+            .map(|(row, column)| std::array::from_fn(|k| Index(row + k / 3, column + k % 3)))
+    }
+}
+
+impl Constraint for Rows {
+    fn allows(&self, board: &Board, index: &Index, value: u8) -> bool {
+        units_allow(&Self::units(index), board, index, value)
+    }
+    fn peers(&self, index: &Index) -> Vec<Index> {
+        unit_peers(&Self::units(index), index)
+    }
+    fn is_built_in(&self) -> bool {
+        true
+    }
+}
+
+impl Constraint for Columns {
+    fn allows(&self, board: &Board, index: &Index, value: u8) -> bool {
+        units_allow(&Self::units(index), board, index, value)
+    }
+    fn peers(&self, index: &Index) -> Vec<Index> {
+        unit_peers(&Self::units(index), index)
+    }
+    fn is_built_in(&self) -> bool {
+        true
+    }
+}
+
+impl Constraint for Boxes {
+    fn allows(&self, board: &Board, index: &Index, value: u8) -> bool {
+        units_allow(&Self::units(index), board, index, value)
+    }
+    fn peers(&self, index: &Index) -> Vec<Index> {
+        unit_peers(&Self::units(index), index)
+    }
+    fn is_built_in(&self) -> bool {
+        true
+    }
+}
+
+impl Constraint for Diagonals {
+    fn allows(&self, board: &Board, index: &Index, value: u8) -> bool {
+        units_allow(&Self::units(), board, index, value)
+    }
+    fn peers(&self, index: &Index) -> Vec<Index> {
+        unit_peers(&Self::units(), index)
+    }
+}
+
+impl Constraint for Windows {
+    fn allows(&self, board: &Board, index: &Index, value: u8) -> bool {
+        units_allow(&Self::units(), board, index, value)
+    }
+    fn peers(&self, index: &Index) -> Vec<Index> {
+        unit_peers(&Self::units(), index)
+    }
+}
+
+/// Cells a knight's move apart can't hold the same digit
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub struct AntiKnight;
+
+/// Cells a king's move apart, including diagonally, can't hold the same digit
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub struct AntiKing;
+
+/// Row and column steps of a knight's move
+const KNIGHT_MOVES: [(isize, isize); 8] = [
+    (-2, -1),
+    (-2, 1),
+    (-1, -2),
+    (-1, 2),
+    (1, -2),
+    (1, 2),
+    (2, -1),
+    (2, 1),
+];
+
+/// Row and column steps of a king's move
+const KING_MOVES: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// Get the cells on the board the given steps away from the cell
+fn moves(index: &Index, steps: &[(isize, isize)]) -> Vec<Index> {
+    steps
+        .iter()
+        .filter_map(|&(rows, columns)| {
+            let row = index.0.checked_add_signed(rows)?;
+            let column = index.1.checked_add_signed(columns)?;
+            Index::new(row, column).ok()
+        })
+        .collect()
+}
+
+impl Constraint for AntiKnight {
+    fn allows(&self, board: &Board, index: &Index, value: u8) -> bool {
+        self.peers(index)
+            .iter()
+            .all(|peer| board.get_cell(peer) != Some(value))
+    }
+    fn peers(&self, index: &Index) -> Vec<Index> {
+        moves(index, &KNIGHT_MOVES)
+    }
+}
+
+impl Constraint for AntiKing {
+    fn allows(&self, board: &Board, index: &Index, value: u8) -> bool {
+        self.peers(index)
+            .iter()
+            .all(|peer| board.get_cell(peer) != Some(value))
+    }
+    fn peers(&self, index: &Index) -> Vec<Index> {
+        moves(index, &KING_MOVES)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::solve_with_constraints;
+
+    fn empty() -> Board {
+        Board::try_new(&[[0; 9]; 9]).unwrap()
+    }
+
+    #[test]
+    fn test_classic_constraints() {
+        let puzzle: Board =
+            ".738.4216...2.95..2856.3.97...3...7475....3.1..4.2.....9756.......7..1..42..3..6."
+                .parse()
+                .unwrap();
+        assert!(CLASSIC
+            .iter()
+            .all(|constraint| constraint.is_satisfied(&puzzle)));
+        // r1c1 can only hold 9
+        let allowed: Vec<u8> = (1..=9)
+            .filter(|&value| {
+                CLASSIC
+                    .iter()
+                    .all(|constraint| constraint.allows(&puzzle, &Index(0, 0), value))
+            })
+            .collect();
+        assert_eq!(allowed, vec![9]);
+        let mut peers: Vec<Index> = CLASSIC
+            .iter()
+            .flat_map(|constraint| constraint.peers(&Index(4, 4)))
+            .collect();
+        peers.sort_by_key(|index| (index.0, index.1));
+        peers.dedup();
+        assert_eq!(peers, Board::peers(Index(4, 4)));
+
+        // the classic constraints alone solve the puzzle, and the solution follows all three
+        let solved = solve_with_constraints(&mut puzzle.clone(), &CLASSIC).unwrap();
+        assert!(solved.is_solution_of(&puzzle));
+        assert!(CLASSIC
+            .iter()
+            .all(|constraint| constraint.is_satisfied(&solved)));
+        // a list is kept by the bitmasks only when it holds nothing but built-in constraints
+        assert!(CLASSIC.is_built_in());
+        assert!(![&Rows as &dyn Constraint, &Diagonals].is_built_in());
+        assert!(!<[&dyn Constraint]>::is_built_in(&[]));
+        let mut broken = solved.clone();
+        broken
+            .set(0, 0, solved.get(0, 1).unwrap().unwrap())
+            .unwrap();
+        assert!(!CLASSIC
+            .iter()
+            .all(|constraint| constraint.is_satisfied(&broken)));
+    }
+
+    #[test]
+    fn test_variant_constraints() {
+        let mut board = empty();
+        board.set(0, 0, 5).unwrap();
+        assert!(!Diagonals.allows(&board, &Index(8, 8), 5));
+        assert!(Diagonals.allows(&board, &Index(8, 8), 4));
+        // r2c2 and r3c4 are in different boxes but the same window
+        board.set(1, 1, 4).unwrap();
+        assert!(!Windows.allows(&board, &Index(2, 3), 4));
+        assert!(Windows.allows(&board, &Index(4, 4), 4));
+        // r1c1 and r3c2 are a knight's move apart, r1c1 and r2c2 a king's move
+        assert!(!AntiKnight.allows(&board, &Index(2, 1), 5));
+        assert!(!AntiKing.allows(&board, &Index(1, 1), 5));
+        assert_eq!(AntiKnight.peers(&Index(0, 0)).len(), 2);
+        assert_eq!(AntiKing.peers(&Index(4, 4)).len(), 8);
+
+        // a cell's own digit doesn't count against it
+        assert!(Diagonals.is_satisfied(&board));
+        board.set(8, 8, 5).unwrap();
+        assert!(!Diagonals.is_satisfied(&board));
+    }
+
+    #[test]
+    fn test_prune() {
+        let mut board = empty();
+        board.set(0, 0, 5).unwrap();
+        let mut candidates = Candidates::new(&board);
+        assert!(candidates.contains(&Index(8, 8), 5));
+        assert!(Diagonals.prune(&board, &mut candidates));
+        assert!(!candidates.contains(&Index(8, 8), 5));
+        assert!(!Diagonals.prune(&board, &mut candidates));
+    }
+
+    /// Only odd digits on the main diagonal
+    struct OddDiagonal;
+
+    impl Constraint for OddDiagonal {
+        fn allows(&self, _board: &Board, index: &Index, value: u8) -> bool {
+            index.0 != index.1 || value % 2 == 1
+        }
+    }
+
+    #[test]
+    fn test_custom_constraint() {
+        let constraints: [&dyn Constraint; 2] = [&OddDiagonal, &AntiKing];
+        let solved = solve_with_constraints(&mut empty(), &constraints).unwrap();
+        assert!(solved.is_valid_board());
+        assert!(constraints
+            .iter()
+            .all(|constraint| constraint.is_satisfied(&solved)));
+        assert!((0..9).all(|i| solved.get(i, i).unwrap().unwrap() % 2 == 1));
+    }
+}
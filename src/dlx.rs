@@ -0,0 +1,273 @@
+//! This module contains a Dancing Links (Algorithm X) solver
+//! Sudoku is treated as an exact cover problem: each of the 729 ways of placing a value
+//! in a cell covers one cell, one value in a row, one in a column and one in a subgrid,
+//! and a solution picks placements covering all 324 of these constraints exactly once
+//!
+//! It is much faster than backtracking on hard puzzles and for counting solutions,
+//! and is useful as a cross-check of the backtracking solver
+
+use crate::board::{Board, Index};
+
+/// Number of constraints each solution must cover
+const CONSTRAINTS: usize = 324;
+
+/// Toroidal doubly linked lists over a sparse 0/1 matrix, stored as index arrays
+/// Node 0 is the root, nodes 1 to 324 are the column headers and the rest are matrix entries
+struct Links {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    /// Column header of each node
+    column: Vec<usize>,
+    /// Placement each node belongs to, numbered row * 81 + column * 9 + value - 1
+    placement: Vec<usize>,
+    /// Entries left in each column, indexed by header
+    size: Vec<usize>,
+    /// First node of each placement
+    first: Vec<usize>,
+}
+
+impl Links {
+    /// Build the matrix of every possible placement on an empty board
+    fn new() -> Self {
+        let headers = CONSTRAINTS + 1;
+        let mut links = Links {
+            left: (0..headers).map(|i| (i + headers - 1) % headers).collect(),
+            right: (0..headers).map(|i| (i + 1) % headers).collect(),
+            up: (0..headers).collect(),
+            down: (0..headers).collect(),
+            column: (0..headers).collect(),
+            placement: vec![0; headers],
+            size: vec![0; headers],
+            first: Vec::with_capacity(729),
+        };
+        for row in 0..9 {
+            for col in 0..9 {
+                for value in 0..9 {
+                    let subgrid = (row / 3) * 3 + col / 3;
+                    links.add_placement([
+                        row * 9 + col,
+                        81 + row * 9 + value,
+                        162 + col * 9 + value,
+                        243 + subgrid * 9 + value,
+                    ]);
+                }
+            }
+        }
+        links
+    }
+    /// Add a placement covering the given constraints
+    fn add_placement(&mut self, constraints: [usize; 4]) {
+        let first = self.column.len();
+        let placement = self.first.len();
+        self.first.push(first);
+        for (k, constraint) in constraints.iter().enumerate() {
+            let node = first + k;
+            let header = constraint + 1;
+            self.column.push(header);
+            self.placement.push(placement);
+            self.up.push(self.up[header]);
+            self.down.push(header);
+            let above = self.up[header];
+            self.down[above] = node;
+            self.up[header] = node;
+            self.size[header] += 1;
+            self.left.push(first + (k + 3) % 4);
+            self.right.push(first + (k + 1) % 4);
+        }
+    }
+    /// Remove a column and every row with an entry in it
+    fn cover(&mut self, header: usize) {
+        let (left, right) = (self.left[header], self.right[header]);
+        self.right[left] = right;
+        self.left[right] = left;
+        let mut i = self.down[header];
+        while i != header {
+            let mut j = self.right[i];
+            while j != i {
+                let (up, down) = (self.up[j], self.down[j]);
+                self.up[down] = up;
+                self.down[up] = down;
+                self.size[self.column[j]] -= 1;
+                j = self.right[j];
+            }
+            i = self.down[i];
+        }
+    }
+    /// Undo cover, in exactly the reverse order
+    fn uncover(&mut self, header: usize) {
+        let mut i = self.up[header];
+        while i != header {
+            let mut j = self.left[i];
+            while j != i {
+                self.size[self.column[j]] += 1;
+                let (up, down) = (self.up[j], self.down[j]);
+                self.up[down] = j;
+                self.down[up] = j;
+                j = self.left[j];
+            }
+            i = self.up[i];
+        }
+        let (left, right) = (self.left[header], self.right[header]);
+        self.right[left] = header;
+        self.left[right] = header;
+    }
+    /// Cover every column of a placement except the one holding node
+    fn cover_row(&mut self, node: usize) {
+        let mut j = self.right[node];
+        while j != node {
+            self.cover(self.column[j]);
+            j = self.right[j];
+        }
+    }
+    /// Undo cover_row
+    fn uncover_row(&mut self, node: usize) {
+        let mut j = self.left[node];
+        while j != node {
+            self.uncover(self.column[j]);
+            j = self.left[j];
+        }
+    }
+    /// Visit every exact cover extending the chosen placements until visit returns true
+    /// Returns true if the search was stopped by visit, leaving the links partially covered
+    fn search(&mut self, chosen: &mut Vec<usize>, visit: &mut dyn FnMut(&[usize]) -> bool) -> bool {
+        if self.right[0] == 0 {
+            return visit(chosen);
+        }
+        // branch on the column with the fewest entries
+        let mut header = self.right[0];
+        let mut j = self.right[header];
+        while j != 0 {
+            if self.size[j] < self.size[header] {
+                header = j;
+            }
+            j = self.right[j];
+        }
+        if self.size[header] == 0 {
+            return false;
+        }
+        self.cover(header);
+        let mut node = self.down[header];
+        while node != header {
+            chosen.push(self.placement[node]);
+            self.cover_row(node);
+            if self.search(chosen, visit) {
+                return true;
+            }
+            self.uncover_row(node);
+            chosen.pop();
+            node = self.down[node];
+        }
+        self.uncover(header);
+        false
+    }
+}
+
+/// Visit the solutions of the board until visit returns true
+fn explore(board: &Board, visit: &mut dyn FnMut(&Board) -> bool) {
+    if !board.is_valid_board() {
+        return;
+    }
+    let mut links = Links::new();
+    for i in 0..9 {
+        for j in 0..9 {
+            if let Some(value) = board.get_cell(&Index(i, j)) {
+                let node = links.first[i * 81 + j * 9 + value as usize - 1];
+                links.cover(links.column[node]);
+                links.cover_row(node);
+            }
+        }
+    }
+    links.search(&mut Vec::new(), &mut |chosen| {
+        let mut solved = board.clone();
+        for placement in chosen {
+            let index = Index(placement / 81, (placement / 9) % 9);
+            solved.update_cell(&index, (placement % 9) as u8 + 1);
+        }
+        visit(&solved)
+    });
+}
+
+/// Solve the board with Dancing Links
+/// Returns the same solution as solve for puzzles with a unique solution,
+/// but may return a different one when there are several
+pub fn solve_dlx(board: &Board) -> Option<Board> {
+    let mut solution = None;
+    explore(board, &mut |solved| {
+        solution = Some(solved.clone());
+        true
+    });
+    solution
+}
+
+/// Count the solutions of the board with Dancing Links, stopping once limit solutions are found
+/// With no limit every solution is counted
+pub fn count_solutions_dlx(board: &Board, limit: Option<usize>) -> usize {
+    let limit = limit.unwrap_or(usize::MAX);
+    let mut count = 0;
+    if limit > 0 {
+        explore(board, &mut |_| {
+            count += 1;
+            count >= limit
+        });
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{count_solutions, solve};
+    use crate::fixtures::wikipedia_board;
+
+    #[test]
+    fn test_solve_dlx_agrees_with_backtracking() {
+        let hard: Board =
+            "800000000003600000070090200050007000000045700000100030001000068008500010090000400"
+                .parse()
+                .unwrap();
+        for board in [wikipedia_board(), hard] {
+            let solution = solve_dlx(&board).unwrap();
+            assert!(solution.is_complete() && solution.is_valid_board());
+            assert!(board.agrees_with(&solution));
+            assert_eq!(Some(solution), board.solved());
+        }
+    }
+
+    #[test]
+    fn test_solve_dlx_unsolvable() {
+        let mut invalid = wikipedia_board();
+        invalid.update_cell(&Index(0, 2), 5);
+        assert_eq!(solve_dlx(&invalid), None);
+
+        let mut unsolvable = wikipedia_board();
+        unsolvable.update_cell(&Index(0, 2), 1);
+        unsolvable.update_cell(&Index(0, 3), 2);
+        assert_eq!(solve_dlx(&unsolvable), unsolvable.solved());
+    }
+
+    #[test]
+    fn test_count_solutions_dlx() {
+        // removing every 1 and 2 from a solution leaves several ways to put them back
+        let mut board = solve(&mut wikipedia_board()).unwrap();
+        for i in 0..9 {
+            for j in 0..9 {
+                if matches!(board.get_cell(&Index(i, j)), Some(1 | 2)) {
+                    board.update_cell(&Index(i, j), 0);
+                }
+            }
+        }
+        assert_eq!(count_solutions(&board, None), 2);
+        assert_eq!(
+            count_solutions_dlx(&board, None),
+            count_solutions(&board, None)
+        );
+        assert_eq!(count_solutions_dlx(&wikipedia_board(), Some(2)), 1);
+        assert_eq!(
+            count_solutions_dlx(&Board::from_digits(&[[0; 9]; 9]), Some(50)),
+            50
+        );
+        assert_eq!(count_solutions_dlx(&wikipedia_board(), Some(0)), 0);
+    }
+}
@@ -0,0 +1,234 @@
+//! This module contains the error type returned when reading a board
+//! Rows and columns in errors are 0-based, like board indices,
+//! but are displayed 1-based in error messages
+
+use std::fmt::Display;
+
+/// The kinds of clue read one per line from their own files
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum ClueKind {
+    Cage,
+    Thermometer,
+    Sandwich,
+    Dot,
+    Mark,
+}
+
+impl Display for ClueKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ClueKind::Cage => "cage",
+            ClueKind::Thermometer => "thermometer",
+            ClueKind::Sandwich => "sandwich clue",
+            ClueKind::Dot => "kropki dot",
+            ClueKind::Mark => "XV mark",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// What the count of a dimension error measures
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum Dimension {
+    Rows,
+    Cells,
+}
+
+impl Display for Dimension {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Dimension::Rows => write!(f, "rows"),
+            Dimension::Cells => write!(f, "cells"),
+        }
+    }
+}
+
+/// Errors from reading a board
+#[derive(Debug)]
+pub enum SudokuError {
+    /// A cell could not be read as a number
+    ParseError {
+        row: usize,
+        column: usize,
+        found: String,
+    },
+    /// The input does not describe a grid of the expected size
+    /// If a single row has the wrong length, row holds it and found counts its cells
+    /// Otherwise found counts the rows or cells of the whole input, as unit says
+    DimensionError {
+        row: Option<usize>,
+        found: usize,
+        expected: usize,
+        unit: Dimension,
+    },
+    /// A cell holds a number outside the allowed range
+    InvalidValue {
+        row: usize,
+        column: usize,
+        value: u32,
+    },
+    /// The board breaks the sudoku rules, first at the given cell
+    RuleViolation { row: usize, column: usize },
+    /// A region of a jigsaw region map does not have 9 cells
+    InvalidRegion { label: char, cells: usize },
+    /// A clue read one per line, such as a killer cage or a thermometer, can't be read
+    /// or can never be met. line numbers the clues from 0 in input order,
+    /// leaving out blank and comment lines
+    InvalidClue {
+        kind: ClueKind,
+        line: usize,
+        reason: String,
+    },
+    /// An f-puzzles puzzle can't be read or uses a feature that isn't supported
+    InvalidFPuzzle { reason: String },
+    /// The input could not be read
+    Io(std::io::Error),
+}
+
+impl Display for SudokuError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SudokuError::ParseError { row, column, found } => write!(
+                f,
+                "Parse error at row {}, column {}: could not read {:?} as a cell",
+                row + 1,
+                column + 1,
+                found
+            ),
+            SudokuError::DimensionError {
+                row: Some(row),
+                found,
+                expected,
+                unit,
+            } => write!(
+                f,
+                "Invalid dimensions: row {} has {} {}, expected {}",
+                row + 1,
+                found,
+                unit,
+                expected
+            ),
+            SudokuError::DimensionError {
+                row: None,
+                found,
+                expected,
+                unit,
+            } => write!(
+                f,
+                "Invalid dimensions: found {} {}, expected {}",
+                found, unit, expected
+            ),
+            SudokuError::InvalidValue { row, column, value } => write!(
+                f,
+                "Invalid value at row {}, column {}: {} is not allowed",
+                row + 1,
+                column + 1,
+                value
+            ),
+            SudokuError::RuleViolation { row, column } => write!(
+                f,
+                "Invalid Board: Board does not satisfy sudoku rules at row {}, column {}",
+                row + 1,
+                column + 1
+            ),
+            SudokuError::InvalidRegion { label, cells } => write!(
+                f,
+                "Invalid regions: region {:?} has {} cells, expected 9",
+                label, cells
+            ),
+            SudokuError::InvalidClue { kind, line, reason } => {
+                write!(f, "Invalid {} {}: {}", kind, line + 1, reason)
+            }
+            SudokuError::InvalidFPuzzle { reason } => {
+                write!(f, "Invalid f-puzzles puzzle: {}", reason)
+            }
+            SudokuError::Io(e) => write!(f, "Could not read input: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SudokuError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SudokuError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for SudokuError {
+    fn from(e: std::io::Error) -> Self {
+        SudokuError::Io(e)
+    }
+}
+
+/// Errors are equal if they have the same variant and context.
+/// Io errors are compared by their kind, as std::io::Error isn't comparable
+impl PartialEq for SudokuError {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (SudokuError::Io(a), SudokuError::Io(b)) => a.kind() == b.kind(),
+            (
+                SudokuError::ParseError { row, column, found },
+                SudokuError::ParseError {
+                    row: other_row,
+                    column: other_column,
+                    found: other_found,
+                },
+            ) => (row, column, found) == (other_row, other_column, other_found),
+            (
+                SudokuError::DimensionError {
+                    row,
+                    found,
+                    expected,
+                    unit,
+                },
+                SudokuError::DimensionError {
+                    row: other_row,
+                    found: other_found,
+                    expected: other_expected,
+                    unit: other_unit,
+                },
+            ) => {
+                (row, found, expected, unit) == (other_row, other_found, other_expected, other_unit)
+            }
+            (
+                SudokuError::InvalidValue { row, column, value },
+                SudokuError::InvalidValue {
+                    row: other_row,
+                    column: other_column,
+                    value: other_value,
+                },
+            ) => (row, column, value) == (other_row, other_column, other_value),
+            (
+                SudokuError::RuleViolation { row, column },
+                SudokuError::RuleViolation {
+                    row: other_row,
+                    column: other_column,
+                },
+            ) => (row, column) == (other_row, other_column),
+            (
+                SudokuError::InvalidRegion { label, cells },
+                SudokuError::InvalidRegion {
+                    label: other_label,
+                    cells: other_cells,
+                },
+            ) => (label, cells) == (other_label, other_cells),
+            (
+                SudokuError::InvalidClue { kind, line, reason },
+                SudokuError::InvalidClue {
+                    kind: other_kind,
+                    line: other_line,
+                    reason: other_reason,
+                },
+            ) => (kind, line, reason) == (other_kind, other_line, other_reason),
+            (
+                SudokuError::InvalidFPuzzle { reason },
+                SudokuError::InvalidFPuzzle {
+                    reason: other_reason,
+                },
+            ) => reason == other_reason,
+            _ => false,
+        }
+    }
+}
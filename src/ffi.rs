@@ -0,0 +1,157 @@
+//! This module exposes the solver to C and C++ through `extern "C"` functions
+//! Boards are passed as buffers of 81 bytes, row by row, with 0 for empty cells
+//! The matching header is include/sudoku_solver.h, and can be regenerated with cbindgen
+//!
+//! Enabled by the `ffi` feature
+
+use crate::board::{count_solutions, Board};
+
+/// The board was solved, or is valid
+pub const SUDOKU_OK: i32 = 0;
+/// The buffer pointer was null
+pub const SUDOKU_NULL_POINTER: i32 = -1;
+/// A byte in the buffer was over 9
+pub const SUDOKU_INVALID_VALUE: i32 = -2;
+/// The board breaks the sudoku rules
+pub const SUDOKU_RULE_VIOLATION: i32 = -3;
+/// The board has no solution
+pub const SUDOKU_UNSOLVABLE: i32 = -4;
+
+/// Read a board from an 81 byte buffer, without checking the rules
+/// # Safety
+/// puzzle must be null or point to 81 readable bytes
+unsafe fn read_board(puzzle: *const u8) -> Result<Board, i32> {
+    if puzzle.is_null() {
+        return Err(SUDOKU_NULL_POINTER);
+    }
+    let bytes = std::slice::from_raw_parts(puzzle, 81);
+    if bytes.iter().any(|&byte| byte > 9) {
+        return Err(SUDOKU_INVALID_VALUE);
+    }
+    let mut digits = [[0; 9]; 9];
+    for (row, chunk) in digits.iter_mut().zip(bytes.chunks_exact(9)) {
+        row.copy_from_slice(chunk);
+    }
+    Ok(Board::from_digits(&digits))
+}
+
+/// Solve the puzzle, writing the solution into the solution buffer
+/// puzzle and solution may point to the same buffer to solve in place
+/// Returns SUDOKU_OK on success, or a negative error code, in which case solution is left untouched
+/// # Safety
+/// puzzle must be null or point to 81 readable bytes,
+/// and solution must be null or point to 81 writable bytes
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_solve(puzzle: *const u8, solution: *mut u8) -> i32 {
+    let board = match read_board(puzzle) {
+        Ok(board) => board,
+        Err(code) => return code,
+    };
+    if solution.is_null() {
+        return SUDOKU_NULL_POINTER;
+    }
+    if !board.is_valid_board() {
+        return SUDOKU_RULE_VIOLATION;
+    }
+    let Some(solved) = board.solved() else {
+        return SUDOKU_UNSOLVABLE;
+    };
+    let out = std::slice::from_raw_parts_mut(solution, 81);
+    for (byte, (_, value)) in out.iter_mut().zip(solved.cells()) {
+        *byte = value.unwrap_or(0);
+    }
+    SUDOKU_OK
+}
+
+/// Check the puzzle against the sudoku rules, without solving it
+/// Returns SUDOKU_OK if no unit repeats a value, or a negative error code
+/// # Safety
+/// puzzle must be null or point to 81 readable bytes
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_validate(puzzle: *const u8) -> i32 {
+    match read_board(puzzle) {
+        Ok(board) if board.is_valid_board() => SUDOKU_OK,
+        Ok(_) => SUDOKU_RULE_VIOLATION,
+        Err(code) => code,
+    }
+}
+
+/// Count the solutions of the puzzle, stopping once limit are found
+/// A limit of 2 is enough to tell whether a puzzle has a unique solution
+/// Returns the count, or a negative error code
+/// # Safety
+/// puzzle must be null or point to 81 readable bytes
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_count_solutions(puzzle: *const u8, limit: u32) -> i32 {
+    match read_board(puzzle) {
+        Ok(board) if board.is_valid_board() => {
+            let limit = limit.min(i32::MAX as u32) as usize;
+            count_solutions(&board, Some(limit)) as i32
+        }
+        Ok(_) => SUDOKU_RULE_VIOLATION,
+        Err(code) => code,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::EASY;
+    use std::ptr;
+
+    fn easy() -> [u8; 81] {
+        let mut buffer = [0; 81];
+        for (byte, c) in buffer.iter_mut().zip(EASY.chars()) {
+            *byte = c.to_digit(10).unwrap_or(0) as u8;
+        }
+        buffer
+    }
+
+    #[test]
+    fn test_solve() {
+        let puzzle = easy();
+        let mut solution = [0; 81];
+        let code = unsafe { sudoku_solve(puzzle.as_ptr(), solution.as_mut_ptr()) };
+        assert_eq!(code, SUDOKU_OK);
+        assert_eq!(solution[..9], [9, 7, 3, 8, 5, 4, 2, 1, 6]);
+
+        // solving in place, with one pointer for both arguments as C callers would pass
+        let mut buffer = easy();
+        let pointer = buffer.as_mut_ptr();
+        let code = unsafe { sudoku_solve(pointer, pointer) };
+        assert_eq!(code, SUDOKU_OK);
+        assert_eq!(buffer, solution);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut puzzle = easy();
+        let mut solution = [0; 81];
+        unsafe {
+            assert_eq!(
+                sudoku_solve(ptr::null(), solution.as_mut_ptr()),
+                SUDOKU_NULL_POINTER
+            );
+            assert_eq!(
+                sudoku_solve(puzzle.as_ptr(), ptr::null_mut()),
+                SUDOKU_NULL_POINTER
+            );
+            puzzle[0] = 7;
+            assert_eq!(sudoku_validate(puzzle.as_ptr()), SUDOKU_RULE_VIOLATION);
+            puzzle[0] = 10;
+            assert_eq!(sudoku_validate(puzzle.as_ptr()), SUDOKU_INVALID_VALUE);
+        }
+        assert_eq!(solution, [0; 81]);
+    }
+
+    #[test]
+    fn test_validate_and_count() {
+        let puzzle = easy();
+        let empty = [0; 81];
+        unsafe {
+            assert_eq!(sudoku_validate(puzzle.as_ptr()), SUDOKU_OK);
+            assert_eq!(sudoku_count_solutions(puzzle.as_ptr(), 2), 1);
+            assert_eq!(sudoku_count_solutions(empty.as_ptr(), 2), 2);
+        }
+    }
+}
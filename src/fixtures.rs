@@ -0,0 +1,87 @@
+//! This module contains the boards shared by the unit tests of the other modules
+
+use crate::board::{Board, Search};
+use crate::constraint::Constraint;
+
+/// The puzzle of tests/test_board_pass.csv as a single line
+pub(crate) const EASY: &str =
+    ".738.4216...2.95..2856.3.97...3...7475....3.1..4.2.....9756.......7..1..42..3..6.";
+
+/// The board of EASY
+pub(crate) fn easy_board() -> Board {
+    EASY.parse().unwrap()
+}
+
+/// The solution of EASY, from tests/test_board_pass_solution.csv
+pub(crate) fn easy_solution() -> Board {
+    Board::from_csv("tests/test_board_pass_solution.csv").unwrap()
+}
+
+/// Get the solution of EASY with the given rows cleared
+/// Rows of a band can swap places, so clearing two rows of a band leaves several classic solutions
+pub(crate) fn easy_solution_without_rows(rows: &[usize]) -> Board {
+    let mut puzzle = easy_solution();
+    for (index, _) in easy_solution().cells() {
+        if rows.contains(&index.row()) {
+            puzzle.update_cell(&index, 0);
+        }
+    }
+    puzzle.as_puzzle()
+}
+
+/// Check that the constraint narrows the solutions of the puzzle down to the solution of EASY alone
+pub(crate) fn assert_only_easy_solution(puzzle: &Board, constraint: &dyn Constraint) {
+    let mut solutions = Vec::new();
+    Search::new()
+        .with_constraint(constraint)
+        .explore(&mut puzzle.clone(), &mut |solved| {
+            solutions.push(solved.clone());
+            solutions.len() >= 2
+        });
+    assert_eq!(solutions, vec![easy_solution()]);
+}
+
+/// A puzzle with a unique solution, given by make_solved_board
+pub(crate) fn make_board() -> Board {
+    Board::from_digits(&[
+        [0, 0, 3, 4, 0, 7, 0, 6, 0],
+        [7, 0, 0, 0, 0, 0, 0, 4, 0],
+        [0, 0, 0, 0, 1, 0, 2, 5, 0],
+        [4, 8, 0, 3, 0, 0, 1, 0, 0],
+        [0, 5, 0, 0, 0, 0, 0, 0, 2],
+        [0, 6, 0, 0, 2, 0, 0, 0, 0],
+        [0, 9, 0, 1, 0, 5, 0, 0, 8],
+        [1, 0, 0, 6, 0, 0, 0, 0, 5],
+        [0, 0, 0, 0, 0, 0, 4, 0, 0],
+    ])
+}
+
+/// The solution of make_board
+pub(crate) fn make_solved_board() -> Board {
+    Board::from_digits(&[
+        [5, 2, 3, 4, 8, 7, 9, 6, 1],
+        [7, 1, 9, 5, 6, 2, 8, 4, 3],
+        [8, 4, 6, 9, 1, 3, 2, 5, 7],
+        [4, 8, 2, 3, 5, 9, 1, 7, 6],
+        [9, 5, 1, 7, 4, 6, 3, 8, 2],
+        [3, 6, 7, 8, 2, 1, 5, 9, 4],
+        [2, 9, 4, 1, 7, 5, 6, 3, 8],
+        [1, 3, 8, 6, 9, 4, 7, 2, 5],
+        [6, 7, 5, 2, 3, 8, 4, 1, 9],
+    ])
+}
+
+/// The example puzzle of the Wikipedia article on sudoku
+pub(crate) fn wikipedia_board() -> Board {
+    Board::from_digits(&[
+        [5, 3, 0, 0, 7, 0, 0, 0, 0],
+        [6, 0, 0, 1, 9, 5, 0, 0, 0],
+        [0, 9, 8, 0, 0, 0, 0, 6, 0],
+        [8, 0, 0, 0, 6, 0, 0, 0, 3],
+        [4, 0, 0, 8, 0, 3, 0, 0, 1],
+        [7, 0, 0, 0, 2, 0, 0, 0, 6],
+        [0, 6, 0, 0, 0, 0, 2, 8, 0],
+        [0, 0, 0, 4, 1, 9, 0, 0, 5],
+        [0, 0, 0, 0, 8, 0, 0, 7, 9],
+    ])
+}
@@ -0,0 +1,839 @@
+//! This module reads and writes puzzles in the f-puzzles JSON format, also used by SudokuPad,
+//! so variant puzzles built in those setting tools can be solved here
+//! and puzzles made here can be played in the browser
+//!
+//! The JSON can be read directly or from a link, where it is compressed with LZ-String
+//! into the `load` parameter of an f-puzzles link or follows `fpuzzles` in a SudokuPad link
+//!
+//! Supported features are the givens of a 9x9 grid, both diagonals, anti-knight, anti-king,
+//! the hyper windows as extra regions, killer cages, thermometers, sandwich sums,
+//! white and black kropki dots, XV marks with their negative constraint, and odd and even cells.
+//! Puzzles using any other feature are rejected rather than solved under the wrong rules
+
+use crate::board::{solve_with_constraints, Board, Index, Parity, Unit, Variant};
+use crate::constraint::Constraint;
+use crate::error::SudokuError;
+use crate::killer::{Cage, Killer};
+use crate::kropki::{Color, Dot};
+use crate::lzstring::{compress_to_base64, decompress_from_base64};
+use crate::parity::Parities;
+use crate::sandwich::Sandwich;
+use crate::thermometer::Thermometer;
+use crate::xv::{Mark, Marks, Sum};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Keys that only change how a puzzle looks, which are skipped when reading it
+const COSMETIC: [&str; 9] = [
+    "ruleset",
+    "solution",
+    "text",
+    "line",
+    "rectangle",
+    "circle",
+    "cage",
+    "disabledlogic",
+    "truecandidatesoptions",
+];
+
+/// A puzzle as it is laid out in f-puzzles JSON
+#[derive(Deserialize, Serialize)]
+struct Json {
+    size: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    author: Option<String>,
+    grid: Vec<Vec<JsonCell>>,
+    #[serde(rename = "diagonal+", default, skip_serializing_if = "is_false")]
+    diagonal_positive: bool,
+    #[serde(rename = "diagonal-", default, skip_serializing_if = "is_false")]
+    diagonal_negative: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    antiknight: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    antiking: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    extraregion: Vec<JsonCells>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    killercage: Vec<JsonCells>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    thermometer: Vec<JsonLines>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    sandwichsum: Vec<JsonClue>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    difference: Vec<JsonCells>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    ratio: Vec<JsonCells>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    xv: Vec<JsonCells>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    odd: Vec<JsonClue>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    even: Vec<JsonClue>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    negative: Vec<String>,
+    /// Every other key, which must be cosmetic or empty
+    #[serde(flatten)]
+    other: BTreeMap<String, Value>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct JsonCell {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<u8>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    given: bool,
+    /// The region of a jigsaw grid, which isn't supported
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<Value>,
+}
+
+/// A feature covering some cells, with an optional value such as a cage sum or an X or V
+#[derive(Deserialize, Serialize)]
+struct JsonCells {
+    cells: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct JsonLines {
+    lines: Vec<Vec<String>>,
+}
+
+/// A feature on a single cell, such as a sandwich sum outside the grid or an odd cell
+#[derive(Deserialize, Serialize)]
+struct JsonClue {
+    cell: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+}
+
+/// A variant puzzle in the form f-puzzles knows, made of its givens and the constraints on top of them
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct FPuzzle {
+    title: Option<String>,
+    author: Option<String>,
+    board: Board,
+    variants: Vec<Variant>,
+    cages: Vec<Cage>,
+    thermometers: Vec<Thermometer>,
+    sandwiches: Vec<Sandwich>,
+    dots: Vec<Dot>,
+    marks: Marks,
+    parities: Parities,
+}
+
+/// Check whether a flag is off, so it can be left out of the JSON
+fn is_false(flag: &bool) -> bool {
+    !flag
+}
+
+/// Get the name f-puzzles uses for a cell, eg `R1C1`
+fn name(index: &Index) -> String {
+    format!("R{}C{}", index.0 + 1, index.1 + 1)
+}
+
+/// Get the cells of the four hyper windows as rows and columns, row by row
+fn windows() -> Vec<Vec<(usize, usize)>> {
+    [(1, 1), (1, 5), (5, 1), (5, 5)]
+        .iter()
+        .map(|&(top, left)| (0..9).map(|k| (top + k / 3, left + k % 3)).collect())
+        .collect()
+}
+
+/// Make the error for a puzzle that can't be read
+fn invalid(reason: impl Into<String>) -> SudokuError {
+    SudokuError::InvalidFPuzzle {
+        reason: reason.into(),
+    }
+}
+
+/// Read a cell in the R1C1 notation f-puzzles uses
+fn cell(cell: &str) -> Result<Index, SudokuError> {
+    Index::from_r1c1(cell).ok_or_else(|| invalid(format!("could not read {:?} as a cell", cell)))
+}
+
+/// Read the two cells of a dot or mark
+fn pair(cells: &[String]) -> Result<(Index, Index), SudokuError> {
+    match cells {
+        [first, second] => Ok((cell(first)?, cell(second)?)),
+        _ => Err(invalid(format!(
+            "expected two cells, found {}",
+            cells.len()
+        ))),
+    }
+}
+
+/// Read a number given as a string, as f-puzzles stores cage sums and sandwich sums
+fn number(value: &Option<String>) -> Result<u8, SudokuError> {
+    let value = value.as_deref().unwrap_or_default();
+    value
+        .parse()
+        .map_err(|_| invalid(format!("could not read {:?} as a number", value)))
+}
+
+/// Check whether a JSON value is empty, as f-puzzles leaves unused features as empty arrays
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null | Value::Bool(false) => true,
+        Value::Array(values) => values.is_empty(),
+        Value::Object(values) => values.is_empty(),
+        _ => false,
+    }
+}
+
+impl FPuzzle {
+    /// Read a puzzle from f-puzzles JSON
+    /// # Errors
+    /// Returns SudokuError::InvalidFPuzzle if the JSON can't be read, the grid isn't 9x9,
+    /// a feature isn't supported or is malformed, or the givens break the rules
+    pub fn from_json(json: &str) -> Result<FPuzzle, SudokuError> {
+        let json: Json = serde_json::from_str(json).map_err(|e| invalid(e.to_string()))?;
+        if let Some((key, _)) = json
+            .other
+            .iter()
+            .find(|(key, value)| !COSMETIC.contains(&key.as_str()) && !is_empty(value))
+        {
+            return Err(invalid(format!("{} is not supported", key)));
+        }
+        if json.size != 9 || json.grid.len() != 9 || json.grid.iter().any(|row| row.len() != 9) {
+            return Err(invalid(format!(
+                "the grid is {0}x{0}, expected 9x9",
+                json.size
+            )));
+        }
+
+        let mut cells = [[0; 9]; 9];
+        for (row, values) in json.grid.iter().enumerate() {
+            for (column, value) in values.iter().enumerate() {
+                if value.region.is_some() {
+                    return Err(invalid("irregular regions are not supported"));
+                }
+                if value.given {
+                    cells[row][column] = value.value.unwrap_or_default();
+                }
+            }
+        }
+        let board = Board::try_new(&cells).map_err(|e| invalid(e.to_string()))?;
+
+        let mut variants = Vec::new();
+        match (json.diagonal_positive, json.diagonal_negative) {
+            (true, true) => variants.push(Variant::Diagonal),
+            (false, false) => {}
+            _ => return Err(invalid("a single diagonal is not supported")),
+        }
+        if json.antiknight {
+            variants.push(Variant::AntiKnight);
+        }
+        if json.antiking {
+            variants.push(Variant::AntiKing);
+        }
+        if !json.extraregion.is_empty() {
+            // regions are compared as sorted rows and columns, whatever order they are listed in
+            let mut regions: Vec<Vec<(usize, usize)>> = json
+                .extraregion
+                .iter()
+                .map(|region| {
+                    let mut cells = region
+                        .cells
+                        .iter()
+                        .map(|name| cell(name).map(|index| (index.0, index.1)))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    cells.sort();
+                    Ok(cells)
+                })
+                .collect::<Result<_, SudokuError>>()?;
+            regions.sort();
+            if regions != windows() {
+                return Err(invalid(
+                    "extra regions other than the hyper windows are not supported",
+                ));
+            }
+            variants.push(Variant::Hyper);
+        }
+
+        let cages = json
+            .killercage
+            .iter()
+            .map(|cage| {
+                let cells = cage
+                    .cells
+                    .iter()
+                    .map(|name| cell(name))
+                    .collect::<Result<_, _>>()?;
+                // a cage without a value only stops its digits repeating
+                match cage.value {
+                    Some(_) => Ok(Cage::new(number(&cage.value)?, cells)),
+                    None => Ok(Cage::without_sum(cells)),
+                }
+            })
+            .collect::<Result<Vec<_>, SudokuError>>()?;
+        Killer::try_new(board.clone(), cages.clone()).map_err(|e| invalid(e.to_string()))?;
+
+        let thermometers = json
+            .thermometer
+            .iter()
+            .flat_map(|thermometer| &thermometer.lines)
+            .map(|line| {
+                let cells = line
+                    .iter()
+                    .map(|name| cell(name))
+                    .collect::<Result<_, _>>()?;
+                Thermometer::new(cells).map_err(invalid)
+            })
+            .collect::<Result<_, _>>()?;
+
+        // sandwich sums sit outside the grid, in row 0 above a column or column 0 left of a row
+        let sandwiches = json
+            .sandwichsum
+            .iter()
+            .map(|clue| {
+                let position =
+                    clue.cell
+                        .to_ascii_lowercase()
+                        .strip_prefix('r')
+                        .and_then(|position| {
+                            let (row, column) = position.split_once('c')?;
+                            Some((row.parse::<usize>().ok()?, column.parse::<usize>().ok()?))
+                        });
+                let line = match position {
+                    Some((0, column @ 1..=9)) => Unit::Column(column - 1),
+                    Some((row @ 1..=9, 0)) => Unit::Row(row - 1),
+                    _ => return Err(invalid(format!("{:?} is not outside the grid", clue.cell))),
+                };
+                Sandwich::new(line, number(&clue.value)?).map_err(invalid)
+            })
+            .collect::<Result<_, _>>()?;
+
+        let mut dots = Vec::new();
+        for (features, color, value) in [
+            (&json.difference, Color::White, "1"),
+            (&json.ratio, Color::Black, "2"),
+        ] {
+            for dot in features {
+                if dot.value.as_deref().is_some_and(|dot| dot != value) {
+                    return Err(invalid(format!(
+                        "dots of value {:?} are not supported",
+                        dot.value
+                    )));
+                }
+                let (first, second) = pair(&dot.cells)?;
+                dots.push(Dot::new(color, first, second).map_err(invalid)?);
+            }
+        }
+
+        let marks = json
+            .xv
+            .iter()
+            .map(|mark| {
+                let sum = match mark.value.as_deref() {
+                    Some("X" | "x") => Sum::X,
+                    Some("V" | "v") => Sum::V,
+                    _ => {
+                        return Err(invalid(format!(
+                            "could not read {:?} as X or V",
+                            mark.value
+                        )))
+                    }
+                };
+                let (first, second) = pair(&mark.cells)?;
+                Mark::new(sum, first, second).map_err(invalid)
+            })
+            .collect::<Result<_, _>>()?;
+        let mut marks = Marks::new(marks);
+        for negative in &json.negative {
+            match negative.as_str() {
+                "xv" => marks = marks.with_negative_constraint(),
+                _ => return Err(invalid(format!("negative {} is not supported", negative))),
+            }
+        }
+
+        let mut parities = Vec::new();
+        for (clues, parity) in [(&json.odd, Parity::Odd), (&json.even, Parity::Even)] {
+            for clue in clues {
+                parities.push((cell(&clue.cell)?, parity));
+            }
+        }
+
+        Ok(FPuzzle {
+            title: json.title,
+            author: json.author,
+            board,
+            variants,
+            cages,
+            thermometers,
+            sandwiches,
+            dots,
+            marks,
+            parities: Parities::new(&parities),
+        })
+    }
+    /// Read a puzzle from an f-puzzles or SudokuPad link, or from the compressed text alone
+    /// # Example
+    /// ```
+    /// use sudoku_solver_by_roy::fpuzzles::FPuzzle;
+    /// let url = std::fs::read_to_string("tests/test_fpuzzles_url.txt").unwrap();
+    /// let puzzle = FPuzzle::from_url(url.trim()).unwrap();
+    /// assert_eq!(puzzle.title(), Some("Example"));
+    /// assert_eq!(puzzle.solved().unwrap().get(0, 0), Ok(Some(9)));
+    /// ```
+    /// # Errors
+    /// Returns SudokuError::InvalidFPuzzle if the link can't be decompressed
+    /// or holds a puzzle that can't be read
+    pub fn from_url(url: &str) -> Result<FPuzzle, SudokuError> {
+        let compressed = match (url.split_once("load="), url.split_once("fpuzzles")) {
+            (Some((_, compressed)), _) | (None, Some((_, compressed))) => compressed,
+            (None, None) => url,
+        };
+        // undo the escaping of the base64 characters links may apply
+        let compressed = compressed
+            .split('&')
+            .next()
+            .unwrap_or_default()
+            .replace("%2B", "+")
+            .replace("%2F", "/")
+            .replace("%3D", "=")
+            .replace(' ', "+");
+        let json = decompress_from_base64(&compressed)
+            .ok_or_else(|| invalid("the link could not be decompressed"))?;
+        FPuzzle::from_json(&json)
+    }
+    /// Make a puzzle of the givens of a board, with no rules beyond the usual ones
+    /// Rules are added with the with_ methods, eg to export a generated puzzle
+    /// # Example
+    /// ```
+    /// use sudoku_solver_by_roy::board::{Board, Variant};
+    /// use sudoku_solver_by_roy::fpuzzles::FPuzzle;
+    /// let board = Board::from_csv("tests/test_board_pass.csv").unwrap();
+    /// let url = FPuzzle::new(board)
+    ///     .with_title("Example")
+    ///     .with_variant(Variant::AntiKing)
+    ///     .to_url();
+    /// assert!(url.starts_with("https://www.f-puzzles.com/?load="));
+    /// assert_eq!(FPuzzle::from_url(&url).unwrap().title(), Some("Example"));
+    /// ```
+    pub fn new(board: Board) -> Self {
+        FPuzzle {
+            title: None,
+            author: None,
+            board: board.clues(),
+            variants: Vec::new(),
+            cages: Vec::new(),
+            thermometers: Vec::new(),
+            sandwiches: Vec::new(),
+            dots: Vec::new(),
+            marks: Marks::default(),
+            parities: Parities::default(),
+        }
+    }
+    /// Set the title shown with the puzzle
+    pub fn with_title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+    /// Set the author shown with the puzzle
+    pub fn with_author(mut self, author: &str) -> Self {
+        self.author = Some(author.to_string());
+        self
+    }
+    /// Add the rules of a variant, which does nothing for the classic rules
+    pub fn with_variant(mut self, variant: Variant) -> Self {
+        if variant != Variant::Classic && !self.variants.contains(&variant) {
+            self.variants.push(variant);
+        }
+        self
+    }
+    /// Add killer cages
+    /// # Errors
+    /// Returns the error of Killer::try_new if the cages don't fit each other or the givens
+    pub fn with_cages(mut self, cages: Vec<Cage>) -> Result<Self, SudokuError> {
+        self.cages.extend(cages);
+        Killer::try_new(self.board.clone(), self.cages.clone())?;
+        Ok(self)
+    }
+    /// Add thermometers
+    pub fn with_thermometers(mut self, thermometers: Vec<Thermometer>) -> Self {
+        self.thermometers.extend(thermometers);
+        self
+    }
+    /// Add sandwich sums
+    pub fn with_sandwiches(mut self, sandwiches: Vec<Sandwich>) -> Self {
+        self.sandwiches.extend(sandwiches);
+        self
+    }
+    /// Add kropki dots
+    pub fn with_dots(mut self, dots: Vec<Dot>) -> Self {
+        self.dots.extend(dots);
+        self
+    }
+    /// Set the XV marks and whether they use the negative constraint
+    pub fn with_marks(mut self, marks: Marks) -> Self {
+        self.marks = marks;
+        self
+    }
+    /// Set the odd and even cells
+    pub fn with_parities(mut self, parities: Parities) -> Self {
+        self.parities = parities;
+        self
+    }
+    /// Get the title of the puzzle, if it has one
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+    /// Get the author of the puzzle, if it has one
+    pub fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+    /// Get the givens of the puzzle
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+    /// Get every rule of the puzzle on top of the usual rows, columns and boxes
+    pub fn constraints(&self) -> Vec<Box<dyn Constraint + '_>> {
+        let mut constraints: Vec<Box<dyn Constraint + '_>> = Vec::new();
+        for variant in &self.variants {
+            constraints.extend(variant.constraints());
+        }
+        if !self.cages.is_empty() {
+            let killer = Killer::try_new(self.board.clone(), self.cages.clone())
+                .expect("cages are checked when the puzzle is read");
+            constraints.push(Box::new(killer));
+        }
+        constraints.push(Box::new(self.thermometers.as_slice()));
+        constraints.push(Box::new(self.sandwiches.as_slice()));
+        constraints.push(Box::new(self.dots.as_slice()));
+        constraints.push(Box::new(&self.marks));
+        constraints.push(Box::new(self.parities));
+        constraints
+    }
+    /// Solve the puzzle under all of its rules, returning None if it has no solution
+    pub fn solved(&self) -> Option<Board> {
+        let constraints = self.constraints();
+        let constraints: Vec<&dyn Constraint> = constraints.iter().map(|c| &**c as _).collect();
+        solve_with_constraints(&mut self.board.clone(), &constraints)
+    }
+    /// Write the puzzle as f-puzzles JSON
+    pub fn to_json(&self) -> String {
+        let grid = (0..9)
+            .map(|row| {
+                (0..9)
+                    .map(|column| {
+                        let value = self.board.get_cell(&Index(row, column));
+                        JsonCell {
+                            value,
+                            given: value.is_some(),
+                            region: None,
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        let cells = |cells: &[Index]| cells.iter().map(name).collect();
+        let pairs = |color: Color| {
+            self.dots
+                .iter()
+                .filter(|dot| dot.color() == color)
+                .map(|dot| JsonCells {
+                    cells: cells(&dot.cells()),
+                    value: None,
+                })
+                .collect()
+        };
+        let parity = |parity: Parity| {
+            self.parities
+                .marks()
+                .into_iter()
+                .filter(|&(_, marked)| marked == parity)
+                .map(|(index, _)| JsonClue {
+                    cell: name(&index),
+                    value: None,
+                })
+                .collect()
+        };
+        let json = Json {
+            size: 9,
+            title: self.title.clone(),
+            author: self.author.clone(),
+            grid,
+            diagonal_positive: self.variants.contains(&Variant::Diagonal),
+            diagonal_negative: self.variants.contains(&Variant::Diagonal),
+            antiknight: self.variants.contains(&Variant::AntiKnight),
+            antiking: self.variants.contains(&Variant::AntiKing),
+            extraregion: if self.variants.contains(&Variant::Hyper) {
+                windows()
+                    .into_iter()
+                    .map(|window| JsonCells {
+                        cells: window
+                            .into_iter()
+                            .map(|(row, column)| name(&Index(row, column)))
+                            .collect(),
+                        value: None,
+                    })
+                    .collect()
+            } else {
+                Vec::new()
+            },
+            killercage: self
+                .cages
+                .iter()
+                .map(|cage| JsonCells {
+                    cells: cells(cage.cells()),
+                    value: cage.sum().map(|sum| sum.to_string()),
+                })
+                .collect(),
+            thermometer: self
+                .thermometers
+                .iter()
+                .map(|thermometer| JsonLines {
+                    lines: vec![cells(thermometer.cells())],
+                })
+                .collect(),
+            sandwichsum: self
+                .sandwiches
+                .iter()
+                .map(|sandwich| JsonClue {
+                    cell: match sandwich.line() {
+                        Unit::Row(row) => format!("R{}C0", row + 1),
+                        Unit::Column(column) => format!("R0C{}", column + 1),
+                        Unit::Subgrid(_) => {
+                            unreachable!("sandwiches are only made for rows and columns")
+                        }
+                    },
+                    value: Some(sandwich.sum().to_string()),
+                })
+                .collect(),
+            difference: pairs(Color::White),
+            ratio: pairs(Color::Black),
+            xv: self
+                .marks
+                .marks()
+                .iter()
+                .map(|mark| JsonCells {
+                    cells: cells(&mark.cells()),
+                    value: Some(
+                        match mark.sum() {
+                            Sum::X => "X",
+                            Sum::V => "V",
+                        }
+                        .to_string(),
+                    ),
+                })
+                .collect(),
+            odd: parity(Parity::Odd),
+            even: parity(Parity::Even),
+            negative: if self.marks.is_negative() {
+                vec!["xv".to_string()]
+            } else {
+                Vec::new()
+            },
+            other: BTreeMap::new(),
+        };
+        serde_json::to_string(&json).expect("f-puzzles JSON is always serializable")
+    }
+    /// Get an f-puzzles link that opens the puzzle in the browser
+    pub fn to_url(&self) -> String {
+        format!(
+            "https://www.f-puzzles.com/?load={}",
+            compress_to_base64(&self.to_json())
+        )
+    }
+    /// Get a SudokuPad link that opens the puzzle in the browser
+    pub fn to_sudokupad_url(&self) -> String {
+        format!(
+            "https://sudokupad.app/fpuzzles{}",
+            compress_to_base64(&self.to_json())
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{easy_board, easy_solution};
+
+    fn json() -> String {
+        std::fs::read_to_string("tests/test_fpuzzles.json").unwrap()
+    }
+
+    #[test]
+    fn test_from_json() {
+        let puzzle = FPuzzle::from_json(&json()).unwrap();
+        assert_eq!(puzzle.title(), Some("Example"));
+        assert_eq!(puzzle.author(), Some("abroy77"));
+        assert_eq!(puzzle.board(), &easy_board());
+        assert_eq!(puzzle.cages.len(), 2);
+        assert_eq!(puzzle.thermometers[1].to_string(), "r3c5 r2c4 r1c4");
+        assert_eq!(puzzle.sandwiches[0].to_string(), "r1 29");
+        assert_eq!(puzzle.sandwiches[1].to_string(), "c1 0");
+        assert_eq!(puzzle.dots[3].to_string(), "b r1c9 r2c9");
+        assert_eq!(puzzle.marks.marks()[1].to_string(), "v r2c1 r2c2");
+        assert!(!puzzle.marks.is_negative());
+        assert_eq!(puzzle.parities.get(&Index(1, 1)), Some(Parity::Even));
+
+        let solution = easy_solution();
+        assert!(puzzle
+            .constraints()
+            .iter()
+            .all(|c| c.is_satisfied(&solution)));
+        assert_eq!(puzzle.solved(), Some(solution));
+    }
+
+    #[test]
+    fn test_from_url() {
+        let url = std::fs::read_to_string("tests/test_fpuzzles_url.txt").unwrap();
+        let url = url.trim();
+        let expected = FPuzzle::from_json(&json()).unwrap();
+        assert_eq!(FPuzzle::from_url(url).unwrap(), expected);
+        let (_, compressed) = url.split_once("load=").unwrap();
+        assert_eq!(FPuzzle::from_url(compressed).unwrap(), expected);
+        let sudokupad = format!("https://sudokupad.app/fpuzzles{}", compressed);
+        assert_eq!(FPuzzle::from_url(&sudokupad).unwrap(), expected);
+        assert!(matches!(
+            FPuzzle::from_url("https://www.f-puzzles.com/?load=N4Ig*"),
+            Err(SudokuError::InvalidFPuzzle { .. })
+        ));
+    }
+
+    #[test]
+    fn test_variants() {
+        let empty = r#"{"size":9,"grid":[[],[],[],[],[],[],[],[],[]]}"#;
+        let with = |features: &str| {
+            let grid = vec![vec![serde_json::json!({}); 9]; 9];
+            let mut json: serde_json::Value = serde_json::from_str(features).unwrap();
+            json["size"] = 9.into();
+            json["grid"] = grid.into();
+            FPuzzle::from_json(&json.to_string())
+        };
+        // rows of the grid must all have 9 cells
+        assert!(FPuzzle::from_json(empty).is_err());
+
+        let puzzle = with(r#"{"diagonal+":true,"diagonal-":true,"antiknight":true}"#).unwrap();
+        assert_eq!(puzzle.variants, [Variant::Diagonal, Variant::AntiKnight]);
+        assert_eq!(puzzle.constraints().len(), 7);
+
+        let hyper = r#"{"extraregion":[
+            {"cells":["R6C6","R6C7","R6C8","R7C6","R7C7","R7C8","R8C6","R8C7","R8C8"]},
+            {"cells":["R2C2","R2C3","R2C4","R3C2","R3C3","R3C4","R4C2","R4C3","R4C4"]},
+            {"cells":["R2C6","R2C7","R2C8","R3C6","R3C7","R3C8","R4C6","R4C7","R4C8"]},
+            {"cells":["R6C2","R6C3","R6C4","R7C2","R7C3","R7C4","R8C2","R8C3","R8C4"]}]}"#;
+        assert_eq!(with(hyper).unwrap().variants, [Variant::Hyper]);
+
+        let negative = with(r#"{"xv":[{"cells":["R1C1","R1C2"],"value":"X"}],"negative":["xv"]}"#);
+        assert!(negative.unwrap().marks.is_negative());
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let reason = |features: &str| {
+            let mut puzzle: serde_json::Value = serde_json::from_str(&json()).unwrap();
+            let features: serde_json::Value = serde_json::from_str(features).unwrap();
+            for (key, value) in features.as_object().unwrap() {
+                puzzle[key] = value.clone();
+            }
+            match FPuzzle::from_json(&puzzle.to_string()) {
+                Err(SudokuError::InvalidFPuzzle { reason }) => reason,
+                other => panic!("expected an invalid puzzle, found {:?}", other),
+            }
+        };
+        assert_eq!(
+            reason(r#"{"arrow":[{"lines":[["R1C1","R1C2"]],"cells":["R1C1"]}]}"#),
+            "arrow is not supported"
+        );
+        assert_eq!(reason(r#"{"size":6}"#), "the grid is 6x6, expected 9x9");
+        assert_eq!(
+            reason(r#"{"diagonal+":true}"#),
+            "a single diagonal is not supported"
+        );
+        assert_eq!(
+            reason(r#"{"negative":["ratio"]}"#),
+            "negative ratio is not supported"
+        );
+        assert_eq!(
+            reason(r#"{"extraregion":[{"cells":["R1C1"]}]}"#),
+            "extra regions other than the hyper windows are not supported"
+        );
+        // unused features and cosmetic ones are fine
+        assert!(FPuzzle::from_json(
+            &json().replace(r#""size""#, r#""arrow":[],"text":[{}],"size""#)
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_cage_without_value() {
+        let json = json().replacen(r#""value": "17""#, r#""value": null"#, 1);
+        let puzzle = FPuzzle::from_json(&json).unwrap();
+        assert_eq!(
+            puzzle.cages[0],
+            Cage::without_sum(vec![Index(0, 0), Index(0, 1), Index(1, 0)])
+        );
+        assert_eq!(puzzle.cages[1].sum(), Some(11));
+        assert_eq!(puzzle.solved(), Some(easy_solution()));
+        assert!(!puzzle.to_json().contains(r#""value":"17""#));
+        assert_eq!(FPuzzle::from_json(&puzzle.to_json()).unwrap(), puzzle);
+    }
+
+    #[test]
+    fn test_export() {
+        // an empty grid gives the start every f-puzzles link for a 9x9 grid shares
+        let empty = FPuzzle::new(Board::try_new(&[[0; 9]; 9]).unwrap());
+        assert!(empty
+            .to_json()
+            .starts_with(r#"{"size":9,"grid":[[{},{},{},{},{},{},{},{},{}]"#));
+        assert!(empty
+            .to_url()
+            .starts_with("https://www.f-puzzles.com/?load=N4IgzglgXgpiBcBOANCA5gJwgEwQbT2AF9"));
+
+        let puzzle = FPuzzle::from_json(&json()).unwrap();
+        assert_eq!(FPuzzle::from_json(&puzzle.to_json()).unwrap(), puzzle);
+        assert_eq!(FPuzzle::from_url(&puzzle.to_url()).unwrap(), puzzle);
+        assert!(puzzle
+            .to_sudokupad_url()
+            .starts_with("https://sudokupad.app/fpuzzles"));
+        assert_eq!(
+            FPuzzle::from_url(&puzzle.to_sudokupad_url()).unwrap(),
+            puzzle
+        );
+    }
+
+    #[test]
+    fn test_builder() {
+        let solution = easy_solution();
+        let puzzle = FPuzzle::new(solution.clone())
+            .with_author("abroy77")
+            .with_variant(Variant::Classic)
+            .with_variant(Variant::Hyper)
+            .with_variant(Variant::Diagonal)
+            .with_thermometers(vec!["r2c1 r2c2 r2c3".parse().unwrap()])
+            .with_sandwiches(vec!["c1 0".parse().unwrap()])
+            .with_dots(vec!["w r1c5 r1c6".parse().unwrap()])
+            .with_marks(Marks::new(vec!["x r1c1 r2c1".parse().unwrap()]).with_negative_constraint())
+            .with_parities(Parities::new(&[(Index(0, 0), Parity::Odd)]))
+            .with_cages(vec!["17 r1c1 r1c2 r2c1".parse().unwrap()])
+            .unwrap();
+        assert_eq!(puzzle.variants, [Variant::Hyper, Variant::Diagonal]);
+        let read = FPuzzle::from_url(&puzzle.to_url()).unwrap();
+        assert_eq!(read.author(), Some("abroy77"));
+        assert_eq!(read.board(), &solution);
+        let mut variants = read.variants.clone();
+        variants.sort_by_key(|variant| *variant as u8);
+        assert_eq!(variants, [Variant::Diagonal, Variant::Hyper]);
+        assert_eq!(read.cages, puzzle.cages);
+        assert_eq!(read.thermometers, puzzle.thermometers);
+        assert_eq!(read.sandwiches, puzzle.sandwiches);
+        assert_eq!(read.dots, puzzle.dots);
+        assert_eq!(read.marks, puzzle.marks);
+        assert_eq!(read.parities, puzzle.parities);
+
+        // the givens already fill the cage with 9, 7 and 1
+        assert_eq!(
+            FPuzzle::new(solution)
+                .with_cages(vec!["18 r1c1 r1c2 r2c1".parse().unwrap()])
+                .unwrap_err(),
+            SudokuError::RuleViolation { row: 0, column: 0 }
+        );
+    }
+}
@@ -0,0 +1,319 @@
+//! This module contains functions to generate puzzles
+//! Puzzles are made by filling a random solved grid, then removing clues from it one at a time,
+//! only keeping a removal if the puzzle still has a unique solution
+//!
+//! Generation is seeded, so the same seed always produces the same puzzle
+
+use crate::board::{count_solutions, solve, Board, BoardError, Index};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Small deterministic random number generator (splitmix64)
+/// Good enough for shuffling cells, and keeps the crate free of a rand dependency
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+    /// Get a number in 0..bound
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+    /// Shuffle a slice in place (Fisher-Yates)
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// How hard a generated puzzle should be, set by how many clues it keeps
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl Difficulty {
+    /// Clues the generator aims to leave for this difficulty
+    pub fn target_clues(&self) -> usize {
+        match self {
+            Difficulty::Easy => 40,
+            Difficulty::Medium => 32,
+            Difficulty::Hard => 28,
+            Difficulty::Expert => 24,
+        }
+    }
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+            Difficulty::Expert => "expert",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "easy" => Ok(Difficulty::Easy),
+            "medium" => Ok(Difficulty::Medium),
+            "hard" => Ok(Difficulty::Hard),
+            "expert" => Ok(Difficulty::Expert),
+            _ => Err(format!(
+                "unknown difficulty {:?}, expected easy, medium, hard or expert",
+                s
+            )),
+        }
+    }
+}
+
+/// Make a random complete solution
+/// The three subgrids on the diagonal don't share any rows or columns,
+/// so they are filled with shuffled digits and the solver fills in the rest
+pub fn random_solution(rng: &mut Rng) -> Board {
+    let mut board = Board::from_digits(&[[0; 9]; 9]);
+    for subgrid in [0, 4, 8] {
+        let mut digits: Vec<u8> = (1..=9).collect();
+        rng.shuffle(&mut digits);
+        for (k, digit) in digits.into_iter().enumerate() {
+            let index = Index((subgrid / 3) * 3 + k / 3, (subgrid % 3) * 3 + k % 3);
+            board.update_cell(&index, digit);
+        }
+    }
+    solve(&mut board).expect("diagonal subgrids can always be completed")
+}
+
+/// Generate a random puzzle with a unique solution at the given difficulty
+pub fn generate(difficulty: Difficulty, rng: &mut Rng) -> Board {
+    generate_with_symmetry(difficulty, Symmetry::None, rng)
+}
+
+/// Generate a puzzle from a seed, so the same seed and difficulty always give the same puzzle
+/// Useful for daily puzzles and regression tests
+pub fn generate_from_seed(difficulty: Difficulty, seed: u64) -> Board {
+    generate(difficulty, &mut Rng::new(seed))
+}
+
+/// Make a puzzle from a complete solution by removing clues until target_clues remain
+/// Clues are only removed while the puzzle keeps a unique solution, so the result
+/// may keep more than target_clues clues if no further clue can be removed
+/// # Errors
+/// Returns BoardError::NotASolution if the input is not a complete valid grid
+pub fn puzzle_from_solution(
+    solution: &Board,
+    target_clues: usize,
+    seed: u64,
+) -> Result<Board, BoardError> {
+    if !solution.is_complete() {
+        return Err(BoardError::NotASolution);
+    }
+    Ok(dig(
+        solution,
+        target_clues,
+        Symmetry::None,
+        &mut Rng::new(seed),
+    ))
+}
+
+/// Pattern kept by the clues of a generated puzzle
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub enum Symmetry {
+    /// Clues are removed one at a time with no pattern
+    #[default]
+    None,
+    /// The clues look the same after turning the board half way around
+    Rotational,
+    /// The clues look the same mirrored left to right
+    Mirror,
+    /// The clues look the same mirrored along the main diagonal
+    Diagonal,
+}
+
+impl Symmetry {
+    /// Get the cells that must be removed together with the given cell
+    fn orbit(&self, index: Index) -> Vec<Index> {
+        let Index(row, col) = index;
+        let partner = match self {
+            Symmetry::None => index,
+            Symmetry::Rotational => Index(8 - row, 8 - col),
+            Symmetry::Mirror => Index(row, 8 - col),
+            Symmetry::Diagonal => Index(col, row),
+        };
+        if partner == index {
+            vec![index]
+        } else {
+            vec![index, partner]
+        }
+    }
+}
+
+impl Display for Symmetry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Symmetry::None => "none",
+            Symmetry::Rotational => "rotational",
+            Symmetry::Mirror => "mirror",
+            Symmetry::Diagonal => "diagonal",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Symmetry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" => Ok(Symmetry::None),
+            "rotational" => Ok(Symmetry::Rotational),
+            "mirror" => Ok(Symmetry::Mirror),
+            "diagonal" => Ok(Symmetry::Diagonal),
+            _ => Err(format!(
+                "unknown symmetry {:?}, expected none, rotational, mirror or diagonal",
+                s
+            )),
+        }
+    }
+}
+
+/// Generate a random puzzle with a unique solution whose clues keep the given symmetry
+pub fn generate_with_symmetry(difficulty: Difficulty, symmetry: Symmetry, rng: &mut Rng) -> Board {
+    let solution = random_solution(rng);
+    dig(&solution, difficulty.target_clues(), symmetry, rng)
+}
+
+/// Remove clues from a complete solution, a symmetric group of cells at a time,
+/// until target_clues remain or no group can be removed without losing uniqueness
+/// A group is never removed if that would leave fewer than target_clues clues
+fn dig(solution: &Board, target_clues: usize, symmetry: Symmetry, rng: &mut Rng) -> Board {
+    let mut cells: Vec<Index> = (0..81).map(|x| Index(x / 9, x % 9)).collect();
+    rng.shuffle(&mut cells);
+
+    let mut puzzle = solution.clone();
+    let mut clues = 81;
+    for index in cells {
+        if clues <= target_clues {
+            break;
+        }
+        if puzzle.get_cell(&index).is_none() {
+            continue;
+        }
+        let orbit = symmetry.orbit(index);
+        if clues - orbit.len() < target_clues {
+            continue;
+        }
+        for cell in &orbit {
+            puzzle.update_cell(cell, 0);
+        }
+        if count_solutions(&puzzle, Some(2)) == 1 {
+            clues -= orbit.len();
+        } else {
+            for cell in &orbit {
+                puzzle.update_cell(cell, solution.get_cell(cell).unwrap_or(0));
+            }
+        }
+    }
+    puzzle.as_puzzle()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::fixtures::make_solved_board;
+
+    fn clue_count(board: &Board) -> usize {
+        board.cells().filter(|(_, value)| value.is_some()).count()
+    }
+
+    #[test]
+    fn test_puzzle_from_solution() {
+        let solution = make_solved_board();
+        let puzzle = puzzle_from_solution(&solution, 35, 7).unwrap();
+        assert_eq!(clue_count(&puzzle), 35);
+        assert_eq!(count_solutions(&puzzle, Some(2)), 1);
+        // every clue comes from the solution
+        assert!(puzzle
+            .diff(&solution)
+            .iter()
+            .all(|(_, clue, _)| clue.is_none()));
+        // the same seed gives the same puzzle
+        assert_eq!(puzzle_from_solution(&solution, 35, 7), Ok(puzzle));
+    }
+
+    #[test]
+    fn test_random_solution() {
+        let solution = random_solution(&mut Rng::new(3));
+        assert!(solution.is_complete());
+        assert!(solution.is_valid_board());
+        assert_ne!(solution, random_solution(&mut Rng::new(4)));
+    }
+
+    #[test]
+    fn test_generate() {
+        let mut rng = Rng::new(11);
+        for difficulty in [Difficulty::Easy, Difficulty::Hard] {
+            let puzzle = generate(difficulty, &mut rng);
+            assert!(clue_count(&puzzle) >= difficulty.target_clues());
+            assert_eq!(count_solutions(&puzzle, Some(2)), 1);
+            assert_eq!(puzzle.clues(), puzzle);
+        }
+        assert_eq!("Expert".parse(), Ok(Difficulty::Expert));
+        assert!("impossible".parse::<Difficulty>().is_err());
+    }
+
+    #[test]
+    fn test_generate_from_seed() {
+        let puzzle = generate_from_seed(Difficulty::Medium, 2024);
+        assert_eq!(generate_from_seed(Difficulty::Medium, 2024), puzzle);
+        assert_eq!(generate(Difficulty::Medium, &mut Rng::new(2024)), puzzle);
+        assert_ne!(generate_from_seed(Difficulty::Medium, 2025), puzzle);
+    }
+
+    #[test]
+    fn test_generate_with_symmetry() {
+        let mut rng = Rng::new(5);
+        for symmetry in [Symmetry::Rotational, Symmetry::Mirror, Symmetry::Diagonal] {
+            let puzzle = generate_with_symmetry(Difficulty::Medium, symmetry, &mut rng);
+            assert_eq!(count_solutions(&puzzle, Some(2)), 1);
+            for x in 0..81 {
+                let index = Index(x / 9, x % 9);
+                for partner in symmetry.orbit(index) {
+                    assert_eq!(
+                        puzzle.get_cell(&index).is_some(),
+                        puzzle.get_cell(&partner).is_some()
+                    );
+                }
+            }
+        }
+        assert_eq!("mirror".parse(), Ok(Symmetry::Mirror));
+    }
+
+    #[test]
+    fn test_puzzle_from_incomplete_solution() {
+        let mut grid = [[0; 9]; 9];
+        grid[0] = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+        assert_eq!(
+            puzzle_from_solution(&Board::from_digits(&grid), 30, 1),
+            Err(BoardError::NotASolution)
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! This module wraps a board with an undo and redo history of the moves made on it,
+//! so interactive frontends can step backwards and forwards without re-deriving state
+
+use crate::board::{Board, BoardError, Index};
+
+/// A change to one cell, as recorded in the history
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub struct Move {
+    pub index: Index,
+    /// The value before the move, None if the cell was empty
+    pub before: Option<u8>,
+    /// The value after the move, None if the cell was cleared
+    pub after: Option<u8>,
+}
+
+/// A board along with the moves made on it
+/// Making a new move after undoing forgets the undone moves, as in a text editor
+#[derive(Clone, Debug)]
+pub struct History {
+    board: Board,
+    undo: Vec<Move>,
+    redo: Vec<Move>,
+}
+
+impl History {
+    /// Start a history with no moves on the board
+    pub fn new(board: Board) -> Self {
+        History {
+            board,
+            undo: Vec::new(),
+            redo: Vec::new(),
+        }
+    }
+    /// Get the board as it is after the moves made so far
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+    /// Fill a cell with a digit, or clear it with 0, recording the move
+    /// Setting a cell to the value it already holds is not recorded
+    /// # Example
+    /// ```
+    /// use sudoku_solver_by_roy::board::Board;
+    /// use sudoku_solver_by_roy::history::History;
+    /// let mut history = History::new(Board::try_new(&[[0; 9]; 9]).unwrap());
+    /// history.set(0, 0, 5).unwrap();
+    /// history.undo();
+    /// assert_eq!(history.board().get(0, 0), Ok(None));
+    /// history.redo();
+    /// assert_eq!(history.board().get(0, 0), Ok(Some(5)));
+    /// ```
+    /// # Errors
+    /// The same as Board::set, in which case nothing is recorded
+    pub fn set(&mut self, row: usize, column: usize, value: u8) -> Result<(), BoardError> {
+        let before = self.board.get(row, column)?;
+        self.board.set(row, column, value)?;
+        let after = self.board.get(row, column)?;
+        if before != after {
+            self.undo.push(Move {
+                index: Index::new(row, column)?,
+                before,
+                after,
+            });
+            self.redo.clear();
+        }
+        Ok(())
+    }
+    /// Take back the last move, returning it, or None if there is nothing to undo
+    pub fn undo(&mut self) -> Option<Move> {
+        let last = self.undo.pop()?;
+        self.apply(&last.index, last.before);
+        self.redo.push(last);
+        Some(last)
+    }
+    /// Make the last undone move again, returning it, or None if there is nothing to redo
+    pub fn redo(&mut self) -> Option<Move> {
+        let next = self.redo.pop()?;
+        self.apply(&next.index, next.after);
+        self.undo.push(next);
+        Some(next)
+    }
+    /// Check if there is a move to undo
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+    /// Check if there is an undone move to redo
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+    /// Get the moves made so far, oldest first, leaving out undone ones
+    pub fn moves(&self) -> &[Move] {
+        &self.undo
+    }
+    /// Put a recorded value back, which was already checked when the move was made
+    fn apply(&mut self, index: &Index, value: Option<u8>) {
+        self.board.update_cell(index, value.unwrap_or(0));
+    }
+}
+
+impl From<Board> for History {
+    fn from(board: Board) -> Self {
+        History::new(board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn easy() -> History {
+        let board: Board =
+            ".738.4216...2.95..2856.3.97...3...7475....3.1..4.2.....9756.......7..1..42..3..6."
+                .parse()
+                .unwrap();
+        History::new(board)
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut history = easy();
+        let start = history.board().clone();
+        history.set(0, 0, 9).unwrap();
+        history.set(0, 0, 1).unwrap();
+        history.set(1, 0, 6).unwrap();
+        assert_eq!(history.moves().len(), 3);
+
+        let undone = history.undo().unwrap();
+        assert_eq!(undone.index, Index::new(1, 0).unwrap());
+        assert_eq!(history.board().get(1, 0), Ok(None));
+        history.undo();
+        assert_eq!(history.board().get(0, 0), Ok(Some(9)));
+        history.undo();
+        assert_eq!(history.board(), &start);
+        assert!(!history.can_undo());
+        assert_eq!(history.undo(), None);
+
+        history.redo();
+        history.redo();
+        assert_eq!(history.board().get(0, 0), Ok(Some(1)));
+        assert!(history.can_redo());
+    }
+
+    #[test]
+    fn test_new_move_clears_redo() {
+        let mut history = easy();
+        history.set(0, 0, 9).unwrap();
+        history.undo();
+        history.set(0, 0, 1).unwrap();
+        assert!(!history.can_redo());
+        assert_eq!(history.redo(), None);
+    }
+
+    #[test]
+    fn test_unrecorded_moves() {
+        let mut history = easy();
+        // clues can't be changed, and clearing an empty cell changes nothing
+        assert_eq!(
+            history.set(0, 1, 5),
+            Err(BoardError::GivenCell(Index::new(0, 1).unwrap()))
+        );
+        history.set(0, 0, 0).unwrap();
+        assert!(!history.can_undo());
+    }
+}
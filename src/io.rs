@@ -1,60 +1,498 @@
-//! This module contains the functions to read a board from a csv file
+//! This module contains the functions to read a board from a csv file or a string
 //! The csv file must be a 9x9 grid of numbers between 0 and 9
-//! 0 represents an empty cell
+//! 0 represents an empty cell, as do `.`, `_` and blank fields unless CsvOptions say otherwise
 //! Any other number represents a filled cell
-//! # Panics
-//! 1. File not found
-//! 2. File not readable
-//! 3. File not csv
-//! 4. File not 9x9
-//! 5. File contains numbers other than 0-9
-//! 6. File contains non integers
-//! 7. Board does not satisfy sudoku rules
-
-use crate::board::Board;
-use csv::ReaderBuilder;
-use std::path::PathBuf;
+//! # Errors
+//! Reading fails with a SudokuError when
+//! 1. File not found or not readable (Io)
+//! 2. File not csv, or contains non integers (ParseError)
+//! 3. File not 9x9 (DimensionError)
+//! 4. File contains numbers other than 0-9 (InvalidValue)
+//! 5. Board does not satisfy sudoku rules (RuleViolation)
+//!
+//! Besides csv, puzzles can be read from single lines and the .sdm collections made of them,
+//! and from the Simple Sudoku .sdk and .ss grids used by many puzzle archives
+
+use crate::board::{Board, Index};
+use crate::error::{ClueKind, Dimension, SudokuError};
+use csv::{Reader, ReaderBuilder};
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Get one of the sample puzzles bundled with the crate
+/// Available names are "easy", "medium" and "hard"
+/// # Example
+/// ```
+/// use sudoku_solver_by_roy::board::solve;
+/// use sudoku_solver_by_roy::io::example_puzzle;
+/// let mut board = example_puzzle("easy").unwrap();
+/// assert!(solve(&mut board).is_some());
+/// ```
+pub fn example_puzzle(name: &str) -> Option<Board> {
+    let csv = match name {
+        "easy" => include_str!("../puzzles/easy.csv"),
+        "medium" => include_str!("../puzzles/medium.csv"),
+        "hard" => include_str!("../puzzles/hard.csv"),
+        _ => return None,
+    };
+    let reader = csv_reader_builder().from_reader(csv.as_bytes());
+    Some(Board::parse_csv(reader, &CsvOptions::default()).expect("bundled puzzles are valid"))
+}
+
+/// Read a collection of puzzles written one per line, as in .sdm files
+/// Blank lines and lines starting with `#` are skipped, and anything after the first
+/// whitespace on a line (such as a rating or comment) is ignored.
+/// Each puzzle is read with Board::from_line, so one bad line doesn't stop the rest
+pub fn puzzles_from_lines(s: &str) -> impl Iterator<Item = Result<Board, SudokuError>> + '_ {
+    s.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| Board::from_line(line.split_whitespace().next().unwrap_or_default()))
+}
+
+/// Read clues written one per line, such as killer cages or thermometers
+/// Blank lines and lines starting with `#` are skipped, and each line is read with FromStr
+/// # Errors
+/// Returns SudokuError::InvalidClue of the given kind for the first line that can't be read,
+/// numbering clues from 0
+pub fn parse_lines<T: FromStr<Err = String>>(
+    s: &str,
+    kind: ClueKind,
+) -> Result<Vec<T>, SudokuError> {
+    s.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .enumerate()
+        .map(|(line, clue)| {
+            clue.parse()
+                .map_err(|reason| SudokuError::InvalidClue { kind, line, reason })
+        })
+        .collect()
+}
+
+/// Read a SadMan Sudoku .sdm file, holding one puzzle per line
+/// Lines are read as by puzzles_from_lines, but any bad puzzle fails the whole file
+pub fn read_sdm(path: impl AsRef<Path>) -> Result<Vec<Board>, SudokuError> {
+    let contents = std::fs::read_to_string(path)?;
+    puzzles_from_lines(&contents).collect()
+}
+
+/// Options for reading boards from csv
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct CsvOptions {
+    /// Pad rows shorter than 9 cells with empty cells. Longer rows are still rejected
+    pub pad_rows: bool,
+    /// Cells read as empty besides `0`, compared after trimming whitespace
+    /// An empty string makes empty fields, as in `1,,3`, read as empty cells
+    pub empty_markers: Vec<String>,
+}
+
+impl Default for CsvOptions {
+    /// Rows must have 9 cells, and `.`, `_` and empty fields are read as empty cells
+    fn default() -> Self {
+        CsvOptions {
+            pad_rows: false,
+            empty_markers: [".", "_", ""].map(String::from).to_vec(),
+        }
+    }
+}
+
+/// Csv reader settings shared by every csv source
+/// Row lengths are checked by parse_csv so that errors are reported consistently
+fn csv_reader_builder() -> ReaderBuilder {
+    let mut builder = ReaderBuilder::new();
+    builder.has_headers(false).flexible(true);
+    builder
+}
+
+/// Convert an error from the csv reader, found while reading the given row
+fn csv_error(e: csv::Error, row: usize) -> SudokuError {
+    let found = e.to_string();
+    match e.into_kind() {
+        csv::ErrorKind::Io(e) => SudokuError::Io(e),
+        _ => SudokuError::ParseError {
+            row,
+            column: 0,
+            found,
+        },
+    }
+}
+
+/// Parse a board from a string, detecting its format
+/// Accepted formats are comma separated rows, a single 81 character line, a Simple Sudoku grid,
+/// or a grid of 9 lines with 9 cells each. Empty cells are `0` or `.` (`0` only for csv)
+/// # Example
+/// ```
+/// use sudoku_solver_by_roy::board::Board;
+/// let line = "..34.7.6.7......4.....1.25.48.3..1...5......2.6..2.....9.1.5..81..6....5......4..";
+/// let board: Board = line.parse().unwrap();
+/// assert_eq!(board.to_line(), line);
+/// ```
+impl FromStr for Board {
+    type Err = SudokuError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.contains(',') {
+            let reader = csv_reader_builder().from_reader(s.as_bytes());
+            return Board::parse_csv(reader, &CsvOptions::default());
+        }
+        if s.contains('|') || s.starts_with(['#', '[', '*']) {
+            return Board::from_sdk(s);
+        }
+        let lines: Vec<&str> = s.lines().filter(|line| !line.trim().is_empty()).collect();
+        if lines.len() == 1 {
+            return Board::from_line(lines[0]);
+        }
+        let is_grid = lines.len() == 9
+            && lines
+                .iter()
+                .all(|line| line.chars().filter(|c| !c.is_whitespace()).count() == 9);
+        if !is_grid {
+            return Err(SudokuError::DimensionError {
+                row: None,
+                found: lines.len(),
+                expected: 9,
+                unit: Dimension::Rows,
+            });
+        }
+        Board::from_string(s)
+    }
+}
 
 impl Board {
     /// Create a new board from a csv file
-    pub fn from_csv(path: &PathBuf) -> Result<Board, &'static str> {
-        let mut board = [[0; 9]; 9];
-        let mut reader = ReaderBuilder::new()
-            .has_headers(false)
+    /// Empty cells may be `0`, `.`, `_` or left blank
+    pub fn from_csv(path: impl AsRef<Path>) -> Result<Board, SudokuError> {
+        Board::from_csv_with(path, &CsvOptions::default())
+    }
+
+    /// Create a new board from a csv file, padding rows shorter than 9 cells with empty cells
+    /// Rows longer than 9 cells are still rejected
+    pub fn from_csv_padded(path: impl AsRef<Path>) -> Result<Board, SudokuError> {
+        let options = CsvOptions {
+            pad_rows: true,
+            ..CsvOptions::default()
+        };
+        Board::from_csv_with(path, &options)
+    }
+
+    /// Create a new board from a csv file, with the given options
+    /// # Example
+    /// ```
+    /// use sudoku_solver_by_roy::board::Board;
+    /// use sudoku_solver_by_roy::io::CsvOptions;
+    /// // only accept 0 for empty cells
+    /// let strict = CsvOptions { pad_rows: false, empty_markers: Vec::new() };
+    /// let board = Board::from_csv_with("tests/test_board_pass.csv", &strict);
+    /// assert!(board.is_ok());
+    /// ```
+    pub fn from_csv_with(
+        path: impl AsRef<Path>,
+        options: &CsvOptions,
+    ) -> Result<Board, SudokuError> {
+        let reader = csv_reader_builder()
             .from_path(path)
-            .expect("Could not open csv file");
+            .map_err(|e| csv_error(e, 0))?;
+        Board::parse_csv(reader, options)
+    }
+
+    /// Create a new board from anything implementing Read, such as a network stream,
+    /// embedded bytes or an in-memory buffer
+    /// The whole input is read and parsed in any format accepted by parse
+    /// # Example
+    /// ```
+    /// use sudoku_solver_by_roy::board::Board;
+    /// let bytes: &[u8] = b"..34.7.6.7......4.....1.25.48.3..1...5......2.6..2.....9.1.5..81..6....5......4..";
+    /// let board = Board::from_reader(bytes).unwrap();
+    /// assert!(board.solved().is_some());
+    /// ```
+    pub fn from_reader(mut reader: impl Read) -> Result<Board, SudokuError> {
+        let mut contents = String::new();
+        reader.read_to_string(&mut contents)?;
+        contents.parse()
+    }
 
+    fn parse_csv<R: Read>(
+        mut reader: Reader<R>,
+        options: &CsvOptions,
+    ) -> Result<Board, SudokuError> {
+        let mut board = [[0; 9]; 9];
         let mut line_count = 0;
         for (i, row) in reader.records().enumerate() {
-            let row = row.map_err(|_| "Unreadable csv")?;
-            if row.len() != 9 {
-                return Err("Invalid csv file. Only 9x9 boards allowed");
+            let row = row.map_err(|e| csv_error(e, i))?;
+            if i >= 9 {
+                return Err(SudokuError::DimensionError {
+                    row: None,
+                    found: i + 1,
+                    expected: 9,
+                    unit: Dimension::Rows,
+                });
+            }
+            if row.len() > 9 || (!options.pad_rows && row.len() != 9) {
+                return Err(SudokuError::DimensionError {
+                    row: Some(i),
+                    found: row.len(),
+                    expected: 9,
+                    unit: Dimension::Cells,
+                });
             }
 
             for (j, cell) in row.iter().enumerate() {
-                let input_value = match cell.trim().parse::<u8>() {
+                if options
+                    .empty_markers
+                    .iter()
+                    .any(|marker| marker == cell.trim())
+                {
+                    continue;
+                }
+                let input_value = match cell.trim().parse::<u32>() {
                     Ok(value) => value,
-                    Err(_) => return Err("Invalid csv file. Only int numbers allowed"),
+                    Err(_) => {
+                        return Err(SudokuError::ParseError {
+                            row: i,
+                            column: j,
+                            found: cell.to_string(),
+                        })
+                    }
                 };
                 if input_value > 9 {
-                    return Err("Invalid csv file. Only numbers between 0 and 9 allowed");
+                    return Err(SudokuError::InvalidValue {
+                        row: i,
+                        column: j,
+                        value: input_value,
+                    });
                 }
 
-                board[i][j] = input_value;
+                board[i][j] = input_value as u8;
             }
             line_count += 1;
         }
         if line_count != 9 {
-            return Err("Invalid csv file. Only 9x9 boards allowed");
+            return Err(SudokuError::DimensionError {
+                row: None,
+                found: line_count,
+                expected: 9,
+                unit: Dimension::Rows,
+            });
+        }
+
+        Board::try_new(&board)
+    }
+
+    /// Create a new board from the standard one line format used by puzzle collections
+    /// The line must hold exactly 81 cells, with `0` or `.` for empty cells
+    /// Surrounding whitespace such as a trailing newline is ignored
+    pub fn from_line(line: &str) -> Result<Board, SudokuError> {
+        let line = line.trim();
+        let length = line.chars().count();
+        if length != 81 {
+            return Err(SudokuError::DimensionError {
+                row: None,
+                found: length,
+                expected: 81,
+                unit: Dimension::Cells,
+            });
+        }
+        Board::parse_string(line, &['0', '.'])
+    }
+
+    /// Create a new board from a string of 81 cells read row by row
+    /// Empty cells may be written as `0` or `.`, and whitespace is ignored
+    /// so both single line puzzles and 9 line grids are accepted
+    pub fn from_string(s: &str) -> Result<Board, SudokuError> {
+        Board::parse_string(s, &['0', '.'])
+    }
+
+    /// Create a new board from a string of 81 cells, only accepting `.` for empty cells
+    /// A `0` is rejected rather than treated as empty
+    pub fn from_string_strict(s: &str) -> Result<Board, SudokuError> {
+        Board::parse_string(s, &['.'])
+    }
+
+    /// Create a new board from a string of 81 cells, only accepting the given character for empty cells
+    pub fn from_string_with_empty(s: &str, empty: char) -> Result<Board, SudokuError> {
+        Board::parse_string(s, &[empty])
+    }
+
+    /// Create a new board from a Simple Sudoku grid, as in .sdk and .ss files
+    /// Each row is a line of 9 cells, which may be split into boxes with `|`.
+    /// Border lines such as `*-----------*` or `|---+---+---|` and comment lines starting with `#`
+    /// are skipped. Only the first `[section]` of an .sdk file is read, as later ones hold
+    /// the solving state. Empty cells are `.`, `0` or `X`
+    /// # Example
+    /// ```
+    /// use sudoku_solver_by_roy::board::Board;
+    /// let grid = "\
+    /// *-----------*
+    /// |..3|4.7|.6.|
+    /// |7..|...|.4.|
+    /// |...|.1.|25.|
+    /// |---+---+---|
+    /// |48.|3..|1..|
+    /// |.5.|...|..2|
+    /// |.6.|.2.|...|
+    /// |---+---+---|
+    /// |.9.|1.5|..8|
+    /// |1..|6..|..5|
+    /// |...|...|4..|
+    /// *-----------*";
+    /// let board = Board::from_sdk(grid).unwrap();
+    /// assert!(board.to_line().starts_with("..34.7.6.7......4."));
+    /// ```
+    pub fn from_sdk(s: &str) -> Result<Board, SudokuError> {
+        let mut cells = String::with_capacity(81);
+        for line in s.lines().map(str::trim) {
+            if line.starts_with('[') {
+                if !cells.is_empty() {
+                    break;
+                }
+                continue;
+            }
+            if line.starts_with('#') || line.chars().all(|c| "*-+|".contains(c)) {
+                continue;
+            }
+            cells.extend(line.chars().filter(|c| *c != '|').map(|c| match c {
+                'X' | 'x' => '.',
+                c => c,
+            }));
+        }
+        Board::parse_string(&cells, &['0', '.'])
+    }
+
+    fn parse_string(s: &str, empty_markers: &[char]) -> Result<Board, SudokuError> {
+        let mut cells = Vec::with_capacity(81);
+        for c in s.chars().filter(|c| !c.is_whitespace()) {
+            let (row, column) = (cells.len() / 9, cells.len() % 9);
+            if empty_markers.contains(&c) {
+                cells.push(0);
+                continue;
+            }
+            match c.to_digit(10) {
+                Some(0) => {
+                    return Err(SudokuError::InvalidValue {
+                        row,
+                        column,
+                        value: 0,
+                    })
+                }
+                Some(value) => cells.push(value as u8),
+                None => {
+                    return Err(SudokuError::ParseError {
+                        row,
+                        column,
+                        found: c.to_string(),
+                    })
+                }
+            }
+        }
+        if cells.len() != 81 {
+            return Err(SudokuError::DimensionError {
+                row: None,
+                found: cells.len(),
+                expected: 81,
+                unit: Dimension::Cells,
+            });
+        }
+
+        let mut board = [[0; 9]; 9];
+        for (i, value) in cells.into_iter().enumerate() {
+            board[i / 9][i % 9] = value;
         }
+        Board::try_new(&board)
+    }
 
-        // check if the board is valid
-        let board = Board::new(&board);
-        if !board.is_valid_board() {
-            return Err("Invalid Board: Board does not satisfy sudoku rules");
+    /// Create a new board from a 9x9 array of u8, checking it
+    /// 0 represents an empty cell and any other number a given clue
+    /// # Example
+    /// ```
+    /// use sudoku_solver_by_roy::board::Board;
+    /// let board = Board::try_new(&[
+    /// [0, 0, 3, 4, 0, 7, 0, 6, 0],
+    /// [7, 0, 0, 0, 0, 0, 0, 4, 0],
+    /// [0, 0, 0, 0, 1, 0, 2, 5, 0],
+    /// [4, 8, 0, 3, 0, 0, 1, 0, 0],
+    /// [0, 5, 0, 0, 0, 0, 0, 0, 2],
+    /// [0, 6, 0, 0, 2, 0, 0, 0, 0],
+    /// [0, 9, 0, 1, 0, 5, 0, 0, 8],
+    /// [1, 0, 0, 6, 0, 0, 0, 0, 5],
+    /// [0, 0, 0, 0, 0, 0, 4, 0, 0],
+    /// ]);
+    /// assert!(board.is_ok());
+    /// ```
+    /// # Errors
+    /// 1. SudokuError::InvalidValue if a cell holds a number over 9
+    /// 2. SudokuError::RuleViolation if the board breaks the sudoku rules
+    pub fn try_new(board: &[[u8; 9]; 9]) -> Result<Board, SudokuError> {
+        for (i, row) in board.iter().enumerate() {
+            for (j, cell) in row.iter().enumerate() {
+                if *cell > 9 {
+                    return Err(SudokuError::InvalidValue {
+                        row: i,
+                        column: j,
+                        value: *cell as u32,
+                    });
+                }
+            }
         }
+        check_rules(Board::from_digits(board))
+    }
 
-        Ok(board)
+    /// Write the board to a csv file in the format read by from_csv,
+    /// so solutions can be saved and read back
+    pub fn write_csv(&self, path: impl AsRef<Path>) -> Result<(), SudokuError> {
+        Ok(std::fs::write(path, self.to_csv())?)
+    }
+
+    /// Get the board as 9 lines of comma separated cells, with `0` for empty cells
+    /// This is the format read by from_csv
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::with_capacity(162);
+        for i in 0..9 {
+            let cells: Vec<String> = (0..9)
+                .map(|j| self.get_cell(&Index(i, j)).unwrap_or(0).to_string())
+                .collect();
+            csv.push_str(&cells.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+impl TryFrom<[[u8; 9]; 9]> for Board {
+    type Error = SudokuError;
+
+    fn try_from(board: [[u8; 9]; 9]) -> Result<Self, Self::Error> {
+        Board::try_new(&board)
+    }
+}
+
+/// Check that a board read from input satisfies the sudoku rules
+fn check_rules(board: Board) -> Result<Board, SudokuError> {
+    match board.first_conflict() {
+        Some(index) => Err(SudokuError::RuleViolation {
+            row: index.0,
+            column: index.1,
+        }),
+        None => Ok(board),
+    }
+}
+
+/// Boards serialize as their one line format, so they read naturally in json, toml or yaml
+#[cfg(feature = "serde")]
+impl serde::Serialize for Board {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_line())
+    }
+}
+
+/// Boards deserialize from a string in any format accepted by parse
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Board {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
     }
 }
 
@@ -62,13 +500,14 @@ impl Board {
 mod tests {
 
     use super::*;
+    use std::path::PathBuf;
 
     #[test]
     fn test_from_csv() {
-        let board = Board::from_csv(&PathBuf::from("tests/test_board_pass.csv")).unwrap();
+        let board = Board::from_csv("tests/test_board_pass.csv").unwrap();
         assert_eq!(
             board,
-            Board::new(&[
+            Board::from_digits(&[
                 [0, 7, 3, 8, 0, 4, 2, 1, 6], //
                 [0, 0, 0, 2, 0, 9, 5, 0, 0], //
                 [2, 8, 5, 6, 0, 3, 0, 9, 7],
@@ -82,29 +521,386 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_example_puzzle() {
+        for name in ["easy", "medium", "hard"] {
+            let board = example_puzzle(name).unwrap();
+            assert!(board.is_valid_board());
+        }
+        assert_eq!(
+            example_puzzle("easy"),
+            Board::from_csv("tests/test_board_pass.csv").ok()
+        );
+        assert_eq!(example_puzzle("impossible"), None);
+    }
+
+    const EASY_LINE: &str =
+        "073804216000209500285603097000300074750000301004020000097560000000700100420030060";
+
+    #[test]
+    fn test_from_string() {
+        let expected = Board::from_csv("tests/test_board_pass.csv").unwrap();
+        assert_eq!(Board::from_string(EASY_LINE), Ok(expected.clone()));
+        assert_eq!(
+            Board::from_string(&EASY_LINE.replace('0', ".")),
+            Ok(expected.clone())
+        );
+        let grid = std::fs::read_to_string("tests/test_board_pass.csv")
+            .unwrap()
+            .replace(',', "");
+        assert_eq!(Board::from_string(&grid), Ok(expected));
+        assert_eq!(
+            Board::from_string(&EASY_LINE[1..]),
+            Err(SudokuError::DimensionError {
+                row: None,
+                found: 80,
+                expected: 81,
+                unit: Dimension::Cells
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        use crate::killer::Cage;
+        let cages: Vec<Cage> =
+            parse_lines("# cages\n\n3 r1c1 r1c2\n  17 r9c8 r9c9  ", ClueKind::Cage).unwrap();
+        assert_eq!(cages.len(), 2);
+        assert_eq!(cages[1].sum(), Some(17));
+        // comments and blank lines don't count towards the clue number
+        assert_eq!(
+            parse_lines::<Cage>("# cages\n3 r1c1 r1c2\n\n4 r1c3 x", ClueKind::Cage)
+                .unwrap_err()
+                .to_string(),
+            "Invalid cage 2: could not read \"x\" as a cell"
+        );
+    }
+
+    #[test]
+    fn test_puzzles_from_lines() {
+        let collection = format!(
+            "# a small collection\n{}\n\n{} rated 1.2\n{}\n",
+            EASY_LINE,
+            EASY_LINE.replace('0', "."),
+            &EASY_LINE[1..]
+        );
+        let puzzles: Vec<Result<Board, SudokuError>> = puzzles_from_lines(&collection).collect();
+        let expected = Board::from_line(EASY_LINE).unwrap();
+        assert_eq!(puzzles.len(), 3);
+        assert_eq!(puzzles[0], Ok(expected.clone()));
+        assert_eq!(puzzles[1], Ok(expected));
+        assert!(puzzles[2].is_err());
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let board = Board::from_line(EASY_LINE).unwrap();
+        let csv = std::fs::read_to_string("tests/test_board_pass.csv").unwrap();
+        assert_eq!(board.to_csv(), csv);
+        assert_eq!(board.to_csv().parse(), Ok(board));
+    }
+
+    #[test]
+    fn test_from_line() {
+        let expected = Board::from_csv("tests/test_board_pass.csv").unwrap();
+        assert_eq!(Board::from_line(EASY_LINE), Ok(expected.clone()));
+        assert_eq!(
+            Board::from_line(&format!("{}\n", EASY_LINE.replace('0', "."))),
+            Ok(expected)
+        );
+        let grid = std::fs::read_to_string("tests/test_board_pass.csv")
+            .unwrap()
+            .replace(',', "");
+        assert_eq!(
+            Board::from_line(&grid),
+            Err(SudokuError::DimensionError {
+                row: None,
+                found: 89,
+                expected: 81,
+                unit: Dimension::Cells
+            })
+        );
+        assert_eq!(
+            Board::from_line(&EASY_LINE.replacen('7', "x", 1)),
+            Err(SudokuError::ParseError {
+                row: 0,
+                column: 1,
+                found: "x".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_from_str_detects_format() {
+        let expected = Board::from_csv("tests/test_board_pass.csv").unwrap();
+        let csv = std::fs::read_to_string("tests/test_board_pass.csv").unwrap();
+        let grid = csv.replace(',', "");
+        let spaced_grid = csv.replace(',', " ");
+
+        assert_eq!(csv.parse::<Board>(), Ok(expected.clone()));
+        assert_eq!(EASY_LINE.parse::<Board>(), Ok(expected.clone()));
+        assert_eq!(grid.parse::<Board>(), Ok(expected.clone()));
+        assert_eq!(spaced_grid.parse::<Board>(), Ok(expected));
+        assert_eq!(
+            grid.replacen('\n', "", 1).parse::<Board>(),
+            Err(SudokuError::DimensionError {
+                row: None,
+                found: 8,
+                expected: 9,
+                unit: Dimension::Rows
+            })
+        );
+    }
+
+    #[test]
+    fn test_from_string_strict() {
+        let expected = Board::from_csv("tests/test_board_pass.csv").unwrap();
+        assert_eq!(
+            Board::from_string_strict(EASY_LINE),
+            Err(SudokuError::InvalidValue {
+                row: 0,
+                column: 0,
+                value: 0
+            })
+        );
+        assert_eq!(
+            Board::from_string_strict(&EASY_LINE.replace('0', ".")),
+            Ok(expected.clone())
+        );
+        assert_eq!(
+            Board::from_string_with_empty(&EASY_LINE.replace('0', "_"), '_'),
+            Ok(expected)
+        );
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let board = Board::from_csv("tests/does_not_exist.csv");
+        assert_eq!(
+            board,
+            Err(SudokuError::Io(std::io::Error::from(
+                std::io::ErrorKind::NotFound
+            )))
+        );
+    }
+
     #[test]
     fn test_invalid_non_int() {
-        let board = Board::from_csv(&PathBuf::from("tests/test_invalid_non_int.csv"));
-        assert_eq!(board, Err("Invalid csv file. Only int numbers allowed"));
+        let board = Board::from_csv("tests/test_invalid_non_int.csv");
+        assert_eq!(
+            board,
+            Err(SudokuError::ParseError {
+                row: 0,
+                column: 0,
+                found: "1.9".to_string()
+            })
+        );
     }
     #[test]
     fn test_invalid_8_lines() {
-        let board = Board::from_csv(&PathBuf::from("tests/test_invalid_8_lines.csv"));
-        assert_eq!(board, Err("Invalid csv file. Only 9x9 boards allowed"));
+        let board = Board::from_csv("tests/test_invalid_8_lines.csv");
+        assert_eq!(
+            board,
+            Err(SudokuError::DimensionError {
+                row: None,
+                found: 8,
+                expected: 9,
+                unit: Dimension::Rows
+            })
+        );
     }
 
     #[test]
     fn test_invalid_column() {
-        let board = Board::from_csv(&PathBuf::from("tests/test_invalid_column.csv"));
-        assert_eq!(board, Err("Invalid csv file. Only 9x9 boards allowed"));
+        let board = Board::from_csv("tests/test_invalid_column.csv");
+        assert_eq!(
+            board,
+            Err(SudokuError::DimensionError {
+                row: Some(0),
+                found: 8,
+                expected: 9,
+                unit: Dimension::Cells
+            })
+        );
     }
 
     #[test]
-    fn test_invalid_sudoku() {
-        let board = Board::from_csv(&PathBuf::from("tests/test_board_invalid_sudoku.csv"));
+    fn test_from_csv_padded() {
+        let path = PathBuf::from("tests/test_padded_rows.csv");
+        assert_eq!(
+            Board::from_csv(&path),
+            Err(SudokuError::DimensionError {
+                row: Some(1),
+                found: 7,
+                expected: 9,
+                unit: Dimension::Cells
+            })
+        );
+        let board = Board::from_csv_padded(&path).unwrap();
+        assert_eq!(board, Board::from_csv("tests/test_board_pass.csv").unwrap());
+    }
+
+    #[test]
+    fn test_from_csv_paths() {
+        let expected = Board::from_csv("tests/test_board_pass.csv").unwrap();
+        let path = PathBuf::from("tests/test_board_pass.csv");
+        assert_eq!(Board::from_csv(&path).unwrap(), expected);
+        assert_eq!(Board::from_csv(path).unwrap(), expected);
+        assert_eq!(
+            Board::from_csv(Path::new("tests/test_board_pass.csv")).unwrap(),
+            expected
+        );
+        assert_eq!(
+            Board::from_csv(String::from("tests/test_board_pass.csv")).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_csv_empty_markers() {
+        let expected = Board::from_csv("tests/test_board_pass.csv").unwrap();
+        let path = PathBuf::from("tests/test_board_pass_markers.csv");
+        assert_eq!(Board::from_csv(&path).unwrap(), expected);
+        assert_eq!(
+            std::fs::read_to_string(&path)
+                .unwrap()
+                .parse::<Board>()
+                .unwrap(),
+            expected
+        );
+
+        let strict = CsvOptions {
+            pad_rows: false,
+            empty_markers: Vec::new(),
+        };
+        assert_eq!(
+            Board::from_csv_with(&path, &strict),
+            Err(SudokuError::ParseError {
+                row: 0,
+                column: 0,
+                found: ".".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_padded_rejects_long_rows() {
+        let board = Board::from_csv_padded("tests/test_invalid_long_row.csv");
         assert_eq!(
             board,
-            Err("Invalid Board: Board does not satisfy sudoku rules")
+            Err(SudokuError::DimensionError {
+                row: Some(1),
+                found: 10,
+                expected: 9,
+                unit: Dimension::Cells
+            })
         );
     }
+
+    #[test]
+    fn test_from_sdk() {
+        let expected = Board::from_csv("tests/test_board_pass.csv").unwrap();
+        let ss = std::fs::read_to_string("tests/test_board_pass.ss").unwrap();
+        assert_eq!(Board::from_sdk(&ss).unwrap(), expected);
+        assert_eq!(ss.parse::<Board>().unwrap(), expected);
+
+        let sdk = "#AAbhishek\n#DAn easy puzzle\n[Puzzle]\n\
+            X738X4216\nXXX2X95XX\n2856X3X97\nXXX3XXX74\n75XXXX3X1\n\
+            XX4X2XXXX\nX9756XXXX\nXXX7XX1XX\n42XX3XX6X\n\
+            [State]\n973854216\n";
+        assert_eq!(Board::from_sdk(sdk).unwrap(), expected);
+        assert_eq!(sdk.parse::<Board>().unwrap(), expected);
+
+        assert!(matches!(
+            Board::from_sdk("|123|456|789|"),
+            Err(SudokuError::DimensionError { found: 9, .. })
+        ));
+    }
+
+    #[test]
+    fn test_read_sdm() {
+        let puzzles = read_sdm(Path::new("tests/test_collection_pass.sdm")).unwrap();
+        assert_eq!(puzzles.len(), 2);
+        assert!(read_sdm(Path::new("tests/test_collection.sdm")).is_err());
+        assert!(matches!(
+            read_sdm(Path::new("tests/does_not_exist.sdm")),
+            Err(SudokuError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let board = Board::from_csv("tests/test_board_pass.csv").unwrap();
+        let solution = board.solved().unwrap();
+        let path = std::env::temp_dir().join("sudoku_test_write_csv.csv");
+        solution.write_csv(&path).unwrap();
+        assert_eq!(Board::from_csv(&path).unwrap(), solution);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            solution.write_csv(Path::new("tests/no_such_dir/solution.csv")),
+            Err(SudokuError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_from_reader() {
+        let expected = Board::from_csv("tests/test_board_pass.csv").unwrap();
+        let file = std::fs::File::open("tests/test_board_pass.csv").unwrap();
+        assert_eq!(Board::from_reader(file).unwrap(), expected);
+        let cursor = std::io::Cursor::new(expected.to_line());
+        assert_eq!(Board::from_reader(cursor).unwrap(), expected);
+        let invalid_utf8: &[u8] = &[0xff, 0xfe];
+        assert!(matches!(
+            Board::from_reader(invalid_utf8),
+            Err(SudokuError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_try_new() {
+        let mut grid = [[0; 9]; 9];
+        grid[0][0] = 5;
+        assert!(Board::try_new(&grid).is_ok());
+        assert_eq!(Board::try_from(grid), Board::try_new(&grid));
+        grid[2][1] = 5;
+        assert_eq!(
+            Board::try_new(&grid),
+            Err(SudokuError::RuleViolation { row: 0, column: 0 })
+        );
+        grid[4][6] = 12;
+        assert_eq!(
+            Board::try_from(grid),
+            Err(SudokuError::InvalidValue {
+                row: 4,
+                column: 6,
+                value: 12
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_sudoku() {
+        let board = Board::from_csv("tests/test_board_invalid_sudoku.csv");
+        assert_eq!(board, Err(SudokuError::RuleViolation { row: 0, column: 0 }));
+        assert_eq!(
+            board.unwrap_err().to_string(),
+            "Invalid Board: Board does not satisfy sudoku rules at row 1, column 1"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let line =
+            ".738.4216...2.95..2856.3.97...3...7475....3.1..4.2.....9756.......7..1..42..3..6.";
+        let board: Board = line.parse().unwrap();
+        let json = serde_json::to_string(&board).unwrap();
+        assert_eq!(json, format!("\"{}\"", line));
+        assert_eq!(serde_json::from_str::<Board>(&json).unwrap(), board);
+
+        let csv = serde_json::to_string(&board.to_csv()).unwrap();
+        assert_eq!(serde_json::from_str::<Board>(&csv).unwrap(), board);
+        let invalid = serde_json::from_str::<Board>("\"11\"");
+        assert!(invalid.is_err());
+    }
 }
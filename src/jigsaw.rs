@@ -0,0 +1,423 @@
+//! This module contains jigsaw sudoku, where the 3x3 boxes are replaced by irregular regions of 9 cells
+//! Every row, column and region must hold each digit exactly once
+//!
+//! Regions are given as a map of 81 symbols, one per cell row by row, where cells sharing a symbol
+//! share a region, eg a map starting `111222333` puts the first three cells of row 1 in one region
+
+use crate::board::{Board, BoardError, Index, Search, SolverOptions};
+use crate::constraint::Constraint;
+use crate::error::{Dimension, SudokuError};
+use crate::sized::{first_conflict, SizedBoard};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// The region of each cell of a jigsaw board, numbered 0 to 8
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub struct Regions([[u8; 9]; 9]);
+
+impl Regions {
+    /// The standard 3x3 boxes, which make a jigsaw board an ordinary sudoku
+    pub fn boxes() -> Self {
+        let mut regions = [[0; 9]; 9];
+        for (row, cells) in regions.iter_mut().enumerate() {
+            for (column, region) in cells.iter_mut().enumerate() {
+                *region = (row / 3 * 3 + column / 3) as u8;
+            }
+        }
+        Regions(regions)
+    }
+    /// Get the region of the cell at a 0-based row and column, numbered from 0
+    /// in the order the regions first appear in the map
+    /// # Errors
+    /// Returns BoardError::OutOfBounds if the row or column is over 8
+    pub fn region(&self, row: usize, column: usize) -> Result<usize, BoardError> {
+        if row >= 9 || column >= 9 {
+            return Err(BoardError::OutOfBounds { row, column });
+        }
+        Ok(self.0[row][column] as usize)
+    }
+    /// Get the region of every cell, row by row
+    pub(crate) fn map(&self) -> &[[u8; 9]; 9] {
+        &self.0
+    }
+    /// Get the cells of a region, row by row
+    fn cells(&self, region: u8) -> impl Iterator<Item = Index> + '_ {
+        (0..81)
+            .map(|i| Index(i / 9, i % 9))
+            .filter(move |index| self.0[index.0][index.1] == region)
+    }
+}
+
+impl Constraint for Regions {
+    /// Check that no other cell of the region holds the value
+    fn allows(&self, board: &Board, index: &Index, value: u8) -> bool {
+        self.cells(self.0[index.0][index.1])
+            .filter(|cell| cell != index)
+            .all(|cell| board.get_cell(&cell) != Some(value))
+    }
+    /// Get the other cells of the region
+    fn peers(&self, index: &Index) -> Vec<Index> {
+        self.cells(self.0[index.0][index.1])
+            .filter(|cell| cell != index)
+            .collect()
+    }
+    /// The regions replace the boxes, so the solver keeps them as bitmasks
+    fn regions(&self) -> Option<Regions> {
+        Some(*self)
+    }
+    /// Once they replace the boxes, the solver's bitmasks keep the regions
+    fn is_built_in(&self) -> bool {
+        true
+    }
+}
+
+impl FromStr for Regions {
+    type Err = SudokuError;
+
+    /// Read a region map of 81 symbols, ignoring whitespace
+    /// Any symbols can be used as long as each one labels exactly 9 cells.
+    /// Regions are not checked to be connected
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let symbols: Vec<char> = s.chars().filter(|c| !c.is_whitespace()).collect();
+        if symbols.len() != 81 {
+            return Err(SudokuError::DimensionError {
+                row: None,
+                found: symbols.len(),
+                expected: 81,
+                unit: Dimension::Cells,
+            });
+        }
+        // number the labels in order of first appearance, counting their cells
+        let mut labels: Vec<(char, usize)> = Vec::new();
+        let mut numbers = HashMap::new();
+        let mut regions = [[0; 9]; 9];
+        for (i, label) in symbols.into_iter().enumerate() {
+            let number = *numbers.entry(label).or_insert_with(|| {
+                labels.push((label, 0));
+                labels.len() - 1
+            });
+            labels[number].1 += 1;
+            regions[i / 9][i % 9] = number as u8;
+        }
+        match labels.into_iter().find(|&(_, cells)| cells != 9) {
+            Some((label, cells)) => Err(SudokuError::InvalidRegion { label, cells }),
+            None => Ok(Regions(regions)),
+        }
+    }
+}
+
+impl Display for Regions {
+    /// Print the map as 9 lines of region numbers from 1 to 9
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.0 {
+            let line: String = row
+                .iter()
+                .map(|&region| char::from(b'1' + region))
+                .collect();
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// A 9x9 board with irregular regions in place of boxes
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Jigsaw {
+    /// Digits 1 to 9, 0 for empty cells
+    cells: [[u8; 9]; 9],
+    regions: Regions,
+}
+
+impl Jigsaw {
+    /// Create a board from rows of digits, with 0 for empty cells, and its regions
+    /// # Errors
+    /// 1. SudokuError::InvalidValue if a cell holds a number over 9
+    /// 2. SudokuError::RuleViolation if a row, column or region repeats a digit
+    pub fn try_new(cells: &[[u8; 9]; 9], regions: Regions) -> Result<Jigsaw, SudokuError> {
+        for (row, values) in cells.iter().enumerate() {
+            for (column, &value) in values.iter().enumerate() {
+                if value > 9 {
+                    return Err(SudokuError::InvalidValue {
+                        row,
+                        column,
+                        value: value as u32,
+                    });
+                }
+            }
+        }
+        match first_conflict(cells, &regions.0) {
+            Some((row, column)) => Err(SudokuError::RuleViolation { row, column }),
+            None => Ok(Jigsaw {
+                cells: *cells,
+                regions,
+            }),
+        }
+    }
+    /// Read a puzzle and its region map
+    /// The puzzle is read as 81 digits with . or 0 for empty cells, or as 9 comma separated rows,
+    /// and the region map as 81 symbols
+    /// # Example
+    /// ```
+    /// use sudoku_solver_by_roy::jigsaw::Jigsaw;
+    /// let regions = "
+    ///     111222333
+    ///     112222333
+    ///     111122333
+    ///     444555666
+    ///     444555666
+    ///     444555666
+    ///     777888999
+    ///     777888999
+    ///     777888999";
+    /// let puzzle = std::fs::read_to_string("tests/test_board_pass.csv").unwrap();
+    /// let board = Jigsaw::parse(&puzzle, regions).unwrap();
+    /// assert!(board.solved().unwrap().is_solved());
+    /// ```
+    pub fn parse(puzzle: &str, regions: &str) -> Result<Jigsaw, SudokuError> {
+        let regions: Regions = regions.parse()?;
+        Jigsaw::try_new(&SizedBoard::<9>::parse_cells(puzzle)?, regions)
+    }
+    /// Get the region map of the board
+    pub fn regions(&self) -> &Regions {
+        &self.regions
+    }
+    /// Get the digit at a 0-based row and column, or None if the cell is empty
+    /// # Errors
+    /// Returns BoardError::OutOfBounds if the row or column is over 8
+    pub fn get(&self, row: usize, column: usize) -> Result<Option<u8>, BoardError> {
+        if row >= 9 || column >= 9 {
+            return Err(BoardError::OutOfBounds { row, column });
+        }
+        Ok(Some(self.cells[row][column]).filter(|&value| value != 0))
+    }
+    /// Check that no row, column or region repeats a digit
+    pub fn is_valid_board(&self) -> bool {
+        first_conflict(&self.cells, &self.regions.0).is_none()
+    }
+    /// Check that every cell is filled and the rules hold
+    pub fn is_solved(&self) -> bool {
+        self.cells.iter().flatten().all(|&value| value != 0) && self.is_valid_board()
+    }
+    /// Get the digits as a board, with the regions left out
+    pub fn board(&self) -> Board {
+        Board::from_digits(&self.cells)
+    }
+    /// Solve a copy of the board, returning None if it has no solution
+    pub fn solved(&self) -> Option<Jigsaw> {
+        self.solved_with_options(&SolverOptions::default())
+            .ok()
+            .flatten()
+    }
+    /// Solve a copy of the board, giving up when the options say to
+    /// Returns Ok(None) if the board has no solution, and
+    /// BoardError::TimedOut or BoardError::Cancelled if the solve was aborted
+    pub fn solved_with_options(
+        &self,
+        options: &SolverOptions,
+    ) -> Result<Option<Jigsaw>, BoardError> {
+        let mut search = Search::new()
+            .with_constraint(self.regions)
+            .with_options(options);
+        let solution = search.run(&mut self.board());
+        match search.interrupted {
+            Some(reason) => Err(reason),
+            None => Ok(solution.map(|solution| self.with_board(&solution))),
+        }
+    }
+    /// Count the solutions of the board, stopping once limit are found
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        let mut count = 0;
+        if limit > 0 {
+            Search::new()
+                .with_constraint(self.regions)
+                .explore(&mut self.board(), &mut |_| {
+                    count += 1;
+                    count >= limit
+                });
+        }
+        count
+    }
+    /// Get a board with the same regions holding the digits of another board
+    fn with_board(&self, board: &Board) -> Jigsaw {
+        let mut cells = [[0; 9]; 9];
+        for (index, value) in board.cells() {
+            cells[index.0][index.1] = value.unwrap_or(0);
+        }
+        Jigsaw {
+            cells,
+            regions: self.regions,
+        }
+    }
+    /// Get the digits as a line of 81 cells, with . for empty cells
+    pub fn to_line(&self) -> String {
+        self.cells
+            .iter()
+            .flatten()
+            .map(|&value| match value {
+                0 => '.',
+                _ => char::from(b'0' + value),
+            })
+            .collect()
+    }
+}
+
+impl Display for Jigsaw {
+    /// Print the digits beside the region map, as 9 lines of `digits regions`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let line = self.to_line();
+        let regions = self.regions.to_string();
+        for (digits, regions) in line.as_bytes().chunks(9).zip(regions.lines()) {
+            writeln!(f, "{} {}", String::from_utf8_lossy(digits), regions)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::solve_with_constraints;
+    use crate::constraint::Diagonals;
+    use crate::fixtures::{easy_board, easy_solution};
+
+    /// Boxes 1 and 2 swap the cells at r2c3 and r3c4,
+    /// which hold the same digit in tests/test_board_pass_solution.csv
+    const REGIONS: &str = "
+        AAABBBCCC
+        AABBBBCCC
+        AAAABBCCC
+        DDDEEEFFF
+        DDDEEEFFF
+        DDDEEEFFF
+        GGGHHHIII
+        GGGHHHIII
+        GGGHHHIII";
+
+    #[test]
+    fn test_parse_regions() {
+        let regions: Regions = REGIONS.parse().unwrap();
+        assert_eq!(regions.region(1, 2), Ok(1));
+        assert_eq!(regions.region(2, 3), Ok(0));
+        assert_eq!(regions.region(8, 8), Ok(8));
+        assert_eq!(regions.to_string().parse::<Regions>().unwrap(), regions);
+
+        let standard = format!(
+            "{}{}{}",
+            "111222333".repeat(3),
+            "444555666".repeat(3),
+            "777888999".repeat(3)
+        );
+        let standard: Regions = standard.parse().unwrap();
+        assert_eq!(standard, Regions::boxes());
+    }
+
+    #[test]
+    fn test_invalid_regions() {
+        let uneven = REGIONS.replacen('B', "A", 1);
+        assert_eq!(
+            uneven.parse::<Regions>(),
+            Err(SudokuError::InvalidRegion {
+                label: 'A',
+                cells: 10
+            })
+        );
+        assert!(matches!(
+            "AAA".parse::<Regions>(),
+            Err(SudokuError::DimensionError { expected: 81, .. })
+        ));
+    }
+
+    #[test]
+    fn test_rules_follow_regions() {
+        let regions: Regions = REGIONS.parse().unwrap();
+        let mut cells = [[0; 9]; 9];
+        cells[0][0] = 1;
+        // r2c3 is in the second region, so it doesn't see r1c1
+        cells[1][2] = 1;
+        assert!(Jigsaw::try_new(&cells, regions).is_ok());
+        assert!(SizedBoard::<9>::try_new(&cells).is_err());
+        // r3c4 is in the first region, so it does
+        cells[1][2] = 0;
+        cells[2][3] = 1;
+        assert_eq!(
+            Jigsaw::try_new(&cells, regions),
+            Err(SudokuError::RuleViolation { row: 2, column: 3 })
+        );
+    }
+
+    #[test]
+    fn test_solve() {
+        let puzzle = std::fs::read_to_string("tests/test_board_pass.csv").unwrap();
+        let puzzle = Jigsaw::parse(&puzzle, REGIONS).unwrap();
+        let solved = puzzle.solved().unwrap();
+        assert!(solved.is_solved());
+        assert_eq!(solved.regions(), puzzle.regions());
+        for row in 0..9 {
+            for column in 0..9 {
+                if let Some(value) = puzzle.get(row, column).unwrap() {
+                    assert_eq!(solved.get(row, column), Ok(Some(value)));
+                }
+            }
+        }
+
+        // the sudoku solution also solves the jigsaw, as the swapped cells hold the same digit
+        let solution = std::fs::read_to_string("tests/test_board_pass_solution.csv").unwrap();
+        let solution = Jigsaw::parse(&solution, REGIONS).unwrap();
+        assert!(solution.is_solved());
+        assert_eq!(solution.count_solutions(2), 1);
+        let mut line = solution.to_line();
+        line.replace_range(0..3, "...");
+        let puzzle = Jigsaw::parse(&line, REGIONS).unwrap();
+        assert_eq!(puzzle.solved(), Some(solution));
+    }
+
+    #[test]
+    fn test_regions_constraint() {
+        let regions: Regions = REGIONS.parse().unwrap();
+        assert_eq!(regions.peers(&Index(1, 2)).len(), 8);
+        assert!(regions.peers(&Index(1, 2)).contains(&Index(0, 3)));
+
+        // the swapped cells hold the same digit, so the puzzle keeps its solution
+        let mut board = easy_board();
+        assert_eq!(
+            solve_with_constraints(&mut board, &[&regions]),
+            Some(easy_solution())
+        );
+
+        // r1c1 and r2c3 share a box but not a region
+        let mut cells = [[0; 9]; 9];
+        cells[0][0] = 1;
+        cells[1][2] = 1;
+        let board = Board::from_digits(&cells);
+        assert!(!board.is_valid_board());
+        assert!(regions.is_satisfied(&board));
+
+        // r1c1 and r3c4 share a region
+        cells[1][2] = 0;
+        cells[2][3] = 1;
+        let mut board = Board::from_digits(&cells);
+        assert!(board.is_valid_board());
+        assert!(!regions.is_satisfied(&board));
+        assert_eq!(solve_with_constraints(&mut board, &[&regions]), None);
+    }
+
+    #[test]
+    fn test_regions_with_other_constraints() {
+        let regions: Regions = REGIONS.parse().unwrap();
+        // regions in a list of constraints still replace the boxes,
+        // and the rest of the list is still checked
+        let combined: [&dyn Constraint; 2] = [&regions, &Diagonals];
+        let combined = combined.as_slice();
+        assert_eq!(
+            solve_with_constraints(&mut easy_board(), &[&regions, &Diagonals]),
+            None
+        );
+        // the easy solution follows the regions but not the diagonals
+        assert!(regions.is_satisfied(&easy_solution()));
+        assert_eq!(
+            solve_with_constraints(&mut easy_board(), &[&combined]),
+            None
+        );
+    }
+}
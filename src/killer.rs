@@ -0,0 +1,382 @@
+//! This module contains killer sudoku, where cells are grouped into cages with a target sum
+//! The digits of a cage must add up to its sum without repeating, on top of the usual rules
+//!
+//! Cages are read one per line as a sum followed by the cells of the cage in r1c1 notation,
+//! eg `15 r1c1 r1c2 r2c1`. A sum of `?` leaves the cage without a sum, so its digits only mustn't repeat.
+//! Blank lines and lines starting with `#` are skipped
+
+use crate::board::{Board, Index, Search};
+use crate::constraint::{sums_to, Constraint};
+use crate::error::{ClueKind, SudokuError};
+use crate::io::parse_lines;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// A group of cells whose digits add up to the sum without repeating
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Cage {
+    /// None for a cage whose digits only mustn't repeat
+    sum: Option<u8>,
+    cells: Vec<Index>,
+}
+
+impl Cage {
+    /// Make a cage of the cells, which is checked when the killer board is made
+    pub fn new(sum: u8, cells: Vec<Index>) -> Self {
+        Cage {
+            sum: Some(sum),
+            cells,
+        }
+    }
+    /// Make a cage without a sum, whose digits only mustn't repeat
+    pub fn without_sum(cells: Vec<Index>) -> Self {
+        Cage { sum: None, cells }
+    }
+    /// Get the total the digits of the cage must add up to, if it has one
+    pub fn sum(&self) -> Option<u8> {
+        self.sum
+    }
+    /// Get the cells of the cage
+    pub fn cells(&self) -> &[Index] {
+        &self.cells
+    }
+    /// Check that the digits placed so far are distinct and leave the rest of the sum
+    /// reachable with the digits not yet used
+    fn fits(&self, digit: impl Fn(&Index) -> u8) -> bool {
+        let mut used: u16 = 0;
+        let mut total = 0;
+        let mut empty = 0;
+        for cell in &self.cells {
+            match digit(cell) {
+                0 => empty += 1,
+                value => {
+                    if used & (1 << value) != 0 {
+                        return false;
+                    }
+                    used |= 1 << value;
+                    total += value as usize;
+                }
+            }
+        }
+        match self.sum {
+            Some(sum) => total <= sum as usize && sums_to(!used, empty, sum as usize - total),
+            None => self.cells.len() <= 9,
+        }
+    }
+}
+
+impl FromStr for Cage {
+    type Err = String;
+
+    /// Read a cage as its sum, or `?` for none, followed by its cells, eg `15 r1c1 r1c2 r2c1`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split_whitespace();
+        let sum = match parts.next().unwrap_or_default() {
+            "?" => None,
+            sum => Some(
+                sum.parse()
+                    .map_err(|_| format!("could not read {:?} as a sum", sum))?,
+            ),
+        };
+        let cells = parts
+            .map(|cell| {
+                Index::from_r1c1(cell).ok_or_else(|| format!("could not read {:?} as a cell", cell))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Cage { sum, cells })
+    }
+}
+
+impl Display for Cage {
+    /// Print the cage in the format it is read in
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.sum {
+            Some(sum) => write!(f, "{}", sum)?,
+            None => write!(f, "?")?,
+        }
+        for cell in &self.cells {
+            write!(f, " {}", cell)?;
+        }
+        Ok(())
+    }
+}
+
+/// Read cages, one per line
+/// # Errors
+/// Returns SudokuError::InvalidClue for a line that can't be read, numbering cages from 0
+pub fn parse_cages(s: &str) -> Result<Vec<Cage>, SudokuError> {
+    parse_lines(s, ClueKind::Cage)
+}
+
+/// A board with cages on top of the usual rules
+#[derive(Clone, Debug)]
+pub struct Killer {
+    board: Board,
+    cages: Vec<Cage>,
+    /// The cage of each cell, if it is in one
+    cage_of: [[Option<usize>; 9]; 9],
+}
+
+impl Killer {
+    /// Make a killer board from a board of clues, which is usually empty, and its cages
+    /// Cages don't have to cover the whole board
+    /// # Errors
+    /// 1. SudokuError::InvalidClue if a cage is empty, repeats or shares a cell,
+    ///    or its sum can't be made from distinct digits
+    /// 2. SudokuError::RuleViolation if the clues in a cage break it, at the first cell of the cage
+    pub fn try_new(board: Board, cages: Vec<Cage>) -> Result<Killer, SudokuError> {
+        let mut cage_of = [[None; 9]; 9];
+        for (number, cage) in cages.iter().enumerate() {
+            let invalid = |reason: &str| SudokuError::InvalidClue {
+                kind: ClueKind::Cage,
+                line: number,
+                reason: reason.to_string(),
+            };
+            if cage.cells.is_empty() {
+                return Err(invalid("has no cells"));
+            }
+            for cell in &cage.cells {
+                match cage_of[cell.0][cell.1] {
+                    Some(other) if other == number => {
+                        return Err(invalid(&format!("lists {} twice", cell)))
+                    }
+                    Some(other) => {
+                        return Err(invalid(&format!("shares {} with cage {}", cell, other + 1)))
+                    }
+                    None => cage_of[cell.0][cell.1] = Some(number),
+                }
+            }
+            if !cage.fits(|_| 0) {
+                return Err(invalid(&match cage.sum {
+                    Some(sum) => format!(
+                        "{} cells can't add up to {} without repeating",
+                        cage.cells.len(),
+                        sum
+                    ),
+                    None => format!("{} cells can't hold distinct digits", cage.cells.len()),
+                }));
+            }
+            if !cage.fits(|cell| board.get_cell(cell).unwrap_or(0)) {
+                let first = cage.cells[0];
+                return Err(SudokuError::RuleViolation {
+                    row: first.0,
+                    column: first.1,
+                });
+            }
+        }
+        Ok(Killer {
+            board,
+            cages,
+            cage_of,
+        })
+    }
+    /// Read cages onto an empty board
+    /// # Example
+    /// ```
+    /// use sudoku_solver_by_roy::killer::Killer;
+    /// let cages = std::fs::read_to_string("tests/test_killer_cages.txt").unwrap();
+    /// let killer = Killer::parse(&cages).unwrap();
+    /// let solution = killer.solved().unwrap();
+    /// assert!(killer.is_solution(&solution));
+    /// ```
+    pub fn parse(cages: &str) -> Result<Killer, SudokuError> {
+        Killer::try_new(Board::try_new(&[[0; 9]; 9])?, parse_cages(cages)?)
+    }
+    /// Get the clues of the board
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+    /// Get the cages of the board
+    pub fn cages(&self) -> &[Cage] {
+        &self.cages
+    }
+    /// Check that a board is complete, follows the usual rules, keeps the clues and fills every cage
+    pub fn is_solution(&self, board: &Board) -> bool {
+        board.is_complete()
+            && board.cells().all(|(index, value)| {
+                let clue = self.board.get_cell(&index);
+                clue.is_none() || clue == value
+            })
+            && self.is_satisfied(board)
+    }
+    /// Solve the board, returning None if it has no solution
+    pub fn solved(&self) -> Option<Board> {
+        let mut solution = None;
+        self.explore(&mut |solved| {
+            solution = Some(solved.clone());
+            true
+        });
+        solution
+    }
+    /// Count the solutions of the board, stopping once limit are found
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        let mut count = 0;
+        if limit > 0 {
+            self.explore(&mut |_| {
+                count += 1;
+                count >= limit
+            });
+        }
+        count
+    }
+    /// Visit the solutions until visit returns true
+    fn explore(&self, visit: &mut dyn FnMut(&Board) -> bool) {
+        Search::new()
+            .with_constraint(self)
+            .explore(&mut self.board.clone(), visit);
+    }
+}
+
+impl Constraint for Killer {
+    /// Check whether the value can go in the cell without breaking its cage
+    fn allows(&self, board: &Board, index: &Index, value: u8) -> bool {
+        match self.cage_of[index.0][index.1] {
+            Some(cage) => self.cages[cage].fits(|cell| {
+                if cell == index {
+                    value
+                } else {
+                    board.get_cell(cell).unwrap_or(0)
+                }
+            }),
+            None => true,
+        }
+    }
+    /// Get the other cells of the cell's cage, which can't repeat its digit
+    fn peers(&self, index: &Index) -> Vec<Index> {
+        match self.cage_of[index.0][index.1] {
+            Some(cage) => self.cages[cage]
+                .cells
+                .iter()
+                .filter(|&cell| cell != index)
+                .copied()
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::easy_solution;
+
+    fn cages() -> String {
+        std::fs::read_to_string("tests/test_killer_cages.txt").unwrap()
+    }
+
+    #[test]
+    fn test_parse_cages() {
+        let cages = parse_cages("# a comment\n\n3 r1c1 r1c2\n17 R9C8 r9c9").unwrap();
+        assert_eq!(cages.len(), 2);
+        assert_eq!(cages[0].sum(), Some(3));
+        assert_eq!(cages[1].cells(), [Index(8, 7), Index(8, 8)]);
+        assert_eq!(cages[0].to_string(), "3 r1c1 r1c2");
+
+        assert_eq!(
+            parse_cages("3 r1c1 r1c2\n4 r1c3 r0c1"),
+            Err(SudokuError::InvalidClue {
+                kind: ClueKind::Cage,
+                line: 1,
+                reason: "could not read \"r0c1\" as a cell".to_string()
+            })
+        );
+        assert!(parse_cages("x r1c1").is_err());
+    }
+
+    #[test]
+    fn test_invalid_cages() {
+        let invalid = |cages: &str| Killer::parse(cages).unwrap_err();
+        assert!(matches!(
+            invalid("3"),
+            SudokuError::InvalidClue {
+                kind: ClueKind::Cage,
+                line: 0,
+                ..
+            }
+        ));
+        assert!(matches!(
+            invalid("3 r1c1 r1c2\n9 r1c2 r1c3"),
+            SudokuError::InvalidClue {
+                kind: ClueKind::Cage,
+                line: 1,
+                ..
+            }
+        ));
+        assert!(matches!(
+            invalid("4 r1c1 r1c1"),
+            SudokuError::InvalidClue {
+                kind: ClueKind::Cage,
+                line: 0,
+                ..
+            }
+        ));
+        // two distinct digits add up to at least 3 and at most 17
+        assert!(matches!(
+            invalid("2 r1c1 r1c2"),
+            SudokuError::InvalidClue {
+                kind: ClueKind::Cage,
+                line: 0,
+                ..
+            }
+        ));
+        assert!(Killer::parse("17 r1c1 r1c2").is_ok());
+
+        let mut board = Board::try_new(&[[0; 9]; 9]).unwrap();
+        board.set(0, 0, 9).unwrap();
+        assert_eq!(
+            Killer::try_new(board, parse_cages("4 r1c1 r1c2").unwrap()).unwrap_err(),
+            SudokuError::RuleViolation { row: 0, column: 0 }
+        );
+    }
+
+    #[test]
+    fn test_solve() {
+        let killer = Killer::parse(&cages()).unwrap();
+        let solution = killer.solved().unwrap();
+        assert!(killer.is_solution(&solution));
+        assert_eq!(killer.count_solutions(2), 1);
+        assert_eq!(solution, easy_solution());
+
+        // a board following the usual rules isn't enough
+        let classic = crate::board::solve(&mut Board::try_new(&[[0; 9]; 9]).unwrap()).unwrap();
+        assert!(!killer.is_solution(&classic));
+    }
+
+    #[test]
+    fn test_unsolvable() {
+        // the first row can't hold two cages of 17 and one of 3,
+        // as 9 can only be used once
+        let killer = Killer::parse("17 r1c1 r1c2\n17 r1c3 r1c4\n3 r1c5 r1c6").unwrap();
+        assert_eq!(killer.solved(), None);
+        assert_eq!(killer.count_solutions(1), 0);
+    }
+
+    #[test]
+    fn test_cage_without_sum() {
+        let cages = parse_cages("? r1c1 r5c5").unwrap();
+        assert_eq!(cages[0], Cage::without_sum(vec![Index(0, 0), Index(4, 4)]));
+        assert_eq!(cages[0].sum(), None);
+        assert_eq!(cages[0].to_string(), "? r1c1 r5c5");
+
+        // r1c1 and r5c5 aren't peers, but the cage stops them repeating a digit
+        let mut board = Board::try_new(&[[0; 9]; 9]).unwrap();
+        board.set(0, 0, 1).unwrap();
+        let killer = Killer::try_new(board.clone(), cages).unwrap();
+        assert!(!killer.allows(&board, &Index(4, 4), 1));
+        assert!(killer.allows(&board, &Index(4, 4), 2));
+        let solution = killer.solved().unwrap();
+        assert!(killer.is_solution(&solution));
+        assert_ne!(solution.get(4, 4), Ok(Some(1)));
+
+        let cells = (0..9).map(|column| format!("r1c{}", column + 1));
+        let full = format!("? {} r2c1", cells.collect::<Vec<_>>().join(" "));
+        assert!(matches!(
+            Killer::parse(&full),
+            Err(SudokuError::InvalidClue {
+                kind: ClueKind::Cage,
+                line: 0,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,191 @@
+//! This module contains kropki dots, which sit between two orthogonally adjacent cells
+//! A white dot joins consecutive digits and a black dot joins digits where one is double the other
+//!
+//! Dots are read one per line as `w` or `b` followed by the two cells in r1c1 notation,
+//! eg `w r1c1 r1c2`. Blank lines and lines starting with `#` are skipped
+
+use crate::board::{Board, Index};
+use crate::constraint::Constraint;
+use crate::error::{ClueKind, SudokuError};
+use crate::io::parse_lines;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// The color of a kropki dot
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum Color {
+    /// The digits are consecutive, eg 4 and 5
+    White,
+    /// One digit is double the other, eg 3 and 6
+    Black,
+}
+
+impl Color {
+    /// Check whether two digits can sit either side of a dot of this color
+    fn joins(self, a: u8, b: u8) -> bool {
+        match self {
+            Color::White => a.abs_diff(b) == 1,
+            Color::Black => a == 2 * b || b == 2 * a,
+        }
+    }
+}
+
+/// A dot between two orthogonally adjacent cells
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub struct Dot {
+    color: Color,
+    cells: [Index; 2],
+}
+
+impl Dot {
+    /// Make a dot of the color between two cells
+    /// # Errors
+    /// Returns the reason if the cells aren't orthogonally adjacent
+    pub fn new(color: Color, first: Index, second: Index) -> Result<Dot, String> {
+        Ok(Dot {
+            color,
+            cells: Index::edge(first, second)?,
+        })
+    }
+    /// Get the color of the dot
+    pub fn color(&self) -> Color {
+        self.color
+    }
+    /// Get the two cells the dot sits between
+    pub fn cells(&self) -> [Index; 2] {
+        self.cells
+    }
+}
+
+impl Constraint for Dot {
+    /// Check that the value can sit next to the digit on the other side of the dot,
+    /// or next to some digit if the other cell is empty
+    fn allows(&self, board: &Board, index: &Index, value: u8) -> bool {
+        let other = match self.cells {
+            [first, other] | [other, first] if first == *index => other,
+            _ => return true,
+        };
+        match board.get_cell(&other) {
+            Some(digit) => self.color.joins(value, digit),
+            None => (1..=9).any(|digit| self.color.joins(value, digit)),
+        }
+    }
+}
+
+impl FromStr for Dot {
+    type Err = String;
+
+    /// Read a dot as its color followed by its cells, eg `w r1c1 r1c2` or `b r3c4 r4c4`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        let [color, first, second] = parts[..] else {
+            return Err(format!("expected a color and two cells, found {:?}", s));
+        };
+        let color = match color {
+            "w" | "W" => Color::White,
+            "b" | "B" => Color::Black,
+            _ => return Err(format!("could not read {:?} as a color", color)),
+        };
+        let cell = |cell: &str| {
+            Index::from_r1c1(cell).ok_or_else(|| format!("could not read {:?} as a cell", cell))
+        };
+        Dot::new(color, cell(first)?, cell(second)?)
+    }
+}
+
+impl Display for Dot {
+    /// Print the dot in the format it is read in
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let color = match self.color {
+            Color::White => 'w',
+            Color::Black => 'b',
+        };
+        write!(f, "{} {} {}", color, self.cells[0], self.cells[1])
+    }
+}
+
+/// Read kropki dots, one per line
+/// # Errors
+/// Returns SudokuError::InvalidClue for a line that can't be read or joins cells that aren't adjacent,
+/// numbering dots from 0
+pub fn parse_dots(s: &str) -> Result<Vec<Dot>, SudokuError> {
+    parse_lines(s, ClueKind::Dot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{count_solutions, solve_with_constraints};
+    use crate::fixtures::{assert_only_easy_solution, easy_board, easy_solution_without_rows};
+
+    fn dots() -> Vec<Dot> {
+        let dots = std::fs::read_to_string("tests/test_kropki.txt").unwrap();
+        parse_dots(&dots).unwrap()
+    }
+
+    #[test]
+    fn test_parse_dots() {
+        let dots = dots();
+        assert_eq!(dots.len(), 12);
+        assert_eq!(dots[0].color(), Color::White);
+        assert_eq!(dots[1].cells(), [Index(0, 5), Index(0, 6)]);
+        assert_eq!(dots[1].to_string(), "b r1c6 r1c7");
+
+        assert_eq!(
+            parse_dots("w r1c1 r1c2\nw r1c1 r2c2"),
+            Err(SudokuError::InvalidClue {
+                kind: ClueKind::Dot,
+                line: 1,
+                reason: "r1c1 and r2c2 aren't next to each other".to_string()
+            })
+        );
+        assert!(parse_dots("x r1c1 r1c2").is_err());
+        assert!(parse_dots("w r1c1").is_err());
+        assert!(parse_dots("b r1c1 r1c1").is_err());
+    }
+
+    #[test]
+    fn test_allows() {
+        let white: Dot = "w r1c1 r1c2".parse().unwrap();
+        let black: Dot = "b r1c1 r2c1".parse().unwrap();
+        let mut board = Board::try_new(&[[0; 9]; 9]).unwrap();
+        // 5, 7 and 9 have no half or double among the digits
+        assert!(!black.allows(&board, &Index(1, 0), 5));
+        assert!(black.allows(&board, &Index(1, 0), 8));
+        board.set(0, 0, 4).unwrap();
+        assert!(white.allows(&board, &Index(0, 1), 3));
+        assert!(white.allows(&board, &Index(0, 1), 5));
+        assert!(!white.allows(&board, &Index(0, 1), 6));
+        assert!(black.allows(&board, &Index(1, 0), 2));
+        assert!(!black.allows(&board, &Index(1, 0), 6));
+        // other cells aren't affected
+        assert!(white.allows(&board, &Index(0, 2), 6));
+    }
+
+    #[test]
+    fn test_solve() {
+        // clearing the first and third rows leaves four classic solutions,
+        // and only the dots touching those rows tell them apart
+        let puzzle = easy_solution_without_rows(&[0, 2]);
+        assert_eq!(count_solutions(&puzzle, None), 4);
+        assert_only_easy_solution(&puzzle, &dots().as_slice());
+    }
+
+    #[test]
+    fn test_wrong_dot() {
+        // the clues in r1c2 and r1c3 are 7 and 3, which aren't consecutive
+        let wrong: Dot = "w r1c2 r1c3".parse().unwrap();
+        assert!(!wrong.is_satisfied(&easy_board()));
+        assert_eq!(solve_with_constraints(&mut easy_board(), &[&wrong]), None);
+    }
+
+    #[test]
+    fn test_empty_grid() {
+        // the dots alone don't pin down a grid, but any grid found keeps to them
+        let dots = dots();
+        let mut empty = Board::try_new(&[[0; 9]; 9]).unwrap();
+        let solved = solve_with_constraints(&mut empty, &[&dots.as_slice()]).unwrap();
+        assert!(solved.is_complete());
+        assert!(dots.as_slice().is_satisfied(&solved));
+    }
+}
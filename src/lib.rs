@@ -1,2 +1,39 @@
+mod bitboard;
 pub mod board;
+pub mod candidates;
+pub mod constraint;
+pub mod dlx;
+pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(test)]
+mod fixtures;
+#[cfg(feature = "serde")]
+pub mod fpuzzles;
+pub mod generator;
+pub mod history;
 pub mod io;
+pub mod jigsaw;
+pub mod killer;
+pub mod kropki;
+pub mod logic;
+#[cfg(feature = "serde")]
+mod lzstring;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+pub mod parity;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod rating;
+pub mod samurai;
+pub mod sandwich;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod sized;
+pub mod solver;
+pub mod thermometer;
+#[cfg(feature = "serde")]
+pub mod trace;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod xv;
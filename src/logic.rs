@@ -0,0 +1,667 @@
+//! This module contains a solver that uses human solving techniques instead of guessing
+//! Every deduction is recorded as a step with an explanation of the reasoning,
+//! so the crate can be used to teach how a puzzle is solved
+//!
+//! Techniques are tried from simplest to hardest, going back to the simplest after
+//! every deduction. Puzzles that need more than these techniques are left partly solved
+
+use crate::board::{solve_steps, Board, Index, Unit};
+use crate::candidates::Candidates;
+use std::fmt::Display;
+
+/// Human solving techniques, from simplest to hardest
+#[derive(Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Hash, Debug)]
+pub enum Technique {
+    /// A value that fits in only one cell of a unit
+    HiddenSingle,
+    /// A cell with only one candidate left
+    NakedSingle,
+    /// A value confined to one line within a box, or one box within a line,
+    /// can be removed from the rest of that line or box
+    LockedCandidates,
+    /// Two cells of a unit sharing the same two candidates take both of them,
+    /// so they can be removed from the rest of the unit
+    NakedPair,
+    /// Two values of a unit confined to the same two cells take both of them,
+    /// so every other candidate can be removed from those cells
+    HiddenPair,
+    /// A value confined to the same two columns in two rows can be removed
+    /// from the rest of those columns, and likewise with rows and columns swapped
+    XWing,
+}
+
+impl Display for Technique {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Technique::HiddenSingle => "hidden single",
+            Technique::NakedSingle => "naked single",
+            Technique::LockedCandidates => "locked candidates",
+            Technique::NakedPair => "naked pair",
+            Technique::HiddenPair => "hidden pair",
+            Technique::XWing => "x-wing",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Techniques in the order they are tried
+const TECHNIQUES: [Technique; 6] = [
+    Technique::HiddenSingle,
+    Technique::NakedSingle,
+    Technique::LockedCandidates,
+    Technique::NakedPair,
+    Technique::HiddenPair,
+    Technique::XWing,
+];
+
+/// What a step found out about the board
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Deduction {
+    /// The cell must hold the value
+    Place(Index, u8),
+    /// Each cell can't hold the value paired with it
+    Eliminate(Vec<(Index, u8)>),
+}
+
+/// A single deduction made by the logical solver
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct LogicStep {
+    pub technique: Technique,
+    pub deduction: Deduction,
+    /// The reasoning behind the deduction, eg
+    /// "r1c3 must be 4: it is the only place left for 4 in row 1"
+    pub explanation: String,
+}
+
+/// Mask with the bits of all values 1 to 9 set
+const ALL_VALUES: u16 = 0b11_1111_1110;
+
+/// Iterate over all 27 units
+fn all_units() -> impl Iterator<Item = Unit> {
+    (0..9)
+        .map(Unit::Row)
+        .chain((0..9).map(Unit::Column))
+        .chain((0..9).map(Unit::Subgrid))
+}
+
+/// List the cells of eliminations for an explanation, eg "r1c4, r1c5"
+fn list_cells(eliminations: &[(Index, u8)]) -> String {
+    eliminations
+        .iter()
+        .map(|(index, _)| index.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// A board along with its pencil marks
+pub(crate) struct Grid {
+    board: Board,
+    candidates: Candidates,
+}
+
+impl Grid {
+    pub(crate) fn new(board: &Board) -> Self {
+        Grid {
+            board: board.clone(),
+            candidates: Candidates::new(board),
+        }
+    }
+    pub(crate) fn board(&self) -> &Board {
+        &self.board
+    }
+    fn mask(&self, index: &Index) -> u16 {
+        self.candidates.mask(index)
+    }
+    /// Find the next deduction, using the simplest technique that makes one
+    pub(crate) fn next_step(&self) -> Option<LogicStep> {
+        TECHNIQUES
+            .iter()
+            .find_map(|technique| self.find(*technique))
+    }
+    /// Apply a deduction to the board and candidates
+    pub(crate) fn apply(&mut self, step: &LogicStep) {
+        match &step.deduction {
+            Deduction::Place(index, value) => {
+                self.board.update_cell(index, *value);
+                self.candidates.place(index, *value);
+            }
+            Deduction::Eliminate(eliminations) => {
+                for (index, value) in eliminations {
+                    self.candidates.eliminate(index, *value);
+                }
+            }
+        }
+    }
+    /// Get the eliminations of the candidates in bits from the cells that still have them
+    fn eliminations(&self, cells: impl Iterator<Item = Index>, bits: u16) -> Vec<(Index, u8)> {
+        cells
+            .flat_map(|cell| {
+                (1..=9)
+                    .filter(move |value| bits & self.mask(&cell) & (1 << value) != 0)
+                    .map(move |value| (cell, value))
+            })
+            .collect()
+    }
+    fn find(&self, technique: Technique) -> Option<LogicStep> {
+        match technique {
+            Technique::HiddenSingle => self.hidden_single(),
+            Technique::NakedSingle => self.naked_single(),
+            Technique::LockedCandidates => self.locked_candidates(),
+            Technique::NakedPair => self.naked_pair(),
+            Technique::HiddenPair => self.hidden_pair(),
+            Technique::XWing => self.x_wing(),
+        }
+    }
+    fn hidden_single(&self) -> Option<LogicStep> {
+        for unit in all_units() {
+            for value in 1..=9 {
+                let mut places = unit
+                    .cells()
+                    .into_iter()
+                    .filter(|cell| self.mask(cell) & (1 << value) != 0);
+                if let (Some(cell), None) = (places.next(), places.next()) {
+                    return Some(LogicStep {
+                        technique: Technique::HiddenSingle,
+                        deduction: Deduction::Place(cell, value),
+                        explanation: format!(
+                            "{} must be {}: it is the only place left for {} in {}",
+                            cell, value, value, unit
+                        ),
+                    });
+                }
+            }
+        }
+        None
+    }
+    fn naked_single(&self) -> Option<LogicStep> {
+        for i in 0..9 {
+            for j in 0..9 {
+                let mask = self.mask(&Index(i, j));
+                if mask.count_ones() == 1 {
+                    let (cell, value) = (Index(i, j), mask.trailing_zeros() as u8);
+                    return Some(LogicStep {
+                        technique: Technique::NakedSingle,
+                        deduction: Deduction::Place(cell, value),
+                        explanation: format!(
+                            "{} must be {}: it is the only candidate left for the cell",
+                            cell, value
+                        ),
+                    });
+                }
+            }
+        }
+        None
+    }
+    fn locked_candidates(&self) -> Option<LogicStep> {
+        for unit in all_units() {
+            for value in 1..=9u8 {
+                let bit = 1 << value;
+                let places: Vec<Index> = unit
+                    .cells()
+                    .into_iter()
+                    .filter(|cell| self.mask(cell) & bit != 0)
+                    .collect();
+                let Some(first) = places.first() else {
+                    continue;
+                };
+                // the other unit holding every place for the value
+                let other = match unit {
+                    Unit::Subgrid(_) if places.iter().all(|cell| cell.0 == first.0) => {
+                        Unit::Row(first.0)
+                    }
+                    Unit::Subgrid(_) if places.iter().all(|cell| cell.1 == first.1) => {
+                        Unit::Column(first.1)
+                    }
+                    Unit::Row(_) | Unit::Column(_)
+                        if places
+                            .iter()
+                            .all(|cell| (cell.0 / 3, cell.1 / 3) == (first.0 / 3, first.1 / 3)) =>
+                    {
+                        Unit::Subgrid(first.0 / 3 * 3 + first.1 / 3)
+                    }
+                    _ => continue,
+                };
+                let eliminations = self.eliminations(
+                    other
+                        .cells()
+                        .into_iter()
+                        .filter(|cell| !places.contains(cell)),
+                    bit,
+                );
+                if !eliminations.is_empty() {
+                    return Some(LogicStep {
+                        technique: Technique::LockedCandidates,
+                        explanation: format!(
+                            "{} in {} can only go in {}, so it is removed from {}",
+                            value,
+                            unit,
+                            other,
+                            list_cells(&eliminations)
+                        ),
+                        deduction: Deduction::Eliminate(eliminations),
+                    });
+                }
+            }
+        }
+        None
+    }
+    fn naked_pair(&self) -> Option<LogicStep> {
+        for unit in all_units() {
+            let cells = unit.cells();
+            for (k, first) in cells.iter().enumerate() {
+                let pair = self.mask(first);
+                if pair.count_ones() != 2 {
+                    continue;
+                }
+                let Some(second) = cells[k + 1..].iter().find(|cell| self.mask(cell) == pair)
+                else {
+                    continue;
+                };
+                let eliminations = self.eliminations(
+                    cells
+                        .into_iter()
+                        .filter(|cell| cell != first && cell != second),
+                    pair,
+                );
+                if !eliminations.is_empty() {
+                    let low = pair.trailing_zeros();
+                    let high = 15 - pair.leading_zeros();
+                    return Some(LogicStep {
+                        technique: Technique::NakedPair,
+                        explanation: format!(
+                            "{} and {} can only be {} or {} in {}, so these are removed from {}",
+                            first,
+                            second,
+                            low,
+                            high,
+                            unit,
+                            list_cells(&eliminations)
+                        ),
+                        deduction: Deduction::Eliminate(eliminations),
+                    });
+                }
+            }
+        }
+        None
+    }
+    fn hidden_pair(&self) -> Option<LogicStep> {
+        for unit in all_units() {
+            let cells = unit.cells();
+            // positions in the unit where each value can go, as a mask indexed by value
+            let places: Vec<u16> = (0..=9)
+                .map(|value| {
+                    cells
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, cell)| self.mask(cell) & (1 << value) != 0)
+                        .fold(0, |mask, (k, _)| mask | 1 << k)
+                })
+                .collect();
+            for low in 1..=9 {
+                if places[low].count_ones() != 2 {
+                    continue;
+                }
+                let Some(high) = (low + 1..=9).find(|value| places[*value] == places[low]) else {
+                    continue;
+                };
+                let first = cells[places[low].trailing_zeros() as usize];
+                let second = cells[15 - places[low].leading_zeros() as usize];
+                let others = ALL_VALUES & !(1 << low | 1 << high);
+                let eliminations = self.eliminations([first, second].into_iter(), others);
+                if !eliminations.is_empty() {
+                    return Some(LogicStep {
+                        technique: Technique::HiddenPair,
+                        explanation: format!(
+                            "{} and {} can only go in {} and {} in {}, so the other candidates are removed from them",
+                            low, high, first, second, unit
+                        ),
+                        deduction: Deduction::Eliminate(eliminations),
+                    });
+                }
+            }
+        }
+        None
+    }
+    fn x_wing(&self) -> Option<LogicStep> {
+        // lines are rows crossed by columns, then columns crossed by rows
+        for rows_first in [true, false] {
+            let line = |l| {
+                if rows_first {
+                    Unit::Row(l)
+                } else {
+                    Unit::Column(l)
+                }
+            };
+            let cross = |k| {
+                if rows_first {
+                    Unit::Column(k)
+                } else {
+                    Unit::Row(k)
+                }
+            };
+            for value in 1..=9u8 {
+                let bit = 1 << value;
+                // positions along each line where the value can go, as a mask
+                let positions: Vec<u16> = (0..9)
+                    .map(|l| {
+                        line(l)
+                            .cells()
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, cell)| self.mask(cell) & bit != 0)
+                            .fold(0, |mask, (k, _)| mask | 1 << k)
+                    })
+                    .collect();
+                for first in 0..9 {
+                    if positions[first].count_ones() != 2 {
+                        continue;
+                    }
+                    let Some(second) = (first + 1..9).find(|l| positions[*l] == positions[first])
+                    else {
+                        continue;
+                    };
+                    let near = positions[first].trailing_zeros() as usize;
+                    let far = 15 - positions[first].leading_zeros() as usize;
+                    // the kth cell of a crossing unit lies on the kth line
+                    let eliminations = self.eliminations(
+                        [near, far].into_iter().flat_map(|k| {
+                            cross(k)
+                                .cells()
+                                .into_iter()
+                                .enumerate()
+                                .filter(|(l, _)| *l != first && *l != second)
+                                .map(|(_, cell)| cell)
+                        }),
+                        bit,
+                    );
+                    if !eliminations.is_empty() {
+                        return Some(LogicStep {
+                            technique: Technique::XWing,
+                            explanation: format!(
+                                "{} in {} and {} can only go in {} and {}, so it is removed from {}",
+                                value,
+                                line(first),
+                                line(second),
+                                cross(near),
+                                cross(far),
+                                list_cells(&eliminations)
+                            ),
+                            deduction: Deduction::Eliminate(eliminations),
+                        });
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Solve the board using only human techniques, recording each deduction in order
+/// Returns the board as far as the techniques could get along with the steps taken.
+/// The board is complete if the puzzle needed nothing beyond these techniques
+pub fn solve_logically(board: &Board) -> (Board, Vec<LogicStep>) {
+    let mut steps = Vec::new();
+    if !board.is_valid_board() {
+        return (board.clone(), steps);
+    }
+    let mut grid = Grid::new(board);
+    while !grid.board().is_complete() {
+        let Some(step) = grid.next_step() else {
+            break;
+        };
+        grid.apply(&step);
+        steps.push(step);
+    }
+    (grid.board, steps)
+}
+
+/// Something the solver did, in the order solve_traced reports it
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum SolveEvent {
+    /// The cell was filled with the value, as a guess between several candidates or as a deduction
+    Place {
+        index: Index,
+        value: u8,
+        is_guess: bool,
+    },
+    /// The cell was cleared, as a guess below it led to a contradiction
+    Backtrack { index: Index },
+    /// Each cell can't hold the value paired with it, as shown by the technique
+    Eliminate {
+        eliminations: Vec<(Index, u8)>,
+        technique: Technique,
+    },
+}
+
+/// Solve the board, reporting every placement, elimination and backtrack so frontends can animate it
+/// Human techniques are used first, and the backtracking solver finishes off whatever they leave.
+/// Replaying the placements and backtracks in order on the board gives the solution if there is one
+/// # Example
+/// ```
+/// use sudoku_solver_by_roy::board::Board;
+/// use sudoku_solver_by_roy::logic::{solve_traced, SolveEvent};
+/// let board: Board =
+///     ".738.4216...2.95..2856.3.97...3...7475....3.1..4.2.....9756.......7..1..42..3..6."
+///         .parse()
+///         .unwrap();
+/// let guesses = solve_traced(&board)
+///     .filter(|event| matches!(event, SolveEvent::Place { is_guess: true, .. }))
+///     .count();
+/// assert_eq!(guesses, 0);
+/// ```
+pub fn solve_traced(board: &Board) -> impl Iterator<Item = SolveEvent> {
+    let (reached, steps) = solve_logically(board);
+    let logical = steps.into_iter().map(|step| match step.deduction {
+        Deduction::Place(index, value) => SolveEvent::Place {
+            index,
+            value,
+            is_guess: false,
+        },
+        Deduction::Eliminate(eliminations) => SolveEvent::Eliminate {
+            eliminations,
+            technique: step.technique,
+        },
+    });
+    let searched = solve_steps(&reached)
+        .into_iter()
+        .map(|step| match step.value {
+            0 => SolveEvent::Backtrack { index: step.index },
+            value => SolveEvent::Place {
+                index: step.index,
+                value,
+                is_guess: step.is_guess,
+            },
+        });
+    // the trace is collected up front, as the search reports its steps through a callback
+    logical.chain(searched).collect::<Vec<_>>().into_iter()
+}
+
+/// The next cell that can be deduced, and how
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Hint {
+    pub index: Index,
+    pub value: u8,
+    /// The hardest technique needed to reach the deduction
+    pub technique: Technique,
+    /// The reasoning, as the candidate eliminations needed first followed by the placement itself
+    pub steps: Vec<LogicStep>,
+}
+
+impl Board {
+    /// Get the next cell that can be deduced with human techniques,
+    /// along with the technique and reasoning behind it
+    /// Returns None if the board is complete, breaks the rules,
+    /// or needs more than the logical solver's techniques
+    /// # Example
+    /// ```
+    /// use sudoku_solver_by_roy::board::Board;
+    /// let board: Board =
+    ///     ".738.4216...2.95..2856.3.97...3...7475....3.1..4.2.....9756.......7..1..42..3..6."
+    ///         .parse()
+    ///         .unwrap();
+    /// let hint = board.hint().unwrap();
+    /// assert_eq!(hint.value, 5);
+    /// println!("{}", hint.steps.last().unwrap().explanation);
+    /// ```
+    pub fn hint(&self) -> Option<Hint> {
+        if !self.is_valid_board() {
+            return None;
+        }
+        let mut grid = Grid::new(self);
+        let mut steps = Vec::new();
+        while let Some(step) = grid.next_step() {
+            if let Deduction::Place(index, value) = step.deduction {
+                steps.push(step);
+                let technique = steps.iter().map(|step| step.technique).max()?;
+                return Some(Hint {
+                    index,
+                    value,
+                    technique,
+                    steps,
+                });
+            }
+            grid.apply(&step);
+            steps.push(step);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve_traced() {
+        // Arto Inkala's puzzle needs guessing after the human techniques run out
+        let board: Board =
+            "8..........36......7..9.2...5...7.......457.....1...3...1....68..85...1..9....4.."
+                .parse()
+                .unwrap();
+        let events: Vec<SolveEvent> = solve_traced(&board).collect();
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, SolveEvent::Place { is_guess: true, .. })));
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, SolveEvent::Backtrack { .. })));
+
+        let mut replay = board.clone();
+        for event in events {
+            match event {
+                SolveEvent::Place { index, value, .. } => replay.update_cell(&index, value),
+                SolveEvent::Backtrack { index } => replay.update_cell(&index, 0),
+                SolveEvent::Eliminate { .. } => (),
+            }
+        }
+        assert_eq!(Some(replay), board.solved());
+    }
+
+    #[test]
+    fn test_solve_logically() {
+        let board = Board::from_csv("tests/test_board_pass.csv").unwrap();
+        let (solved, steps) = solve_logically(&board);
+        assert_eq!(Some(solved), board.solved());
+        assert!(steps
+            .iter()
+            .all(|step| step.technique == Technique::HiddenSingle));
+        let first = &steps[0];
+        assert_eq!(first.deduction, Deduction::Place(Index(0, 4), 5));
+        assert_eq!(
+            first.explanation,
+            "r1c5 must be 5: it is the only place left for 5 in row 1"
+        );
+    }
+
+    #[test]
+    fn test_solve_logically_eliminations() {
+        let board: Board =
+            ".....82......7...5.1....8...7......66891..3....3..9...56..8.1....1.4......2..19.."
+                .parse()
+                .unwrap();
+        let (solved, steps) = solve_logically(&board);
+        assert_eq!(Some(solved), board.solved());
+        let elimination = steps
+            .iter()
+            .find(|step| matches!(step.deduction, Deduction::Eliminate(_)))
+            .unwrap();
+        assert!(elimination.technique >= Technique::LockedCandidates);
+        assert!(elimination.explanation.contains("so"));
+    }
+
+    #[test]
+    fn test_hint() {
+        let board = Board::from_csv("tests/test_board_pass.csv").unwrap();
+        let hint = board.hint().unwrap();
+        assert_eq!((hint.index, hint.value), (Index(0, 4), 5));
+        assert_eq!(hint.technique, Technique::HiddenSingle);
+        assert_eq!(hint.steps.len(), 1);
+
+        // every hint along the way agrees with the solution
+        let board: Board =
+            ".....82......7...5.1....8...7......66891..3....3..9...56..8.1....1.4......2..19.."
+                .parse()
+                .unwrap();
+        let solution = board.solved().unwrap();
+        let mut partial = board.clone();
+        let mut needed_elimination = false;
+        while let Some(hint) = partial.hint() {
+            assert_eq!(solution.get_cell(&hint.index), Some(hint.value));
+            needed_elimination |= hint.steps.len() > 1;
+            partial.update_cell(&hint.index, hint.value);
+        }
+        assert_eq!(partial, solution);
+        assert!(needed_elimination);
+
+        assert_eq!(solution.hint(), None);
+    }
+
+    #[test]
+    fn test_x_wing() {
+        // an x-wing on 7 in rows 2 and 6, columns 4 and 8
+        let board: Board =
+            "1.....569492.561.8.561.924...964.8.1.64.1....218.356.4.4.5...169.5.614.2621.....5"
+                .parse()
+                .unwrap();
+        let grid = Grid::new(&board);
+        let step = grid.find(Technique::XWing).unwrap();
+        assert!(step
+            .explanation
+            .starts_with("7 in row 2 and row 6 can only go in column 4 and column 8"));
+        let Deduction::Eliminate(eliminations) = &step.deduction else {
+            panic!("x-wing should eliminate candidates");
+        };
+        assert!(eliminations.iter().all(|(_, value)| *value == 7));
+    }
+
+    #[test]
+    fn test_hidden_pair() {
+        // 1 and 2 are removed from the rest of row 1, leaving them only r1c1 and r1c2
+        let mut grid = Grid::new(&Board::from_digits(&[[0; 9]; 9]));
+        let row: Vec<(Index, u8)> = (2..9)
+            .flat_map(|column| [(Index(0, column), 1), (Index(0, column), 2)])
+            .collect();
+        grid.apply(&LogicStep {
+            technique: Technique::XWing,
+            deduction: Deduction::Eliminate(row),
+            explanation: String::new(),
+        });
+        assert_eq!(grid.find(Technique::NakedPair), None);
+        let step = grid.find(Technique::HiddenPair).unwrap();
+        assert_eq!(
+            step.explanation,
+            "1 and 2 can only go in r1c1 and r1c2 in row 1, so the other candidates are removed from them"
+        );
+        let Deduction::Eliminate(eliminations) = &step.deduction else {
+            panic!("hidden pair should eliminate candidates");
+        };
+        assert_eq!(eliminations.len(), 14);
+        assert!(eliminations
+            .iter()
+            .all(|(index, value)| index.0 == 0 && index.1 < 2 && *value > 2));
+
+        // once the other candidates are gone the pair is naked, and there is nothing left to remove
+        grid.apply(&step);
+        assert_eq!(grid.find(Technique::HiddenPair), None);
+    }
+}
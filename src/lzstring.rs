@@ -0,0 +1,254 @@
+//! This module contains the base64 flavour of LZ-String, the compression used by
+//! f-puzzles and SudokuPad links to fit a puzzle's JSON into a URL
+//!
+//! LZ-String works on UTF-16 code units and writes its codes 6 bits to a base64 character,
+//! starting from the most significant bit
+
+use std::collections::{HashMap, HashSet};
+
+/// The base64 characters, where each character stands for its position
+const KEY: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// The longest output decompression produces, in UTF-16 code units
+/// A short link can expand to far more than any puzzle needs, so longer outputs are rejected
+const MAX_LENGTH: usize = 256 * 1024;
+
+/// Reads codes bit by bit from base64 characters
+struct Bits<'a> {
+    chars: &'a [u8],
+    /// The next character to read
+    next: usize,
+    /// The value of the character being read
+    value: u8,
+    /// The bit of the value to read next, 0 once every bit is read
+    position: u8,
+}
+
+impl Bits<'_> {
+    /// Read a code of the given width, least significant bit first
+    /// Returns None if the input runs out or holds a character outside the base64 alphabet
+    fn read(&mut self, width: u32) -> Option<u32> {
+        let mut code = 0;
+        for bit in 0..width {
+            if self.position == 0 {
+                let char = *self.chars.get(self.next)?;
+                self.value = KEY.iter().position(|&key| key == char)? as u8;
+                self.next += 1;
+                self.position = 1 << 5;
+            }
+            if self.value & self.position != 0 {
+                code |= 1 << bit;
+            }
+            self.position >>= 1;
+        }
+        Some(code)
+    }
+}
+
+/// Decompress a string compressed to base64 by LZ-String
+/// Trailing `=` padding is ignored
+/// Returns None if the input isn't valid compressed data or decompresses to over MAX_LENGTH code units
+pub(crate) fn decompress_from_base64(input: &str) -> Option<String> {
+    let mut bits = Bits {
+        chars: input.trim_end_matches('=').as_bytes(),
+        next: 0,
+        value: 0,
+        position: 0,
+    };
+    // codes 0 to 2 mark an 8 bit character, a 16 bit character and the end of the data
+    let mut dictionary: Vec<Vec<u16>> = vec![Vec::new(); 3];
+    let mut enlarge_in: u32 = 4;
+    let mut width = 3;
+    let first = match bits.read(2)? {
+        0 => bits.read(8)?,
+        1 => bits.read(16)?,
+        _ => return Some(String::new()),
+    };
+    let mut previous = vec![first as u16];
+    dictionary.push(previous.clone());
+    let mut result = previous.clone();
+    loop {
+        let mut code = bits.read(width)? as usize;
+        if code < 2 {
+            let char = bits.read(if code == 0 { 8 } else { 16 })?;
+            dictionary.push(vec![char as u16]);
+            code = dictionary.len() - 1;
+            enlarge_in -= 1;
+        } else if code == 2 {
+            return String::from_utf16(&result).ok();
+        }
+        if enlarge_in == 0 {
+            enlarge_in = 1 << width;
+            width += 1;
+        }
+        let entry = match dictionary.get(code) {
+            Some(entry) => entry.clone(),
+            // the code being defined by this very step
+            None if code == dictionary.len() => {
+                let mut entry = previous.clone();
+                entry.push(previous[0]);
+                entry
+            }
+            None => return None,
+        };
+        if result.len() + entry.len() > MAX_LENGTH {
+            return None;
+        }
+        result.extend(&entry);
+        let mut added = previous;
+        added.push(entry[0]);
+        dictionary.push(added);
+        enlarge_in -= 1;
+        previous = entry;
+        if enlarge_in == 0 {
+            enlarge_in = 1 << width;
+            width += 1;
+        }
+    }
+}
+
+/// The state of a compression, writing codes bit by bit into base64 characters
+struct Compressor {
+    output: String,
+    /// The bits of the character being written
+    value: u8,
+    /// How many bits of the character are written
+    position: u8,
+    /// The code of every phrase seen so far
+    dictionary: HashMap<Vec<u16>, u32>,
+    /// Characters in the dictionary that haven't been written out in full yet
+    to_create: HashSet<u16>,
+    /// Codes left before the width grows by a bit
+    enlarge_in: u32,
+    width: u32,
+}
+
+impl Compressor {
+    /// Write a code of the given width, least significant bit first
+    fn write(&mut self, mut code: u32, width: u32) {
+        for _ in 0..width {
+            self.value = self.value << 1 | (code & 1) as u8;
+            self.position += 1;
+            if self.position == 6 {
+                self.output.push(KEY[self.value as usize] as char);
+                self.value = 0;
+                self.position = 0;
+            }
+            code >>= 1;
+        }
+    }
+    /// Count down to the next time the codes need another bit
+    fn count_code(&mut self) {
+        self.enlarge_in -= 1;
+        if self.enlarge_in == 0 {
+            self.enlarge_in = 1 << self.width;
+            self.width += 1;
+        }
+    }
+    /// Write the code of a phrase, first writing out its character if it is new
+    fn emit(&mut self, phrase: &[u16]) {
+        if phrase.len() == 1 && self.to_create.remove(&phrase[0]) {
+            let char = phrase[0];
+            if char < 256 {
+                self.write(0, self.width);
+                self.write(char as u32, 8);
+            } else {
+                self.write(1, self.width);
+                self.write(char as u32, 16);
+            }
+            self.count_code();
+        } else {
+            self.write(self.dictionary[phrase], self.width);
+        }
+        self.count_code();
+    }
+}
+
+/// Compress a string to base64 with LZ-String, padded with `=` to a multiple of 4 characters
+pub(crate) fn compress_to_base64(input: &str) -> String {
+    let mut compressor = Compressor {
+        output: String::new(),
+        value: 0,
+        position: 0,
+        dictionary: HashMap::new(),
+        to_create: HashSet::new(),
+        enlarge_in: 2,
+        width: 2,
+    };
+    // codes 0 to 2 mark an 8 bit character, a 16 bit character and the end of the data
+    let mut next_code = 3;
+    let mut phrase: Vec<u16> = Vec::new();
+    for char in input.encode_utf16() {
+        if !compressor.dictionary.contains_key([char].as_slice()) {
+            compressor.dictionary.insert(vec![char], next_code);
+            compressor.to_create.insert(char);
+            next_code += 1;
+        }
+        let mut extended = phrase.clone();
+        extended.push(char);
+        if compressor.dictionary.contains_key(&extended) {
+            phrase = extended;
+        } else {
+            compressor.emit(&phrase);
+            compressor.dictionary.insert(extended, next_code);
+            next_code += 1;
+            phrase = vec![char];
+        }
+    }
+    if !phrase.is_empty() {
+        compressor.emit(&phrase);
+    }
+    compressor.write(2, compressor.width);
+    // LZ-String always pads with at least one zero bit, even after a full character
+    compressor.write(0, 6 - compressor.position as u32);
+    let mut output = compressor.output;
+    while !output.len().is_multiple_of(4) {
+        output.push('=');
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decompress_from_base64() {
+        // the start of every f-puzzles link for a 9x9 grid
+        assert_eq!(
+            decompress_from_base64("N4IgzglgXgpiBcBOANCA5gJwgEwQbT2AF9ljSSzKLryBdIA=").as_deref(),
+            Some(r#"{"size":9,"grid":[[{},{},{},{},{},{},{},{},{}]"#)
+        );
+        assert_eq!(decompress_from_base64("").as_deref(), None);
+        assert_eq!(decompress_from_base64("N4Ig*").as_deref(), None);
+
+        let longest = "a".repeat(MAX_LENGTH);
+        assert_eq!(
+            decompress_from_base64(&compress_to_base64(&longest)).as_deref(),
+            Some(longest.as_str())
+        );
+        let too_long = compress_to_base64(&"a".repeat(MAX_LENGTH + 1));
+        assert_eq!(decompress_from_base64(&too_long), None);
+    }
+
+    #[test]
+    fn test_compress_to_base64() {
+        let grid = r#"{"size":9,"grid":[[{},{},{},{},{},{},{},{},{}]"#;
+        assert_eq!(
+            compress_to_base64(grid),
+            "N4IgzglgXgpiBcBOANCA5gJwgEwQbT2AF9ljSSzKLryBdIA="
+        );
+        for text in [
+            "",
+            "a",
+            "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+            "abcabcabd",
+            "größer ∑ 😀",
+        ] {
+            assert_eq!(
+                decompress_from_base64(&compress_to_base64(text)).as_deref(),
+                Some(text)
+            );
+        }
+    }
+}
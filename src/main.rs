@@ -1,28 +1,598 @@
-use clap::Parser;
-use std::path::PathBuf;
-use sudoku_solver_by_roy::board::{solve, Board};
+use clap::{Parser, Subcommand, ValueEnum};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+#[cfg(feature = "serde")]
+use sudoku_solver_by_roy::board::solution_signature_with_stats;
+use sudoku_solver_by_roy::board::{count_solutions, solve, solve_with_constraints, Board};
+use sudoku_solver_by_roy::error::SudokuError;
+#[cfg(feature = "serde")]
+use sudoku_solver_by_roy::fpuzzles::FPuzzle;
+use sudoku_solver_by_roy::generator::{generate_with_symmetry, Difficulty, Rng, Symmetry};
+use sudoku_solver_by_roy::io::puzzles_from_lines;
+use sudoku_solver_by_roy::jigsaw::{Jigsaw, Regions};
+use sudoku_solver_by_roy::rating::rate;
+
+/// Exit code for input that can't be read as a board, like a missing file or a malformed grid
+/// Code 1 is left for other failures, and 2 is used by clap for bad arguments
+const EXIT_UNREADABLE: i32 = 3;
+/// Exit code for boards that break the sudoku rules
+const EXIT_INVALID: i32 = 4;
+/// Exit code for puzzles with no solution
+const EXIT_UNSOLVABLE: i32 = 5;
 
 #[derive(Parser, Debug)]
 #[command(author,version,about,long_about=None)]
 struct Args {
-    #[arg()]
-    csv_path: PathBuf,
+    #[command(subcommand)]
+    command: Command,
+    /// Only print the result as a single line.
+    /// Nothing is printed on failure, and the exit code is nonzero
+    #[arg(short, long, global = true)]
+    quiet: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Solve a puzzle, or every csv file in a directory
+    Solve {
+        /// Puzzle file, read from stdin if missing or -
+        path: Option<PathBuf>,
+        /// Solve every csv file in a directory, printing one line per file
+        #[arg(long, conflicts_with = "path")]
+        batch: Option<PathBuf>,
+        /// Treat the input as many puzzles, one per line as in .sdm files,
+        /// printing one line per puzzle
+        #[arg(long, conflicts_with_all = ["batch", "expect"])]
+        multi: bool,
+        /// Number of threads to solve a multi puzzle file with.
+        /// The output keeps the order of the input
+        #[arg(long, requires = "multi", default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..))]
+        jobs: u16,
+        /// In batch mode, print the estimated difficulty of each puzzle instead of solving it
+        #[arg(long, requires = "batch")]
+        tag_difficulty: bool,
+        /// Compare the solution against an expected solution csv
+        #[arg(long)]
+        expect: Option<PathBuf>,
+        /// Solve as jigsaw sudoku, with the irregular regions read from a map of 81 symbols
+        /// in place of the 3x3 boxes
+        #[arg(long, conflicts_with_all = ["batch", "multi", "expect", "format"])]
+        regions: Option<PathBuf>,
+        /// Print the solution as text, or as json with the puzzle, validity and solve statistics
+        #[arg(long, value_enum, default_value = "text", conflicts_with_all = ["batch", "multi", "expect"])]
+        format: OutputFormat,
+        /// Write the solution as csv to a file instead of printing it
+        #[arg(short, long, conflicts_with_all = ["batch", "multi", "format"])]
+        output: Option<PathBuf>,
+        /// Draw the solved grid with box-drawing characters
+        #[arg(long)]
+        pretty: bool,
+        /// Don't color the clues and the solved cells differently.
+        /// Colors are also left out when the output is not a terminal, or NO_COLOR is set
+        #[arg(long)]
+        no_color: bool,
+    },
+    /// Check that a puzzle follows the rules and has exactly one solution
+    Validate {
+        /// Puzzle file, read from stdin if missing or -
+        path: Option<PathBuf>,
+    },
+    /// Generate a random puzzle with a unique solution
+    Generate {
+        /// easy, medium, hard or expert
+        #[arg(long, default_value = "medium")]
+        difficulty: Difficulty,
+        /// Pattern kept by the clues: none, rotational, mirror or diagonal
+        #[arg(long, default_value = "none")]
+        symmetry: Symmetry,
+        /// Seed for the random generator, taken from the clock if not given
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Grade a puzzle by the human techniques needed to solve it
+    Rate {
+        /// Puzzle file, read from stdin if missing or -
+        path: Option<PathBuf>,
+    },
+    /// Show the next cell that can be deduced, and why
+    Hint {
+        /// Puzzle file, read from stdin if missing or -
+        path: Option<PathBuf>,
+    },
+    /// Print a puzzle in another format
+    Convert {
+        /// Puzzle file, read from stdin if missing or -
+        path: Option<PathBuf>,
+        #[arg(long, value_enum, default_value = "line")]
+        to: Format,
+    },
+    /// Run an HTTP API with POST /solve, /validate, /rate and /generate endpoints
+    #[cfg(feature = "server")]
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        addr: String,
+    },
+}
+
+/// Output formats for convert
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Format {
+    /// 9 lines of comma separated cells, with 0 for empty cells
+    Csv,
+    /// A single line of 81 cells, with . for empty cells
+    Line,
+    /// A grid with lines between the boxes
+    Grid,
+    /// A grid drawn with box-drawing characters
+    Pretty,
+    /// A json array of 9 rows of 9 numbers, with 0 for empty cells
+    #[cfg(feature = "serde")]
+    Json,
+    /// A single line of 81 cells with 0 for empty cells, as in .sdm collections
+    Sdm,
+    /// An f-puzzles link to play the puzzle in the browser
+    #[cfg(feature = "serde")]
+    Fpuzzles,
+}
+
+/// How solve prints the solved grid
+#[derive(Clone, Copy, Debug)]
+struct Style {
+    /// Draw the grid with box-drawing characters
+    pretty: bool,
+    /// Color the clues and the solved cells differently
+    color: bool,
+}
+
+/// The files a single solve reads from and writes to
+#[derive(Clone, Copy, Debug)]
+struct SolvePaths<'a> {
+    /// The puzzle, read from stdin if None
+    puzzle: Option<&'a Path>,
+    /// An expected solution to compare against
+    expect: Option<&'a Path>,
+    /// The jigsaw regions replacing the boxes
+    regions: Option<&'a Path>,
+    /// Where to write the solution as csv, instead of printing it
+    output: Option<&'a Path>,
+}
+
+/// Output formats for solve
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum OutputFormat {
+    /// The solved grid, or a single line in quiet mode
+    Text,
+    /// A json object with the puzzle, the solution, validity and solve statistics
+    #[cfg(feature = "serde")]
+    Json,
 }
 
 fn main() {
     let args = Args::parse();
-    let csv_path = args.csv_path;
+    let quiet = args.quiet;
+    match args.command {
+        Command::Solve {
+            path,
+            batch,
+            multi,
+            jobs,
+            tag_difficulty,
+            expect,
+            regions,
+            format,
+            output,
+            pretty,
+            no_color,
+        } => match format {
+            #[cfg(feature = "serde")]
+            OutputFormat::Json => run_solve_json(path.as_deref(), quiet),
+            OutputFormat::Text => {
+                if let Some(dir) = batch {
+                    run_batch(&dir, tag_difficulty, quiet);
+                } else if multi {
+                    run_multi(path.as_deref(), jobs as usize, quiet);
+                } else {
+                    let color = !no_color
+                        && std::env::var_os("NO_COLOR").is_none()
+                        && std::io::stdout().is_terminal();
+                    let style = Style { pretty, color };
+                    let paths = SolvePaths {
+                        puzzle: path.as_deref(),
+                        expect: expect.as_deref(),
+                        regions: regions.as_deref(),
+                        output: output.as_deref(),
+                    };
+                    run_solve(paths, style, quiet);
+                }
+            }
+        },
+        Command::Validate { path } => {
+            let board = load(path.as_deref(), quiet);
+            let solutions = count_solutions(&board, Some(2));
+            if !quiet {
+                match solutions {
+                    0 => println!("Valid board, but it has no solution"),
+                    1 => println!("Valid puzzle with a unique solution"),
+                    _ => println!("Valid board, but it has more than one solution"),
+                }
+            }
+            match solutions {
+                0 => std::process::exit(EXIT_UNSOLVABLE),
+                1 => (),
+                _ => std::process::exit(1),
+            }
+        }
+        Command::Generate {
+            difficulty,
+            symmetry,
+            seed,
+        } => {
+            let seed = seed.unwrap_or_else(|| {
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+            });
+            let puzzle = generate_with_symmetry(difficulty, symmetry, &mut Rng::new(seed));
+            if quiet {
+                println!("{}", puzzle.to_line());
+            } else {
+                println!("{}", puzzle);
+            }
+        }
+        Command::Rate { path } => {
+            println!("{}", rate(&load(path.as_deref(), quiet)));
+        }
+        Command::Hint { path } => match load(path.as_deref(), quiet).hint() {
+            Some(hint) if quiet => println!("{}: {}", hint.index, hint.value),
+            Some(hint) => {
+                println!("{} is {} ({})", hint.index, hint.value, hint.technique);
+                for step in hint.steps {
+                    println!("{}", step.explanation);
+                }
+            }
+            None => {
+                if !quiet {
+                    println!("No logical deduction found");
+                }
+                std::process::exit(1);
+            }
+        },
+        Command::Convert { path, to } => {
+            let board = load(path.as_deref(), quiet);
+            match to {
+                Format::Csv => print!("{}", board.to_csv()),
+                Format::Line => println!("{}", board.to_line()),
+                Format::Grid => print!("{}", board),
+                Format::Pretty => print!("{}", board.pretty()),
+                #[cfg(feature = "serde")]
+                Format::Json => println!("{}", to_json_rows(&board)),
+                Format::Sdm => println!("{}", board.to_line_with('0')),
+                #[cfg(feature = "serde")]
+                Format::Fpuzzles => println!("{}", FPuzzle::new(board).to_url()),
+            }
+        }
+        #[cfg(feature = "server")]
+        Command::Serve { addr } => {
+            // bind before announcing the address, so a taken port only prints the error
+            let listener = match std::net::TcpListener::bind(&addr) {
+                Ok(listener) => listener,
+                Err(e) => {
+                    if !quiet {
+                        println!("Error: {}", e);
+                    }
+                    std::process::exit(1);
+                }
+            };
+            if !quiet {
+                println!("Listening on http://{}", addr);
+            }
+            sudoku_solver_by_roy::server::serve_on(listener);
+            std::process::exit(1);
+        }
+    }
+}
 
-    let mut board = match Board::from_csv(&csv_path) {
+/// Get the board as a json array of rows, with 0 for empty cells
+#[cfg(feature = "serde")]
+fn to_json_rows(board: &Board) -> String {
+    let rows: Vec<[u8; 9]> = board
+        .rows()
+        .map(|row| row.map(|(_, value)| value.unwrap_or(0)))
+        .collect();
+    serde_json::to_string(&rows).expect("rows of digits are always serializable")
+}
+
+/// Read the whole input, from stdin if there is no path or the path is -
+fn read_input(path: Option<&Path>) -> Result<String, SudokuError> {
+    let contents = match path {
+        Some(path) if path != Path::new("-") => std::fs::read_to_string(path),
+        _ => std::io::read_to_string(std::io::stdin()),
+    };
+    Ok(contents?)
+}
+
+/// Get the exit code for an error reading a board
+fn exit_code(error: &SudokuError) -> i32 {
+    match error {
+        SudokuError::RuleViolation { .. } => EXIT_INVALID,
+        _ => EXIT_UNREADABLE,
+    }
+}
+
+/// Read a board in any supported format, exiting with an error if it can't be read
+/// With no path, or a path of -, the board is read from stdin
+fn load(path: Option<&Path>, quiet: bool) -> Board {
+    let board = read_input(path).and_then(|contents| contents.parse());
+    match board {
         Ok(board) => board,
         Err(e) => {
-            println!("Error: {}", e);
-            return;
+            if !quiet {
+                println!("Error: {}", e);
+            }
+            std::process::exit(exit_code(&e));
+        }
+    }
+}
+
+/// Read a jigsaw puzzle and its region map, exiting with an error message on failure
+fn load_jigsaw(path: Option<&Path>, regions: &Path, quiet: bool) -> (Board, Regions) {
+    let jigsaw = read_input(path).and_then(|puzzle| {
+        let regions = read_input(Some(regions))?;
+        Jigsaw::parse(&puzzle, &regions)
+    });
+    match jigsaw {
+        Ok(jigsaw) => (jigsaw.board(), *jigsaw.regions()),
+        Err(e) => {
+            if !quiet {
+                println!("Error: {}", e);
+            }
+            std::process::exit(exit_code(&e));
+        }
+    }
+}
+
+/// Solve a single puzzle, optionally checking the solution against an expected one
+fn run_solve(paths: SolvePaths, style: Style, quiet: bool) {
+    let (mut board, regions) = match paths.regions {
+        Some(regions) => {
+            let (board, regions) = load_jigsaw(paths.puzzle, regions, quiet);
+            (board, Some(regions))
+        }
+        None => (load(paths.puzzle, quiet), None),
+    };
+    let solution = match &regions {
+        Some(regions) => solve_with_constraints(&mut board, &[regions]),
+        None => solve(&mut board),
+    };
+    let answer = match solution {
+        Some(answer) => answer,
+        None => {
+            if !quiet {
+                println!("No solution found");
+            }
+            std::process::exit(EXIT_UNSOLVABLE);
+        }
+    };
+    // compared before the solution is written, so a mismatch in quiet mode prints nothing
+    let comparison = paths.expect.map(|expect_path| {
+        let expected = load(Some(expect_path), quiet);
+        (expect_path, answer.diff(&expected))
+    });
+    let mismatch = comparison
+        .as_ref()
+        .is_some_and(|(_, differences)| !differences.is_empty());
+    if quiet && mismatch {
+        std::process::exit(1);
+    }
+    if let Some(output) = paths.output {
+        if let Err(e) = answer.write_csv(output) {
+            if !quiet {
+                println!("Error: {}", e);
+            }
+            std::process::exit(1);
+        }
+    } else if quiet {
+        println!("{}", answer.to_line());
+    } else {
+        match (style.pretty, style.color) {
+            (false, false) => println!("{}", answer),
+            (false, true) => println!("{}", answer.colored()),
+            (true, false) => print!("{}", answer.pretty()),
+            (true, true) => print!("{}", answer.pretty_colored()),
+        }
+    }
+
+    if let Some((expect_path, differences)) = comparison {
+        if mismatch {
+            println!("Solution does not match {}", expect_path.display());
+            for (index, found, wanted) in differences {
+                println!(
+                    "{}: found {}, expected {}",
+                    index,
+                    found.map_or(".".to_string(), |x| x.to_string()),
+                    wanted.map_or(".".to_string(), |x| x.to_string())
+                );
+            }
+            std::process::exit(1);
+        }
+        if !quiet {
+            println!("Solution matches {}", expect_path.display());
+        }
+    }
+}
+
+/// The json printed by solve --format json
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+struct SolveReport {
+    puzzle: String,
+    solution: Option<String>,
+    valid: bool,
+    stats: StatsReport,
+}
+
+/// The solve statistics printed as json
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+struct StatsReport {
+    nodes: usize,
+    backtracks: usize,
+    propagation_fills: usize,
+    elapsed_us: u128,
+}
+
+/// Solve a single puzzle, printing the puzzle, the solution and solve statistics as json
+/// The puzzle is valid if it has exactly one solution.
+/// The solution is null if there is none, in which case the exit code is nonzero
+/// The statistics cover the search for a second solution too, which checks validity
+#[cfg(feature = "serde")]
+fn run_solve_json(path: Option<&Path>, quiet: bool) {
+    let board = load(path, quiet);
+    // valid in the same sense as the validate command
+    let (count, solution, stats) = solution_signature_with_stats(&board);
+    let report = SolveReport {
+        puzzle: board.to_line(),
+        solution: solution.as_ref().map(Board::to_line),
+        valid: count == 1,
+        stats: StatsReport {
+            nodes: stats.nodes,
+            backtracks: stats.backtracks,
+            propagation_fills: stats.propagation_fills,
+            elapsed_us: stats.elapsed.as_micros(),
+        },
+    };
+    println!(
+        "{}",
+        serde_json::to_string(&report).expect("reports are always serializable")
+    );
+    if solution.is_none() {
+        std::process::exit(EXIT_UNSOLVABLE);
+    }
+}
+
+/// Solve every puzzle of a one puzzle per line collection, printing a line for each
+/// In quiet mode failed puzzles are printed as empty lines, keeping the output aligned.
+/// The exit code is that of the earliest stage any puzzle failed at, as for batch
+fn run_multi(path: Option<&Path>, jobs: usize, quiet: bool) {
+    let contents = match read_input(path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            if !quiet {
+                println!("Error: {}", e);
+            }
+            std::process::exit(EXIT_UNREADABLE);
+        }
+    };
+    let puzzles: Vec<Result<Board, SudokuError>> = puzzles_from_lines(&contents).collect();
+    // threads take the next unsolved puzzle as they finish one, so a few hard puzzles
+    // don't hold up a single thread, and results are put back in input order by index
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<Option<Result<Board, (i32, String)>>> = vec![None; puzzles.len()];
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs.min(puzzles.len()))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut solved = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(puzzle) = puzzles.get(i) else {
+                            return solved;
+                        };
+                        solved.push((i, solve_line(puzzle)));
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            for (i, result) in worker.join().expect("solver threads don't panic") {
+                results[i] = Some(result);
+            }
+        }
+    });
+    // the exit codes are numbered in the order of the stages, so the lowest is the worst
+    let mut failure: Option<i32> = None;
+    for result in results.into_iter().flatten() {
+        match result {
+            Ok(answer) => println!("{}", answer.to_line()),
+            Err((code, message)) => {
+                if quiet {
+                    println!();
+                } else {
+                    println!("{}", message);
+                }
+                failure = Some(failure.map_or(code, |worst| worst.min(code)));
+            }
+        }
+    }
+    if let Some(code) = failure {
+        std::process::exit(code);
+    }
+}
+
+/// Solve one puzzle of a multi puzzle file,
+/// or get the exit code and the message to print in its place
+fn solve_line(puzzle: &Result<Board, SudokuError>) -> Result<Board, (i32, String)> {
+    match puzzle {
+        Ok(board) => board
+            .solved()
+            .ok_or((EXIT_UNSOLVABLE, "No solution found".to_string())),
+        Err(e) => Err((exit_code(e), format!("Error: {}", e))),
+    }
+}
+
+/// Solve or tag every csv file in a directory, in file name order, printing a line for each
+/// Files that fail are reported on stderr, or left out in quiet mode, and the exit code is that
+/// of the earliest stage any file failed at: unreadable, then invalid, then unsolvable
+fn run_batch(dir: &Path, tag_difficulty: bool, quiet: bool) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            if !quiet {
+                eprintln!("Error: {}", e);
+            }
+            std::process::exit(EXIT_UNREADABLE);
         }
     };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "csv"))
+        .collect();
+    paths.sort();
 
-    match solve(&mut board) {
-        Some(answer) => println!("{}", answer),
-        None => println!("No solution found"),
+    // the exit codes are numbered in the order of the stages, so the lowest is the worst
+    let mut failure: Option<i32> = None;
+    for path in paths {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let mut board = match Board::from_csv(&path) {
+            Ok(board) => board,
+            Err(e) => {
+                if !quiet {
+                    eprintln!("{}: Error: {}", name, e);
+                }
+                failure = Some(failure.map_or(exit_code(&e), |worst| worst.min(exit_code(&e))));
+                continue;
+            }
+        };
+        if tag_difficulty {
+            println!("{}: {}", name, board.estimated_hardness());
+            continue;
+        }
+        match solve(&mut board) {
+            Some(answer) => println!("{}: {}", name, answer.to_line()),
+            None => {
+                if !quiet {
+                    eprintln!("{}: No solution found", name);
+                }
+                failure = Some(failure.map_or(EXIT_UNSOLVABLE, |worst| worst.min(EXIT_UNSOLVABLE)));
+            }
+        }
+    }
+    if let Some(code) = failure {
+        std::process::exit(code);
     }
 }
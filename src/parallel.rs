@@ -0,0 +1,87 @@
+//! This module contains solvers that split the search across threads using rayon
+//! The board is split on the candidates of the empty cell with the fewest of them,
+//! and each branch is searched on its own thread
+//!
+//! Enabled by either the `parallel` or the `rayon` feature
+
+use crate::board::{count_solutions, solve, Board};
+use rayon::prelude::*;
+
+/// Count the solutions of the board in parallel, stopping once cap solutions are found
+/// Always agrees with the sequential count_solutions with Some(cap) as the limit
+pub fn count_solutions_parallel(board: &Board, cap: usize) -> usize {
+    if cap == 0 || !board.is_valid_board() {
+        return 0;
+    }
+    let branches = board.branches();
+    if branches.is_empty() {
+        return count_solutions(board, Some(cap));
+    }
+    let total: usize = branches
+        .par_iter()
+        .map(|branch| count_solutions(branch, Some(cap)))
+        .sum();
+    total.min(cap)
+}
+
+/// Solve the board in parallel, returning the first solution any thread finds
+/// For puzzles with several solutions this may not be the one solve returns
+pub fn solve_parallel(board: &Board) -> Option<Board> {
+    if !board.is_valid_board() {
+        return None;
+    }
+    let branches = board.branches();
+    if branches.is_empty() {
+        return board.solved();
+    }
+    branches
+        .into_par_iter()
+        .find_map_any(|mut branch| solve(&mut branch))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_solve_parallel() {
+        for path in [
+            "tests/test_board_pass.csv",
+            "tests/test_board_pass_solution.csv",
+            "tests/hard_puzzle.csv",
+        ] {
+            let board = Board::from_csv(path).unwrap();
+            assert_eq!(solve_parallel(&board), board.solved());
+        }
+
+        let mut invalid = Board::from_csv("tests/test_board_pass.csv").unwrap();
+        invalid.update_cell(&crate::board::Index(0, 0), 7);
+        assert_eq!(solve_parallel(&invalid), None);
+    }
+
+    #[test]
+    fn test_count_solutions_parallel() {
+        for path in [
+            "tests/test_board_pass.csv",
+            "tests/test_board_pass_solution.csv",
+            "tests/hard_puzzle.csv",
+        ] {
+            let board = Board::from_csv(path).unwrap();
+            assert_eq!(
+                count_solutions_parallel(&board, 5),
+                count_solutions(&board, Some(5))
+            );
+        }
+
+        // a board with many solutions still stops at the cap
+        let mut grid = [[0; 9]; 9];
+        grid[0] = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+        let sparse = Board::from_digits(&grid);
+        assert_eq!(count_solutions_parallel(&sparse, 20), 20);
+        assert_eq!(
+            count_solutions_parallel(&sparse, 20),
+            count_solutions(&sparse, Some(20))
+        );
+    }
+}
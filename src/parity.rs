@@ -0,0 +1,164 @@
+//! This module contains odd/even sudoku, where some empty cells are marked as only taking
+//! odd or only taking even digits
+//!
+//! A marked puzzle is read like a single line or grid board, with `o` for an empty odd cell
+//! and `e` for an empty even cell, eg `o738o4216oee2o95eo...`
+
+use crate::board::{Board, Index, Parity};
+use crate::constraint::Constraint;
+use crate::error::{Dimension, SudokuError};
+
+/// The parity each cell of a board is restricted to, if any
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub struct Parities([[Option<Parity>; 9]; 9]);
+
+impl Parities {
+    /// Restrict the marked cells to their parity
+    /// A cell marked twice keeps its last parity
+    pub fn new(marks: &[(Index, Parity)]) -> Self {
+        let mut parities = [[None; 9]; 9];
+        for (index, parity) in marks {
+            parities[index.0][index.1] = Some(*parity);
+        }
+        Parities(parities)
+    }
+    /// Get the parity the cell is restricted to, if it is marked
+    pub fn get(&self, index: &Index) -> Option<Parity> {
+        self.0[index.0][index.1]
+    }
+    /// Get the marked cells and their parities, row by row
+    pub fn marks(&self) -> Vec<(Index, Parity)> {
+        (0..81)
+            .map(|i| Index(i / 9, i % 9))
+            .filter_map(|index| self.get(&index).map(|parity| (index, parity)))
+            .collect()
+    }
+}
+
+impl Constraint for Parities {
+    /// Check that the value has the parity the cell is marked with
+    fn allows(&self, _board: &Board, index: &Index, value: u8) -> bool {
+        self.get(index).is_none_or(|parity| parity.allows(value))
+    }
+}
+
+/// Read a puzzle of 81 cells, ignoring whitespace, along with the parity marks of its empty cells
+/// Digits are clues, `.` and `0` are empty cells, and `o` and `e` are empty odd and even cells
+/// # Example
+/// ```
+/// use sudoku_solver_by_roy::board::solve_with_parity;
+/// use sudoku_solver_by_roy::parity::parse_with_parity;
+/// let (mut board, parities) = parse_with_parity(
+///     "o738o4216oee2o95eo2856o3e97...3...7475....3.1..4.2.....9756.......7..1..42..3..6.",
+/// )
+/// .unwrap();
+/// let solution = solve_with_parity(&mut board, &parities.marks()).unwrap();
+/// assert_eq!(solution.get(1, 1), Ok(Some(4)));
+/// ```
+/// # Errors
+/// 1. SudokuError::ParseError if a symbol is not a digit, `.`, `o` or `e`
+/// 2. SudokuError::DimensionError if there aren't 81 cells
+/// 3. SudokuError::RuleViolation if the clues break the sudoku rules
+pub fn parse_with_parity(s: &str) -> Result<(Board, Parities), SudokuError> {
+    let symbols: Vec<char> = s.chars().filter(|c| !c.is_whitespace()).collect();
+    if symbols.len() != 81 {
+        return Err(SudokuError::DimensionError {
+            row: None,
+            found: symbols.len(),
+            expected: 81,
+            unit: Dimension::Cells,
+        });
+    }
+    let mut cells = [[0; 9]; 9];
+    let mut marks = Vec::new();
+    for (i, symbol) in symbols.into_iter().enumerate() {
+        let (row, column) = (i / 9, i % 9);
+        match symbol.to_ascii_lowercase() {
+            'o' => marks.push((Index(row, column), Parity::Odd)),
+            'e' => marks.push((Index(row, column), Parity::Even)),
+            '.' => {}
+            digit @ '0'..='9' => cells[row][column] = digit as u8 - b'0',
+            _ => {
+                return Err(SudokuError::ParseError {
+                    row,
+                    column,
+                    found: symbol.to_string(),
+                })
+            }
+        }
+    }
+    Ok((Board::try_new(&cells)?, Parities::new(&marks)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{count_solutions, solve_with_constraints};
+    use crate::candidates::Candidates;
+    use crate::fixtures::{assert_only_easy_solution, easy_board, easy_solution_without_rows};
+
+    const PUZZLE: &str =
+        "o738o4216oee2o95eo2856o3e97...3...7475....3.1..4.2.....9756.......7..1..42..3..6.";
+
+    #[test]
+    fn test_parse_with_parity() {
+        let (board, parities) = parse_with_parity(PUZZLE).unwrap();
+        assert_eq!(board, easy_board());
+        assert_eq!(parities.get(&Index(0, 0)), Some(Parity::Odd));
+        assert_eq!(parities.get(&Index(1, 1)), Some(Parity::Even));
+        assert_eq!(parities.get(&Index(0, 1)), None);
+        assert_eq!(parities.marks().len(), 10);
+
+        assert!(matches!(
+            parse_with_parity(&PUZZLE.replace('o', "x")),
+            Err(SudokuError::ParseError {
+                row: 0,
+                column: 0,
+                ..
+            })
+        ));
+        assert!(matches!(
+            parse_with_parity(&PUZZLE[1..]),
+            Err(SudokuError::DimensionError { found: 80, .. })
+        ));
+    }
+
+    #[test]
+    fn test_prune() {
+        let (board, parities) = parse_with_parity(PUZZLE).unwrap();
+        let mut candidates = Candidates::new(&board);
+        assert!(candidates
+            .get(&Index(1, 1))
+            .iter()
+            .any(|value| value % 2 == 1));
+        assert!(parities.prune(&board, &mut candidates));
+        assert!(candidates
+            .get(&Index(1, 1))
+            .iter()
+            .all(|value| value % 2 == 0));
+        assert!(candidates
+            .get(&Index(1, 0))
+            .iter()
+            .all(|value| value % 2 == 1));
+        // pruning again has nothing left to remove
+        assert!(!parities.prune(&board, &mut candidates));
+    }
+
+    #[test]
+    fn test_solve() {
+        let (_, parities) = parse_with_parity(PUZZLE).unwrap();
+        // clearing the first two rows leaves four classic solutions,
+        // and only the marks in those rows tell them apart
+        let puzzle = easy_solution_without_rows(&[0, 1]);
+        assert_eq!(count_solutions(&puzzle, None), 4);
+        assert_only_easy_solution(&puzzle, &parities);
+    }
+
+    #[test]
+    fn test_broken_mark() {
+        // the clue in r1c2 is 7, which breaks an even mark
+        let broken = Parities::new(&[(Index(0, 1), Parity::Even)]);
+        assert!(!broken.is_satisfied(&easy_board()));
+        assert_eq!(solve_with_constraints(&mut easy_board(), &[&broken]), None);
+    }
+}
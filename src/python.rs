@@ -0,0 +1,121 @@
+//! This module exposes the solver as a Python module with PyO3, so puzzle datasets can be
+//! solved and rated from notebooks
+//! Puzzles are passed in as strings in any format Board parses, and come back as one line strings
+//!
+//! Enabled by the `python` feature. Build the module with `maturin build --features python`
+
+use crate::board::Board;
+use crate::generator::{generate_from_seed, Difficulty};
+use crate::rating::rate as rate_board;
+use pyo3::exceptions::{PyIndexError, PyValueError};
+use pyo3::prelude::*;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Parse a puzzle, turning read errors into a ValueError
+fn parse(puzzle: &str) -> PyResult<Board> {
+    puzzle
+        .parse()
+        .map_err(|e: crate::error::SudokuError| PyValueError::new_err(e.to_string()))
+}
+
+/// A sudoku board, as seen from Python
+#[pyclass(name = "Board", frozen)]
+pub struct PyBoard(Board);
+
+#[pymethods]
+impl PyBoard {
+    #[new]
+    fn new(puzzle: &str) -> PyResult<Self> {
+        parse(puzzle).map(PyBoard)
+    }
+
+    /// Get the value at a row and column counted from 0, or None if the cell is empty
+    fn get(&self, row: usize, column: usize) -> PyResult<Option<u8>> {
+        self.0
+            .get(row, column)
+            .map_err(|e| PyIndexError::new_err(e.to_string()))
+    }
+
+    /// Get the board as a line of 81 cells, with . for empty cells
+    fn to_line(&self) -> String {
+        self.0.to_line()
+    }
+
+    /// Get the solution of the board, or None if it has none
+    fn solved(&self) -> Option<PyBoard> {
+        self.0.solved().map(PyBoard)
+    }
+
+    /// Rate the board as easy, medium, hard or expert
+    fn rate(&self) -> String {
+        rate_board(&self.0).to_string()
+    }
+
+    fn __str__(&self) -> String {
+        self.0.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Board('{}')", self.0.to_line())
+    }
+}
+
+/// Solve the puzzle, returning the solution as a line of 81 digits, or None if it has none
+#[pyfunction]
+fn solve(puzzle: &str) -> PyResult<Option<String>> {
+    Ok(parse(puzzle)?.solved().map(|solution| solution.to_line()))
+}
+
+/// Rate the puzzle as easy, medium, hard or expert
+#[pyfunction]
+fn rate(puzzle: &str) -> PyResult<String> {
+    Ok(rate_board(&parse(puzzle)?).to_string())
+}
+
+/// Generate a puzzle of the difficulty as a line of 81 cells
+/// The same seed always gives the same puzzle; without one the clock is used
+#[pyfunction]
+#[pyo3(signature = (difficulty = "medium", seed = None))]
+fn generate(difficulty: &str, seed: Option<u64>) -> PyResult<String> {
+    let difficulty: Difficulty = difficulty.parse().map_err(PyValueError::new_err)?;
+    let seed = seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+    });
+    Ok(generate_from_seed(difficulty, seed).to_line())
+}
+
+#[pymodule]
+fn sudoku_solver_by_roy(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyBoard>()?;
+    m.add_function(wrap_pyfunction!(solve, m)?)?;
+    m.add_function(wrap_pyfunction!(rate, m)?)?;
+    m.add_function(wrap_pyfunction!(generate, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::EASY;
+
+    #[test]
+    fn test_solve_and_rate() {
+        assert!(solve(EASY).unwrap().unwrap().starts_with("973854216"));
+        assert_eq!(rate(EASY).unwrap(), "easy");
+        let board = PyBoard::new(EASY).unwrap();
+        assert_eq!(board.get(0, 1).unwrap(), Some(7));
+        assert_eq!(
+            board.solved().unwrap().to_line(),
+            solve(EASY).unwrap().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_generate_is_seeded() {
+        let puzzle = generate("easy", Some(7)).unwrap();
+        assert_eq!(puzzle, generate("easy", Some(7)).unwrap());
+        assert_eq!(solve(&puzzle).unwrap().map(|line| line.len()), Some(81));
+    }
+}
@@ -0,0 +1,65 @@
+//! This module rates puzzles by the human solving techniques they need
+//! The puzzle is solved by the logical solver, which tries techniques from simplest
+//! to hardest, and is rated by the hardest technique it needed.
+//! Puzzles that can't be finished with these techniques need guessing, and rate as expert
+
+use crate::board::Board;
+use crate::generator::Difficulty;
+use crate::logic::{solve_logically, Technique};
+
+/// Get the difficulty of puzzles needing the technique
+fn technique_difficulty(technique: Technique) -> Difficulty {
+    match technique {
+        Technique::HiddenSingle => Difficulty::Easy,
+        Technique::NakedSingle => Difficulty::Medium,
+        Technique::LockedCandidates | Technique::NakedPair | Technique::HiddenPair => {
+            Difficulty::Hard
+        }
+        Technique::XWing => Difficulty::Expert,
+    }
+}
+
+/// Grade a puzzle by the hardest human technique needed to solve it
+/// Puzzles solved with hidden singles alone are easy, needing naked singles makes them medium,
+/// and needing locked candidates, naked pairs or hidden pairs makes them hard.
+/// Anything else, including boards without a unique solution, rates as expert
+pub fn rate(board: &Board) -> Difficulty {
+    let (solved, steps) = solve_logically(board);
+    if !solved.is_complete() {
+        return Difficulty::Expert;
+    }
+    steps
+        .iter()
+        .map(|step| step.technique)
+        .max()
+        .map_or(Difficulty::Easy, technique_difficulty)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate() {
+        let easy = Board::from_csv("tests/test_board_pass.csv").unwrap();
+        assert_eq!(rate(&easy), Difficulty::Easy);
+        let solved = Board::from_csv("tests/test_board_pass_solution.csv").unwrap();
+        assert_eq!(rate(&solved), Difficulty::Easy);
+        let medium: Board =
+            ".5...7.9.....2...62...9.3....7.....5..136.....9...1.6.......53.7..4.2......836..1"
+                .parse()
+                .unwrap();
+        assert_eq!(rate(&medium), Difficulty::Medium);
+        let hard: Board =
+            ".....82......7...5.1....8...7......66891..3....3..9...56..8.1....1.4......2..19.."
+                .parse()
+                .unwrap();
+        assert_eq!(rate(&hard), Difficulty::Hard);
+        // Arto Inkala's "world's hardest sudoku" needs guessing
+        let expert: Board =
+            "800000000003600000070090200050007000000045700000100030001000068008500010090000400"
+                .parse()
+                .unwrap();
+        assert_eq!(rate(&expert), Difficulty::Expert);
+    }
+}
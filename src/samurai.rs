@@ -0,0 +1,415 @@
+//! This module contains samurai sudoku, five 9x9 grids overlapping in a 21x21 layout
+//! A centre grid shares each of its corner boxes with a corner box of one of four outer grids,
+//! and every grid must be solved as an ordinary sudoku
+//!
+//! Puzzles are read as 21 lines of the layout, with `.` or `0` for empty cells
+//! Whitespace is ignored, so the gaps between the outer grids can be left out or filled with spaces
+//!
+//! The outer grids only touch the centre grid, so once the centre is solved
+//! each outer grid is solved on its own with the centre's corner box filled in
+
+use crate::board::{solve, solve_all, Board, BoardError, Index, Search, Unit};
+use crate::candidates::Candidates;
+use crate::constraint::Constraint;
+use crate::error::{Dimension, SudokuError};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Number of rows and columns of the layout
+const SIZE: usize = 21;
+
+/// The grid in the middle of the layout, which overlaps the other four
+const CENTRE: usize = 2;
+
+/// The top left cell of each grid in the layout, in the order
+/// top left, top right, centre, bottom left and bottom right
+const OFFSETS: [(usize, usize); 5] = [(0, 0), (0, 12), (6, 6), (12, 0), (12, 12)];
+
+/// A samurai board of five grids, kept in agreement on the boxes they share
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Samurai {
+    grids: [Board; 5],
+}
+
+/// Get each grid covering the cell at a row and column of the layout,
+/// with the index of the cell in that grid
+fn grids_at(row: usize, column: usize) -> impl Iterator<Item = (usize, Index)> {
+    OFFSETS
+        .iter()
+        .enumerate()
+        .filter(move |(_, &(top, left))| {
+            (top..top + 9).contains(&row) && (left..left + 9).contains(&column)
+        })
+        .map(move |(grid, &(top, left))| (grid, Index(row - top, column - left)))
+}
+
+/// Get the columns of the layout that hold cells in a row
+fn columns(row: usize) -> Vec<usize> {
+    (0..SIZE)
+        .filter(|&column| grids_at(row, column).next().is_some())
+        .collect()
+}
+
+impl Samurai {
+    /// Make a samurai board from its five grids, in the order
+    /// top left, top right, centre, bottom left and bottom right
+    /// # Errors
+    /// Returns SudokuError::RuleViolation at the first cell of the layout where two grids disagree
+    pub fn try_new(grids: [Board; 5]) -> Result<Samurai, SudokuError> {
+        for row in 0..SIZE {
+            for column in 0..SIZE {
+                let mut values =
+                    grids_at(row, column).map(|(grid, index)| grids[grid].get_cell(&index));
+                if let Some(first) = values.next() {
+                    if values.any(|value| value != first) {
+                        return Err(SudokuError::RuleViolation { row, column });
+                    }
+                }
+            }
+        }
+        Ok(Samurai { grids })
+    }
+    /// Get the five grids, in the order top left, top right, centre, bottom left and bottom right
+    pub fn grids(&self) -> &[Board; 5] {
+        &self.grids
+    }
+    /// Get the digit at a 0-based row and column of the layout
+    /// # Errors
+    /// Returns BoardError::OutOfBounds if the cell is outside the layout or in a gap between grids
+    pub fn get(&self, row: usize, column: usize) -> Result<Option<u8>, BoardError> {
+        match grids_at(row, column).next() {
+            Some((grid, index)) => Ok(self.grids[grid].get_cell(&index)),
+            None => Err(BoardError::OutOfBounds { row, column }),
+        }
+    }
+    /// Check that every grid is complete and follows the sudoku rules
+    pub fn is_solved(&self) -> bool {
+        self.grids.iter().all(Board::is_complete)
+    }
+    /// Solve the board, returning None if it has no solution
+    pub fn solved(&self) -> Option<Samurai> {
+        let mut solution = None;
+        self.explore(1, &mut |solved| {
+            solution = Some(solved.clone());
+            true
+        });
+        solution
+    }
+    /// Count the solutions of the board, stopping once limit are found
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        let mut count = 0;
+        if limit > 0 {
+            self.explore(limit, &mut |_| {
+                count += 1;
+                count >= limit
+            });
+        }
+        count
+    }
+    /// Visit the solutions until visit returns true, solving the centre grid first
+    /// and then the outer grids around each of its solutions
+    /// At most limit solutions of each outer grid are tried per solution of the centre
+    fn explore(&self, limit: usize, visit: &mut dyn FnMut(&Samurai) -> bool) {
+        let (top, left) = OFFSETS[CENTRE];
+        let mut search = Search::new();
+        for grid in [0, 1, 3, 4] {
+            search = search.with_constraint(Overlap {
+                grid,
+                outer: &self.grids[grid],
+                candidates: Candidates::new(&self.grids[grid]),
+            });
+        }
+        search.explore(&mut self.grids[CENTRE].clone(), &mut |centre| {
+            let mut grids = self.grids.clone();
+            for (index, value) in centre.cells() {
+                for (grid, shared) in grids_at(index.0 + top, index.1 + left) {
+                    if grid != CENTRE && grids[grid].get_cell(&shared).is_none() {
+                        grids[grid].update_cell(&shared, value.unwrap_or(0));
+                    }
+                }
+            }
+            let mut outer = Vec::with_capacity(4);
+            for grid in [0, 1, 3, 4] {
+                let solutions = solve_all(&grids[grid], limit);
+                if solutions.is_empty() {
+                    return false;
+                }
+                outer.push(solutions);
+            }
+            for top_left in &outer[0] {
+                for top_right in &outer[1] {
+                    for bottom_left in &outer[2] {
+                        for bottom_right in &outer[3] {
+                            let solved = Samurai {
+                                grids: [
+                                    top_left.clone(),
+                                    top_right.clone(),
+                                    centre.clone(),
+                                    bottom_left.clone(),
+                                    bottom_right.clone(),
+                                ],
+                            };
+                            if visit(&solved) {
+                                return true;
+                            }
+                        }
+                    }
+                }
+            }
+            false
+        });
+    }
+}
+
+/// Keeps the corner box of the centre grid shared with an outer grid
+/// fillable in the outer grid while the centre is searched
+struct Overlap<'a> {
+    grid: usize,
+    outer: &'a Board,
+    candidates: Candidates,
+}
+
+impl Overlap<'_> {
+    /// Get the index in the outer grid of a cell of the centre grid, if the grids share it
+    fn shared(&self, index: &Index) -> Option<Index> {
+        let (top, left) = OFFSETS[CENTRE];
+        grids_at(index.0 + top, index.1 + left)
+            .find(|&(grid, _)| grid == self.grid)
+            .map(|(_, shared)| shared)
+    }
+}
+
+impl Constraint for Overlap<'_> {
+    /// Check that the outer grid allows the value in the shared cell,
+    /// and once the value completes the shared box, that the outer grid can still be solved
+    fn allows(&self, board: &Board, index: &Index, value: u8) -> bool {
+        let Some(shared) = self.shared(index) else {
+            return true;
+        };
+        let allowed = match self.outer.get_cell(&shared) {
+            Some(clue) => clue == value,
+            None => self.candidates.contains(&shared, value),
+        };
+        if !allowed {
+            return false;
+        }
+        let subgrid = Unit::Subgrid(index.0 / 3 * 3 + index.1 / 3);
+        let mut outer = self.outer.clone();
+        for cell in subgrid.cells() {
+            let digit = if cell == *index {
+                value
+            } else {
+                match board.get_cell(&cell) {
+                    Some(digit) => digit,
+                    // the box isn't complete yet
+                    None => return true,
+                }
+            };
+            if let Some(shared) = self.shared(&cell) {
+                outer.update_cell(&shared, digit);
+            }
+        }
+        solve(&mut outer).is_some()
+    }
+}
+
+impl FromStr for Samurai {
+    type Err = SudokuError;
+
+    /// Read the 21 lines of the layout, ignoring whitespace and blank lines
+    /// Rows crossing two grids hold 18 or 21 cells, and the middle three rows hold 9
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines: Vec<Vec<char>> = s
+            .lines()
+            .map(|line| line.chars().filter(|c| !c.is_whitespace()).collect())
+            .filter(|line: &Vec<char>| !line.is_empty())
+            .collect();
+        if lines.len() != SIZE {
+            return Err(SudokuError::DimensionError {
+                row: None,
+                found: lines.len(),
+                expected: SIZE,
+                unit: Dimension::Rows,
+            });
+        }
+        let mut cells = [[[0; 9]; 9]; 5];
+        for (row, line) in lines.iter().enumerate() {
+            let columns = columns(row);
+            if line.len() != columns.len() {
+                return Err(SudokuError::DimensionError {
+                    row: Some(row),
+                    found: line.len(),
+                    expected: columns.len(),
+                    unit: Dimension::Cells,
+                });
+            }
+            for (&symbol, column) in line.iter().zip(columns) {
+                let value = match symbol {
+                    '.' => 0,
+                    '0'..='9' => symbol as u8 - b'0',
+                    _ => {
+                        return Err(SudokuError::ParseError {
+                            row,
+                            column,
+                            found: symbol.to_string(),
+                        })
+                    }
+                };
+                for (grid, index) in grids_at(row, column) {
+                    cells[grid][index.0][index.1] = value;
+                }
+            }
+        }
+        let mut grids = Vec::with_capacity(5);
+        for (grid, cells) in cells.iter().enumerate() {
+            let (top, left) = OFFSETS[grid];
+            grids.push(Board::try_new(cells).map_err(|error| match error {
+                SudokuError::RuleViolation { row, column } => SudokuError::RuleViolation {
+                    row: row + top,
+                    column: column + left,
+                },
+                error => error,
+            })?);
+        }
+        let grids = grids.try_into().expect("there is a board for each grid");
+        Ok(Samurai { grids })
+    }
+}
+
+impl Display for Samurai {
+    /// Print the 21 lines of the layout with `.` for empty cells and spaces in the gaps
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in 0..SIZE {
+            let line: String = (0..SIZE)
+                .map(|column| match self.get(row, column) {
+                    Ok(Some(value)) => char::from(b'0' + value),
+                    Ok(None) => '.',
+                    Err(_) => ' ',
+                })
+                .collect();
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn puzzle() -> Samurai {
+        std::fs::read_to_string("tests/test_samurai.txt")
+            .unwrap()
+            .parse()
+            .unwrap()
+    }
+
+    fn solution() -> Samurai {
+        std::fs::read_to_string("tests/test_samurai_solution.txt")
+            .unwrap()
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let puzzle = puzzle();
+        assert_eq!(puzzle.get(0, 1), Ok(Some(5)));
+        assert_eq!(puzzle.get(0, 0), Ok(None));
+        assert_eq!(
+            puzzle.get(0, 10),
+            Err(BoardError::OutOfBounds { row: 0, column: 10 })
+        );
+        assert_eq!(
+            puzzle.get(21, 0),
+            Err(BoardError::OutOfBounds { row: 21, column: 0 })
+        );
+        // the centre's top left box is the bottom right box of the top left grid
+        assert_eq!(puzzle.grids()[0].get(7, 8), Ok(Some(6)));
+        assert_eq!(puzzle.grids()[CENTRE].get(1, 2), Ok(Some(6)));
+        let text = std::fs::read_to_string("tests/test_samurai.txt").unwrap();
+        assert_eq!(puzzle.to_string(), text);
+        let solution = solution();
+        assert!(solution.is_solved());
+        assert!(!puzzle.is_solved());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let text = std::fs::read_to_string("tests/test_samurai.txt").unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            lines[1..].join("\n").parse::<Samurai>().unwrap_err(),
+            SudokuError::DimensionError {
+                row: None,
+                found: 20,
+                expected: 21,
+                unit: Dimension::Rows
+            }
+        );
+        let mut short = lines.clone();
+        short[9] = "....74..";
+        assert_eq!(
+            short.join("\n").parse::<Samurai>().unwrap_err(),
+            SudokuError::DimensionError {
+                row: Some(9),
+                found: 8,
+                expected: 9,
+                unit: Dimension::Cells
+            }
+        );
+        let mut letter = lines.clone();
+        letter[9] = "      x...74...";
+        assert!(matches!(
+            letter.join("\n").parse::<Samurai>().unwrap_err(),
+            SudokuError::ParseError {
+                row: 9,
+                column: 6,
+                ..
+            }
+        ));
+        // a second 5 in the top right grid's first row, reported in layout coordinates
+        let mut repeated = lines.clone();
+        repeated[0] = ".5......1   .3..5..85";
+        assert_eq!(
+            repeated.join("\n").parse::<Samurai>().unwrap_err(),
+            SudokuError::RuleViolation { row: 0, column: 16 }
+        );
+    }
+
+    #[test]
+    fn test_try_new() {
+        let solution = solution();
+        assert_eq!(
+            Samurai::try_new(solution.grids().clone()),
+            Ok(solution.clone())
+        );
+        // the top left grid disagrees with the centre on a shared cell
+        let mut grids = solution.grids().clone();
+        grids[0].update_cell(&Index(8, 8), 0);
+        assert_eq!(
+            Samurai::try_new(grids),
+            Err(SudokuError::RuleViolation { row: 8, column: 8 })
+        );
+    }
+
+    #[test]
+    fn test_solve() {
+        let puzzle = puzzle();
+        assert_eq!(puzzle.solved(), Some(solution()));
+        assert_eq!(puzzle.count_solutions(2), 1);
+
+        // each grid alone has many solutions, so the overlaps are needed for a unique answer
+        assert!(puzzle
+            .grids()
+            .iter()
+            .any(|grid| crate::board::count_solutions(grid, Some(2)) == 2));
+
+        // a 4 fits the first row of the top right grid, but its solution has a 2 there
+        let mut grids = puzzle.grids().clone();
+        grids[1].set(0, 0, 4).unwrap();
+        assert!(grids[1].is_valid_board());
+        let unsolvable = Samurai::try_new(grids).unwrap();
+        assert_eq!(unsolvable.solved(), None);
+    }
+}
@@ -0,0 +1,213 @@
+//! This module contains sandwich clues, which give the sum of the digits between the 1 and the 9
+//! of a row or column. A sum of 0 means the 1 and the 9 are next to each other
+//!
+//! Clues are read one per line as the row or column, eg `r1` or `c4`, followed by the sum,
+//! eg `r1 15`. Blank lines and lines starting with `#` are skipped
+
+use crate::board::{Board, Index, Unit};
+use crate::constraint::{sums_to, Constraint};
+use crate::error::{ClueKind, SudokuError};
+use crate::io::parse_lines;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Mask with the bits of the digits that can sit between the 1 and the 9, 2 to 8
+const FILLINGS: u16 = 0b1_1111_1100;
+
+/// Largest sandwich sum, with every digit from 2 to 8 between the 1 and the 9
+const MAX_SUM: u8 = 35;
+
+/// A row or column whose digits between the 1 and the 9 add up to the sum
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub struct Sandwich {
+    line: Unit,
+    sum: u8,
+}
+
+impl Sandwich {
+    /// Make a sandwich clue for a row or column
+    /// # Errors
+    /// Returns the reason if the line is a box or no digits between a 1 and a 9 can add up to the sum
+    pub fn new(line: Unit, sum: u8) -> Result<Sandwich, String> {
+        if matches!(line, Unit::Subgrid(_)) {
+            return Err(format!("{} is not a row or column", line));
+        }
+        if sum == 1 || sum > MAX_SUM {
+            return Err(format!("no digits from 2 to 8 add up to {}", sum));
+        }
+        Ok(Sandwich { line, sum })
+    }
+    /// Get the row or column of the clue
+    pub fn line(&self) -> Unit {
+        self.line
+    }
+    /// Get the sum of the digits between the 1 and the 9
+    pub fn sum(&self) -> u8 {
+        self.sum
+    }
+}
+
+impl Constraint for Sandwich {
+    /// Check that the digits between the 1 and the 9 can still add up to the sum
+    /// with digits the line doesn't hold yet, trying every empty cell for a crust not yet placed
+    fn allows(&self, board: &Board, index: &Index, value: u8) -> bool {
+        let cells = self.line.cells();
+        if !cells.contains(index) {
+            return true;
+        }
+        let digits = cells.map(|cell| {
+            if cell == *index {
+                value
+            } else {
+                board.get_cell(&cell).unwrap_or(0)
+            }
+        });
+        let used = digits.iter().fold(0u16, |used, &digit| used | 1 << digit);
+        // whether the crusts at the two positions leave a sum the cells between can make
+        let fits = |a: usize, b: usize| {
+            let between = &digits[a.min(b) + 1..a.max(b)];
+            let total: usize = between.iter().map(|&digit| digit as usize).sum();
+            let empty = between.iter().filter(|&&digit| digit == 0).count();
+            total <= self.sum as usize
+                && sums_to(FILLINGS & !used, empty, self.sum as usize - total)
+        };
+        let one = digits.iter().position(|&digit| digit == 1);
+        let nine = digits.iter().position(|&digit| digit == 9);
+        match (one, nine) {
+            (Some(one), Some(nine)) => fits(one, nine),
+            // the missing crust needs an empty cell to go in
+            (Some(crust), None) | (None, Some(crust)) => {
+                (0..9).any(|other| digits[other] == 0 && fits(crust, other))
+            }
+            (None, None) => true,
+        }
+    }
+}
+
+impl FromStr for Sandwich {
+    type Err = String;
+
+    /// Read a clue as the row or column followed by the sum, eg `r1 15` or `c4 0`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (line, sum) = s
+            .split_once(char::is_whitespace)
+            .ok_or_else(|| format!("expected a row or column and a sum, found {:?}", s))?;
+        let read_line = || {
+            let number: usize = line.get(1..)?.parse().ok()?;
+            let number = number.checked_sub(1).filter(|&number| number < 9)?;
+            match line.get(..1)? {
+                "r" | "R" => Some(Unit::Row(number)),
+                "c" | "C" => Some(Unit::Column(number)),
+                _ => None,
+            }
+        };
+        let line =
+            read_line().ok_or_else(|| format!("could not read {:?} as a row or column", line))?;
+        let sum = sum.trim();
+        let sum = sum
+            .parse()
+            .map_err(|_| format!("could not read {:?} as a sum", sum))?;
+        Sandwich::new(line, sum)
+    }
+}
+
+impl Display for Sandwich {
+    /// Print the clue in the format it is read in
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Unit::Row(row) => write!(f, "r{} {}", row + 1, self.sum),
+            Unit::Column(column) => write!(f, "c{} {}", column + 1, self.sum),
+            Unit::Subgrid(_) => unreachable!("sandwiches are only made for rows and columns"),
+        }
+    }
+}
+
+/// Read sandwich clues, one per line
+/// # Errors
+/// Returns SudokuError::InvalidClue for a line that can't be read, numbering clues from 0
+pub fn parse_sandwiches(s: &str) -> Result<Vec<Sandwich>, SudokuError> {
+    parse_lines(s, ClueKind::Sandwich)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{count_solutions, solve_with_constraints};
+    use crate::fixtures::{
+        assert_only_easy_solution, easy_board, easy_solution, easy_solution_without_rows,
+    };
+
+    fn sandwiches() -> Vec<Sandwich> {
+        let clues = std::fs::read_to_string("tests/test_sandwiches.txt").unwrap();
+        parse_sandwiches(&clues).unwrap()
+    }
+
+    #[test]
+    fn test_parse_sandwiches() {
+        let sandwiches = sandwiches();
+        assert_eq!(sandwiches.len(), 18);
+        assert_eq!(sandwiches[0].line(), Unit::Row(0));
+        assert_eq!(sandwiches[0].sum(), 29);
+        assert_eq!(sandwiches[9].to_string(), "c1 0");
+
+        assert_eq!(
+            parse_sandwiches("r1 5\nb1 5"),
+            Err(SudokuError::InvalidClue {
+                kind: ClueKind::Sandwich,
+                line: 1,
+                reason: "could not read \"b1\" as a row or column".to_string()
+            })
+        );
+        assert!(parse_sandwiches("r10 5").is_err());
+        assert!(parse_sandwiches("c2 36").is_err());
+        assert!(parse_sandwiches("c2 1").is_err());
+        assert!(parse_sandwiches("c2").is_err());
+    }
+
+    #[test]
+    fn test_allows() {
+        let sandwich: Sandwich = "r1 5".parse().unwrap();
+        let mut board = Board::try_new(&[[0; 9]; 9]).unwrap();
+        board.set(0, 0, 1).unwrap();
+        // a 9 in r1c3 leaves one cell between, which can hold 5
+        assert!(sandwich.allows(&board, &Index(0, 2), 9));
+        // a 9 in r1c2 leaves nothing between
+        assert!(!sandwich.allows(&board, &Index(0, 1), 9));
+        board.set(0, 3, 9).unwrap();
+        // two cells between, from 2 and 3 to 8 and 7, can make 5 but not with a 4
+        assert!(sandwich.allows(&board, &Index(0, 1), 2));
+        assert!(!sandwich.allows(&board, &Index(0, 1), 4));
+        // other lines aren't affected
+        assert!(sandwich.allows(&board, &Index(1, 1), 4));
+    }
+
+    #[test]
+    fn test_solve() {
+        // clearing the top band leaves 120 classic solutions, and the sums pick out one of them
+        let puzzle = easy_solution_without_rows(&[0, 1, 2]);
+        assert_eq!(count_solutions(&puzzle, None), 120);
+        assert_only_easy_solution(&puzzle, &sandwiches().as_slice());
+    }
+
+    #[test]
+    fn test_wrong_sum() {
+        // the 1 and 9 of the first row are far apart in the solution
+        let wrong: Sandwich = "r1 0".parse().unwrap();
+        assert!(!wrong.is_satisfied(&easy_solution()));
+        assert_eq!(solve_with_constraints(&mut easy_board(), &[&wrong]), None);
+    }
+
+    #[test]
+    fn test_cleared_rows() {
+        // with the top three rows cleared, the sandwiches still lead to a matching grid
+        let sandwiches = sandwiches();
+        let solution = easy_solution();
+        let mut cleared = solution.clone();
+        for (index, _) in solution.cells().take(27) {
+            cleared.update_cell(&index, 0);
+        }
+        let solved = solve_with_constraints(&mut cleared, &[&sandwiches.as_slice()]).unwrap();
+        assert!(solved.is_complete());
+        assert!(sandwiches.as_slice().is_satisfied(&solved));
+    }
+}
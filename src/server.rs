@@ -0,0 +1,346 @@
+//! This module contains a small HTTP API over the solver, so the crate can back a puzzle website
+//! Every endpoint takes a POST with a json body and answers with json:
+//! - `/solve` takes `{"puzzle": "..."}` and answers `{"puzzle": "...", "solution": "..." | null}`
+//! - `/validate` takes `{"puzzle": "..."}` and answers `{"valid": true, "solutions": 1}`,
+//!   counting solutions up to 2. A puzzle is valid if it has exactly one
+//! - `/rate` takes `{"puzzle": "..."}` and answers `{"difficulty": "easy"}`
+//! - `/generate` takes `{"difficulty": "hard", "symmetry": "rotational", "seed": 7}`,
+//!   all optional, and answers `{"puzzle": "...", "seed": 7}`
+//!
+//! Puzzles can be sent in any format Board parses, and come back as one line strings.
+//! Unreadable requests and boards get a 400 with `{"error": "..."}`,
+//! and requests whose headers or body are too long get a 413
+//!
+//! Enabled by the `server` feature
+
+use crate::board::{count_solutions, Board};
+use crate::generator::{generate_with_symmetry, Difficulty, Rng, Symmetry};
+use crate::rating::rate;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::sync_channel;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Largest request body accepted, well over any puzzle format
+const MAX_BODY: usize = 64 * 1024;
+/// Largest request line and headers accepted, together
+const MAX_HEAD: usize = 8 * 1024;
+/// How long a client may take to send its request or read the reply before it is dropped
+const TIMEOUT: Duration = Duration::from_secs(10);
+/// Number of threads answering requests. Further connections wait to be accepted
+const WORKERS: usize = 8;
+
+#[derive(Deserialize)]
+struct PuzzleRequest {
+    puzzle: Board,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct GenerateRequest {
+    difficulty: Option<String>,
+    symmetry: Option<String>,
+    seed: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct SolveResponse {
+    puzzle: Board,
+    solution: Option<Board>,
+}
+
+#[derive(Serialize)]
+struct ValidateResponse {
+    valid: bool,
+    solutions: usize,
+}
+
+#[derive(Serialize)]
+struct RateResponse {
+    difficulty: String,
+}
+
+#[derive(Serialize)]
+struct GenerateResponse {
+    puzzle: Board,
+    seed: u64,
+}
+
+#[derive(Serialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+/// Status code and json body of a reply
+type Reply = (u16, String);
+
+fn ok(body: &impl Serialize) -> Reply {
+    (
+        200,
+        serde_json::to_string(body).expect("responses are always serializable"),
+    )
+}
+
+fn error(status: u16, message: impl ToString) -> Reply {
+    let body = ErrorResponse {
+        error: message.to_string(),
+    };
+    (
+        status,
+        serde_json::to_string(&body).expect("responses are always serializable"),
+    )
+}
+
+/// Read the puzzle out of a request body
+fn parse_puzzle(body: &str) -> Result<Board, Reply> {
+    serde_json::from_str::<PuzzleRequest>(body)
+        .map(|request| request.puzzle)
+        .map_err(|e| error(400, e))
+}
+
+fn handle_generate(body: &str) -> Result<Reply, Reply> {
+    // an empty body asks for the defaults
+    let request: GenerateRequest = if body.trim().is_empty() {
+        GenerateRequest::default()
+    } else {
+        serde_json::from_str(body).map_err(|e| error(400, e))?
+    };
+    let difficulty: Difficulty = request
+        .difficulty
+        .as_deref()
+        .unwrap_or("medium")
+        .parse()
+        .map_err(|e| error(400, e))?;
+    let symmetry: Symmetry = request
+        .symmetry
+        .as_deref()
+        .unwrap_or("none")
+        .parse()
+        .map_err(|e| error(400, e))?;
+    let seed = request.seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+    });
+    let puzzle = generate_with_symmetry(difficulty, symmetry, &mut Rng::new(seed));
+    Ok(ok(&GenerateResponse { puzzle, seed }))
+}
+
+/// Answer a request, given its method, path and body
+fn handle(method: &str, path: &str, body: &str) -> Reply {
+    let endpoints = ["/solve", "/validate", "/rate", "/generate"];
+    if !endpoints.contains(&path) {
+        return error(404, format!("No endpoint at {}", path));
+    }
+    if method != "POST" {
+        return error(405, format!("{} only accepts POST", path));
+    }
+    let reply = match path {
+        "/solve" => parse_puzzle(body).map(|puzzle| {
+            let solution = puzzle.solved();
+            ok(&SolveResponse { puzzle, solution })
+        }),
+        "/validate" => parse_puzzle(body).map(|puzzle| {
+            let solutions = count_solutions(&puzzle, Some(2));
+            ok(&ValidateResponse {
+                valid: solutions == 1,
+                solutions,
+            })
+        }),
+        "/rate" => parse_puzzle(body).map(|puzzle| {
+            ok(&RateResponse {
+                difficulty: rate(&puzzle).to_string(),
+            })
+        }),
+        _ => handle_generate(body),
+    };
+    reply.unwrap_or_else(|reply| reply)
+}
+
+/// Get the reason phrase sent with a status code
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        _ => "Internal Server Error",
+    }
+}
+
+/// Read one request from the stream and write the reply
+fn handle_connection(stream: TcpStream) -> std::io::Result<()> {
+    // a client that stops sending or reading mid request would otherwise hold its worker forever
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+    // a client sending a line without end would otherwise grow it until memory runs out
+    let mut head = (&mut reader).take(MAX_HEAD as u64);
+    let mut request_line = String::new();
+    head.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut content_length = 0;
+    let mut ended = false;
+    loop {
+        let mut header = String::new();
+        if head.read_line(&mut header)? == 0 {
+            break;
+        }
+        if header.trim().is_empty() {
+            ended = true;
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    let head_too_long = !ended && head.limit() == 0;
+
+    let (status, body) = if head_too_long {
+        error(
+            413,
+            format!(
+                "Request lines and headers are limited to {} bytes",
+                MAX_HEAD
+            ),
+        )
+    } else if content_length > MAX_BODY {
+        error(413, format!("Bodies are limited to {} bytes", MAX_BODY))
+    } else {
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body)?;
+        handle(&method, &path, &String::from_utf8_lossy(&body))
+    };
+
+    let mut stream = &stream;
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason(status),
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+/// Serve the API on a listener, handing connections to a fixed pool of worker threads
+/// Connections that fail to be accepted are logged and skipped, so this only returns
+/// if every worker has stopped
+pub fn serve_on(listener: TcpListener) {
+    let (sender, receiver) = sync_channel::<TcpStream>(WORKERS);
+    let receiver = Arc::new(Mutex::new(receiver));
+    for _ in 0..WORKERS {
+        let receiver = Arc::clone(&receiver);
+        std::thread::spawn(move || loop {
+            let stream = receiver.lock().expect("workers don't panic").recv();
+            match stream {
+                // a client that hangs up early only loses its own reply
+                Ok(stream) => drop(handle_connection(stream)),
+                Err(_) => return,
+            }
+        });
+    }
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if sender.send(stream).is_err() {
+                    return;
+                }
+            }
+            // running out of file descriptors or a client aborting is no reason to stop serving
+            Err(e) => eprintln!("Error accepting a connection: {}", e),
+        }
+    }
+}
+
+/// Serve the API on an address such as 127.0.0.1:8080
+/// Only returns if binding the address fails, or every worker has stopped
+pub fn serve(addr: impl ToSocketAddrs) -> std::io::Result<()> {
+    serve_on(TcpListener::bind(addr)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::EASY;
+
+    fn puzzle_body(puzzle: &str) -> String {
+        format!("{{\"puzzle\":\"{}\"}}", puzzle)
+    }
+
+    #[test]
+    fn test_endpoints() {
+        let (status, body) = handle("POST", "/solve", &puzzle_body(EASY));
+        assert_eq!(status, 200);
+        assert!(body.contains("\"solution\":\"973854216"));
+
+        let (status, body) = handle("POST", "/validate", &puzzle_body(EASY));
+        assert_eq!(status, 200);
+        assert_eq!(body, "{\"valid\":true,\"solutions\":1}");
+
+        let (_, body) = handle("POST", "/rate", &puzzle_body(EASY));
+        assert_eq!(body, "{\"difficulty\":\"easy\"}");
+
+        let (status, body) = handle("POST", "/generate", "{\"difficulty\":\"easy\",\"seed\":7}");
+        assert_eq!(status, 200);
+        assert!(body.ends_with(",\"seed\":7}"));
+        assert_eq!(
+            body,
+            handle("POST", "/generate", "{\"difficulty\":\"easy\",\"seed\":7}").1
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(handle("POST", "/nowhere", "").0, 404);
+        assert_eq!(handle("GET", "/solve", "").0, 405);
+        assert_eq!(handle("POST", "/solve", "not json").0, 400);
+        assert_eq!(handle("POST", "/solve", &puzzle_body("123")).0, 400);
+        assert_eq!(
+            handle("POST", "/generate", "{\"difficulty\":\"impossible\"}").0,
+            400
+        );
+    }
+
+    #[test]
+    fn test_http_round_trip() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || serve_on(listener));
+
+        let body = puzzle_body(EASY);
+        // more requests than workers, so each worker goes back for another connection
+        for _ in 0..=WORKERS {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            write!(
+                stream,
+                "POST /solve HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+            assert!(response.contains("\"solution\":\"973854216"));
+        }
+
+        // a request line that never ends is cut off at the limit
+        let mut stream = TcpStream::connect(addr).unwrap();
+        let request_line = format!("POST /{}", "a".repeat(MAX_HEAD - 6));
+        stream.write_all(request_line.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 413 Payload Too Large\r\n"));
+    }
+}
@@ -0,0 +1,506 @@
+//! This module contains boards of other sizes than 9x9: 4x4, 6x6, 16x16 (hexadoku) and 25x25
+//! `SizedBoard<N>` is an NxN grid holding the values 1 to N, with boxes of 2x2, 2x3, 3x3, 4x4 or 5x5.
+//! Every row, column and box must hold each value exactly once, as in sudoku
+//!
+//! Values are written as the digits 1 to 9 on boards up to 9x9, as the hex digits 0-F standing for
+//! 1 to 16 on hexadoku, and as the letters A-Y on 25x25 boards, with . for empty cells.
+//! Boards can also be read as N comma separated rows of the numbers 1 to N, with 0 or a blank for empty cells
+//!
+//! `SizedBoard<N>` is a separate type from the 9x9 Board, but every size is solved by the same search.
+//! Board keeps its own solvers, variants and techniques on top, and converts to and from `SizedBoard<9>`
+
+use crate::board::{Board, BoardError, Grid, Index, Search};
+use crate::error::{Dimension, SudokuError};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// A 4x4 board with 2x2 boxes
+pub type Board4 = SizedBoard<4>;
+/// A 6x6 board with boxes of 2 rows and 3 columns
+pub type Board6 = SizedBoard<6>;
+/// A 16x16 board with 4x4 boxes
+pub type Hexadoku = SizedBoard<16>;
+/// A 25x25 board with 5x5 boxes
+pub type Board25 = SizedBoard<25>;
+
+/// Get the rows and columns of a box on an NxN board
+/// Sizes without a standard box shape fail to compile once the board is used,
+/// as callers evaluate it in a const
+const fn box_size(n: usize) -> (usize, usize) {
+    match n {
+        4 => (2, 2),
+        6 => (2, 3),
+        9 => (3, 3),
+        16 => (4, 4),
+        25 => (5, 5),
+        _ => panic!("boards must be 4x4, 6x6, 9x9, 16x16 or 25x25"),
+    }
+}
+
+/// Get the box of every cell of an NxN board, counted row by row from the top left
+pub(crate) fn boxes<const N: usize>() -> [[u8; N]; N] {
+    let (rows, columns) = const { box_size(N) };
+    let mut regions = [[0; N]; N];
+    for (row, cells) in regions.iter_mut().enumerate() {
+        for (column, region) in cells.iter_mut().enumerate() {
+            *region = (row / rows * (N / columns) + column / columns) as u8;
+        }
+    }
+    regions
+}
+
+/// An NxN board
+/// Only the sizes with a standard box shape can be built
+/// ```compile_fail
+/// use sudoku_solver_by_roy::sized::SizedBoard;
+///
+/// let board = SizedBoard::<5>::default();
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct SizedBoard<const N: usize> {
+    /// Values 1 to N, 0 for empty cells
+    cells: [[u8; N]; N],
+}
+
+impl<const N: usize> Default for SizedBoard<N> {
+    fn default() -> Self {
+        // reject sizes without a box shape
+        let _ = Self::BOX;
+        SizedBoard { cells: [[0; N]; N] }
+    }
+}
+
+impl<const N: usize> SizedBoard<N> {
+    /// Rows and columns of a box
+    const BOX: (usize, usize) = box_size(N);
+
+    /// Get the symbol written for a value, or . for an empty cell
+    fn symbol(value: u8) -> char {
+        match value {
+            0 => '.',
+            _ if N <= 9 => char::from(b'0' + value),
+            _ if N == 16 => char::from_digit(value as u32 - 1, 16)
+                .expect("hexadoku values are at most 16")
+                .to_ascii_uppercase(),
+            _ => char::from(b'A' + value - 1),
+        }
+    }
+
+    /// Get the value a symbol stands for, 0 for an empty cell
+    /// Values past N are returned as they are, to be rejected as invalid values
+    fn value_of(c: char) -> Option<u32> {
+        match c {
+            '.' | '_' => Some(0),
+            _ if N <= 9 => c.to_digit(10),
+            _ if N == 16 => c.to_digit(16).map(|digit| digit + 1),
+            _ if c.is_ascii_alphabetic() => Some(c.to_ascii_uppercase() as u32 - 'A' as u32 + 1),
+            _ => None,
+        }
+    }
+
+    /// Create a board from rows of values, with 0 for empty cells
+    /// # Errors
+    /// 1. SudokuError::InvalidValue if a cell holds a number over N
+    /// 2. SudokuError::RuleViolation if the board breaks the rules
+    pub fn try_new(cells: &[[u8; N]; N]) -> Result<Self, SudokuError> {
+        check_values(cells)?;
+        match first_conflict(cells, &boxes()) {
+            Some((row, column)) => Err(SudokuError::RuleViolation { row, column }),
+            None => Ok(SizedBoard { cells: *cells }),
+        }
+    }
+    /// Get the value at a 0-based row and column, or None if the cell is empty
+    /// # Errors
+    /// Returns BoardError::OutOfBounds if the row or column is past the edge of the board
+    pub fn get(&self, row: usize, column: usize) -> Result<Option<u8>, BoardError> {
+        if row >= N || column >= N {
+            return Err(BoardError::OutOfBounds { row, column });
+        }
+        Ok(Some(self.cells[row][column]).filter(|&value| value != 0))
+    }
+    /// Fill the cell at a 0-based row and column with a value, or clear it with 0
+    /// The rules are not checked, so the board can be filled in any order
+    /// # Errors
+    /// 1. BoardError::OutOfBounds if the row or column is past the edge of the board
+    /// 2. BoardError::InvalidValue if the value is over N
+    pub fn set(&mut self, row: usize, column: usize, value: u8) -> Result<(), BoardError> {
+        if row >= N || column >= N {
+            return Err(BoardError::OutOfBounds { row, column });
+        }
+        if value as usize > N {
+            return Err(BoardError::InvalidValue(value));
+        }
+        self.cells[row][column] = value;
+        Ok(())
+    }
+    /// Check that no row, column or box repeats a value
+    pub fn is_valid_board(&self) -> bool {
+        first_conflict(&self.cells, &boxes()).is_none()
+    }
+    /// Check that every cell is filled and the rules hold
+    pub fn is_solved(&self) -> bool {
+        self.cells.iter().flatten().all(|&value| value != 0) && self.is_valid_board()
+    }
+    /// Solve a copy of the board, returning None if it has no solution
+    /// # Example
+    /// ```
+    /// use sudoku_solver_by_roy::sized::Hexadoku;
+    /// let puzzle = Hexadoku::default();
+    /// let solution = puzzle.solved().unwrap();
+    /// assert!(solution.is_solved());
+    /// ```
+    pub fn solved(&self) -> Option<Self> {
+        Search::<N, Self>::blank().run(&mut self.clone())
+    }
+    /// Count the solutions of the board, stopping once limit are found
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        let mut count = 0;
+        if limit > 0 {
+            Search::<N, Self>::blank().explore(&mut self.clone(), &mut |_| {
+                count += 1;
+                count >= limit
+            });
+        }
+        count
+    }
+    /// Get the board as a line of N * N symbols, with . for empty cells
+    pub fn to_line(&self) -> String {
+        self.cells
+            .iter()
+            .flatten()
+            .map(|&value| Self::symbol(value))
+            .collect()
+    }
+    /// Get the board as N lines of comma separated values from 1 to N, with 0 for empty cells
+    pub fn to_csv(&self) -> String {
+        self.cells
+            .iter()
+            .map(|row| {
+                let values: Vec<String> = row.iter().map(|value| value.to_string()).collect();
+                values.join(",") + "\n"
+            })
+            .collect()
+    }
+    /// Read the values of a board without checking the rules,
+    /// as comma separated rows of values from 1 to N or as N * N symbols
+    pub(crate) fn parse_cells(s: &str) -> Result<[[u8; N]; N], SudokuError> {
+        let s = s.trim();
+        let cells = if s.contains(',') {
+            Self::cells_from_csv(s)?
+        } else {
+            Self::cells_from_symbols(s)?
+        };
+        check_values(&cells)?;
+        Ok(cells)
+    }
+    /// Read comma separated rows of values from 1 to N
+    fn cells_from_csv(s: &str) -> Result<[[u8; N]; N], SudokuError> {
+        let lines: Vec<&str> = s.lines().filter(|line| !line.trim().is_empty()).collect();
+        if lines.len() != N {
+            return Err(SudokuError::DimensionError {
+                row: None,
+                found: lines.len(),
+                expected: N,
+                unit: Dimension::Rows,
+            });
+        }
+        let mut cells = [[0; N]; N];
+        for (row, line) in lines.iter().enumerate() {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            if fields.len() != N {
+                return Err(SudokuError::DimensionError {
+                    row: Some(row),
+                    found: fields.len(),
+                    expected: N,
+                    unit: Dimension::Cells,
+                });
+            }
+            for (column, field) in fields.iter().enumerate() {
+                cells[row][column] = match *field {
+                    "" | "." | "_" => 0,
+                    _ => field.parse().map_err(|_| SudokuError::ParseError {
+                        row,
+                        column,
+                        found: field.to_string(),
+                    })?,
+                };
+            }
+        }
+        Ok(cells)
+    }
+    /// Read N * N symbols, with . or _ for empty cells
+    /// Whitespace between the symbols is ignored, so lines and grids are both read
+    fn cells_from_symbols(s: &str) -> Result<[[u8; N]; N], SudokuError> {
+        let symbols: Vec<char> = s.chars().filter(|c| !c.is_whitespace()).collect();
+        if symbols.len() != N * N {
+            return Err(SudokuError::DimensionError {
+                row: None,
+                found: symbols.len(),
+                expected: N * N,
+                unit: Dimension::Cells,
+            });
+        }
+        let mut cells = [[0; N]; N];
+        for (i, c) in symbols.into_iter().enumerate() {
+            let (row, column) = (i / N, i % N);
+            let value = Self::value_of(c).ok_or(SudokuError::ParseError {
+                row,
+                column,
+                found: c.to_string(),
+            })?;
+            if value as usize > N {
+                return Err(SudokuError::InvalidValue { row, column, value });
+            }
+            cells[row][column] = value as u8;
+        }
+        Ok(cells)
+    }
+}
+
+impl<const N: usize> FromStr for SizedBoard<N> {
+    type Err = SudokuError;
+
+    /// Read comma separated rows of values from 1 to N, or N * N symbols
+    /// # Example
+    /// ```
+    /// use sudoku_solver_by_roy::sized::{Board6, Hexadoku};
+    /// let puzzle: Hexadoku = format!("0123456789ABCDEF{}", ".".repeat(240)).parse().unwrap();
+    /// assert_eq!(puzzle.get(0, 15), Ok(Some(16)));
+    /// let small: Board6 = "123456 ...... ...... ...... ...... ......".parse().unwrap();
+    /// assert!(small.solved().is_some());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_new(&Self::parse_cells(s)?)
+    }
+}
+
+impl<const N: usize> Display for SizedBoard<N> {
+    /// Print the board as symbols, with gaps between the boxes
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (rows, columns) = Self::BOX;
+        for (row, values) in self.cells.iter().enumerate() {
+            if row > 0 && row % rows == 0 {
+                writeln!(f)?;
+            }
+            let boxes: Vec<String> = values
+                .chunks(columns)
+                .map(|chunk| chunk.iter().map(|&value| Self::symbol(value)).collect())
+                .collect();
+            writeln!(f, "{}", boxes.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize> Grid<N> for SizedBoard<N> {
+    fn digits(&self) -> [[u8; N]; N] {
+        self.cells
+    }
+    fn update_cell(&mut self, index: &Index, value: u8) {
+        self.cells[index.0][index.1] = value;
+    }
+}
+
+impl From<&Board> for SizedBoard<9> {
+    fn from(board: &Board) -> Self {
+        SizedBoard {
+            cells: board.digits(),
+        }
+    }
+}
+
+impl From<&SizedBoard<9>> for Board {
+    /// Every filled cell becomes a clue of the board
+    fn from(board: &SizedBoard<9>) -> Self {
+        Board::from_digits(&board.cells)
+    }
+}
+
+/// Check that every value is at most N
+fn check_values<const N: usize>(cells: &[[u8; N]; N]) -> Result<(), SudokuError> {
+    for (row, values) in cells.iter().enumerate() {
+        for (column, &value) in values.iter().enumerate() {
+            if value as usize > N {
+                return Err(SudokuError::InvalidValue {
+                    row,
+                    column,
+                    value: value as u32,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Get the first cell, row by row, that repeats a value earlier in its row, column or region
+/// regions holds the region of each cell, numbered from 0
+pub(crate) fn first_conflict<const N: usize>(
+    cells: &[[u8; N]; N],
+    regions: &[[u8; N]; N],
+) -> Option<(usize, usize)> {
+    let mut rows = [0u32; N];
+    let mut columns = [0u32; N];
+    let mut used = [0u32; N];
+    for (row, values) in cells.iter().enumerate() {
+        for (column, &value) in values.iter().enumerate() {
+            if value == 0 {
+                continue;
+            }
+            let bit = 1 << (value - 1);
+            let region = regions[row][column] as usize;
+            if (rows[row] | columns[column] | used[region]) & bit != 0 {
+                return Some((row, column));
+            }
+            rows[row] |= bit;
+            columns[column] |= bit;
+            used[region] |= bit;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A solved board, shifting each row of a band by a box width and each band by one
+    fn pattern<const N: usize>() -> SizedBoard<N> {
+        let (rows, columns) = SizedBoard::<N>::BOX;
+        let mut cells = [[0; N]; N];
+        for (row, values) in cells.iter_mut().enumerate() {
+            for (column, value) in values.iter_mut().enumerate() {
+                *value = ((row % rows * columns + row / rows + column) % N) as u8 + 1;
+            }
+        }
+        SizedBoard::try_new(&cells).unwrap()
+    }
+
+    /// Clear about two cells in five of a solved board
+    fn puzzle<const N: usize>(solution: &SizedBoard<N>) -> SizedBoard<N> {
+        let mut puzzle = solution.clone();
+        for row in 0..N {
+            for column in 0..N {
+                if (row * 7 + column * 3) % 5 < 2 {
+                    puzzle.set(row, column, 0).unwrap();
+                }
+            }
+        }
+        puzzle
+    }
+
+    /// Check that the board solves to a valid grid keeping its clues
+    fn assert_solves<const N: usize>(puzzle: &SizedBoard<N>) {
+        let solved = puzzle.solved().unwrap();
+        assert!(solved.is_solved());
+        for row in 0..N {
+            for column in 0..N {
+                if let Some(value) = puzzle.get(row, column).unwrap() {
+                    assert_eq!(solved.get(row, column), Ok(Some(value)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_patterns_are_solved() {
+        assert!(pattern::<4>().is_solved());
+        assert!(pattern::<6>().is_solved());
+        assert!(pattern::<9>().is_solved());
+        assert!(pattern::<16>().is_solved());
+        assert!(pattern::<25>().is_solved());
+    }
+
+    #[test]
+    fn test_solve_every_size() {
+        assert_solves(&puzzle(&pattern::<4>()));
+        assert_solves(&puzzle(&pattern::<6>()));
+        assert_solves(&puzzle(&pattern::<9>()));
+        assert_solves(&puzzle(&pattern::<16>()));
+        assert_solves(&puzzle(&pattern::<25>()));
+    }
+
+    #[test]
+    fn test_parse_formats() {
+        let board = pattern::<16>();
+        let from_line: Hexadoku = board.to_line().parse().unwrap();
+        let from_csv: Hexadoku = board.to_csv().parse().unwrap();
+        let from_grid: Hexadoku = board.to_string().parse().unwrap();
+        assert_eq!(from_line, board);
+        assert_eq!(from_csv, board);
+        assert_eq!(from_grid, board);
+        assert!(board.to_line().starts_with("0123456789ABCDEF4567"));
+
+        let small = pattern::<6>();
+        assert!(small.to_line().starts_with("123456456123"));
+        assert_eq!(small.to_string().parse::<Board6>().unwrap(), small);
+        let large = pattern::<25>();
+        assert!(large.to_line().starts_with("ABCDEFGHIJKLMNOPQRSTUVWXY"));
+        assert_eq!(large.to_csv().parse::<Board25>().unwrap(), large);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            "0123".parse::<Hexadoku>(),
+            Err(SudokuError::DimensionError { expected: 256, .. })
+        ));
+        // a 4x4 line counts cells even though there are as many as a large board has rows
+        assert_eq!(
+            "123".parse::<Board4>().unwrap_err().to_string(),
+            "Invalid dimensions: found 3 cells, expected 16"
+        );
+        let mut line = ".".repeat(256);
+        line.replace_range(0..1, "G");
+        assert!(matches!(
+            line.parse::<Hexadoku>(),
+            Err(SudokuError::ParseError {
+                row: 0,
+                column: 0,
+                ..
+            })
+        ));
+        // the same value twice in the first row
+        line.replace_range(0..2, "00");
+        assert!(matches!(
+            line.parse::<Hexadoku>(),
+            Err(SudokuError::RuleViolation { row: 0, column: 1 })
+        ));
+        // 7 is too large for a 6x6 board
+        assert!(matches!(
+            format!("7{}", ".".repeat(35)).parse::<Board6>(),
+            Err(SudokuError::InvalidValue { value: 7, .. })
+        ));
+    }
+
+    #[test]
+    fn test_count_solutions() {
+        assert_eq!(pattern::<16>().count_solutions(2), 1);
+        assert_eq!(Hexadoku::default().count_solutions(2), 2);
+        // an empty 4x4 board has 288 solutions
+        assert_eq!(Board4::default().count_solutions(usize::MAX), 288);
+    }
+
+    #[test]
+    fn test_board_conversion() {
+        let board = Board::from_csv("tests/test_board_pass.csv").unwrap();
+        let sized = SizedBoard::<9>::from(&board);
+        assert_eq!(sized.to_line(), board.to_line());
+        assert_eq!(
+            Board::from(&sized.solved().unwrap()),
+            board.solved().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_get_and_set_bounds() {
+        let mut board = Hexadoku::default();
+        assert_eq!(
+            board.set(16, 0, 1),
+            Err(BoardError::OutOfBounds { row: 16, column: 0 })
+        );
+        assert_eq!(board.set(0, 0, 17), Err(BoardError::InvalidValue(17)));
+        board.set(15, 15, 16).unwrap();
+        assert_eq!(board.get(15, 15), Ok(Some(16)));
+        assert_eq!(board.to_line().chars().last(), Some('F'));
+    }
+}
@@ -0,0 +1,62 @@
+//! This module contains the Solver trait, so applications can choose which
+//! solving strategy to use or inject their own
+
+use crate::board::{solve_with_selection, Board, CellSelection};
+use crate::dlx::solve_dlx;
+
+/// A strategy for solving boards
+pub trait Solver {
+    /// Solve the board, returning None if it has no solution
+    /// The board itself is left unchanged
+    fn solve(&self, board: &Board) -> Option<Board>;
+}
+
+/// The backtracking solver, with naked single propagation
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub struct Backtracking {
+    /// How the next cell to branch on is chosen
+    pub selection: CellSelection,
+}
+
+impl Solver for Backtracking {
+    fn solve(&self, board: &Board) -> Option<Board> {
+        solve_with_selection(&mut board.clone(), self.selection)
+    }
+}
+
+/// The Dancing Links (Algorithm X) solver
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub struct DancingLinks;
+
+impl Solver for DancingLinks {
+    fn solve(&self, board: &Board) -> Option<Board> {
+        solve_dlx(board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Index;
+    use crate::fixtures::wikipedia_board;
+
+    #[test]
+    fn test_solvers_agree() {
+        let solvers: [&dyn Solver; 3] = [
+            &Backtracking::default(),
+            &Backtracking {
+                selection: CellSelection::FirstEmpty,
+            },
+            &DancingLinks,
+        ];
+        let board = wikipedia_board();
+        let solution = solvers[0].solve(&board).unwrap();
+        let mut invalid = wikipedia_board();
+        invalid.update_cell(&Index(0, 2), 5);
+        for solver in solvers {
+            assert_eq!(solver.solve(&board), Some(solution.clone()));
+            assert_eq!(solver.solve(&invalid), None);
+        }
+        assert_eq!(board, wikipedia_board());
+    }
+}
@@ -0,0 +1,200 @@
+//! This module contains thermometer lines, paths of cells whose digits strictly increase
+//! from the bulb to the tip
+//!
+//! Thermometers are read one per line as their cells from the bulb to the tip in r1c1 notation,
+//! eg `r2c1 r2c2 r3c3`. Each cell must touch the one before it, diagonally or not,
+//! and blank lines and lines starting with `#` are skipped
+
+use crate::board::{Board, Index};
+use crate::constraint::Constraint;
+use crate::error::{ClueKind, SudokuError};
+use crate::io::parse_lines;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// A path of cells whose digits strictly increase from the first cell to the last
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Thermometer {
+    cells: Vec<Index>,
+}
+
+impl Thermometer {
+    /// Make a thermometer from its cells, starting at the bulb
+    /// # Errors
+    /// Returns the reason if the thermometer is empty, longer than 9 cells,
+    /// repeats a cell or has a cell not touching the one before it
+    pub fn new(cells: Vec<Index>) -> Result<Thermometer, String> {
+        if cells.is_empty() || cells.len() > 9 {
+            return Err(format!("has {} cells, expected 1 to 9", cells.len()));
+        }
+        for (k, cell) in cells.iter().enumerate() {
+            if cells[..k].contains(cell) {
+                return Err(format!("lists {} twice", cell));
+            }
+            if let Some(previous) = k.checked_sub(1).map(|k| cells[k]) {
+                if previous.0.abs_diff(cell.0) > 1 || previous.1.abs_diff(cell.1) > 1 {
+                    return Err(format!("{} doesn't touch {}", cell, previous));
+                }
+            }
+        }
+        Ok(Thermometer { cells })
+    }
+    /// Get the cells of the thermometer, starting at the bulb
+    pub fn cells(&self) -> &[Index] {
+        &self.cells
+    }
+}
+
+impl Constraint for Thermometer {
+    /// Check that the value leaves room for the cells below and above it,
+    /// counting one step per cell between it and any filled cell of the thermometer
+    fn allows(&self, board: &Board, index: &Index, value: u8) -> bool {
+        let Some(position) = self.cells.iter().position(|cell| cell == index) else {
+            return true;
+        };
+        let value = value as usize;
+        let above = self.cells.len() - 1 - position;
+        if value <= position || value + above > 9 {
+            return false;
+        }
+        self.cells
+            .iter()
+            .enumerate()
+            .all(|(other, cell)| match board.get_cell(cell) {
+                Some(digit) if other < position => digit as usize + (position - other) <= value,
+                Some(digit) if other > position => value + (other - position) <= digit as usize,
+                _ => true,
+            })
+    }
+    /// Get the other cells of the thermometer, which can't repeat the cell's digit
+    fn peers(&self, index: &Index) -> Vec<Index> {
+        if !self.cells.contains(index) {
+            return Vec::new();
+        }
+        self.cells
+            .iter()
+            .filter(|&cell| cell != index)
+            .copied()
+            .collect()
+    }
+}
+
+impl FromStr for Thermometer {
+    type Err = String;
+
+    /// Read a thermometer as its cells from the bulb to the tip, eg `r2c1 r2c2 r3c3`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cells = s
+            .split_whitespace()
+            .map(|cell| {
+                Index::from_r1c1(cell).ok_or_else(|| format!("could not read {:?} as a cell", cell))
+            })
+            .collect::<Result<_, _>>()?;
+        Thermometer::new(cells)
+    }
+}
+
+impl Display for Thermometer {
+    /// Print the thermometer in the format it is read in
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cells: Vec<String> = self.cells.iter().map(Index::to_string).collect();
+        write!(f, "{}", cells.join(" "))
+    }
+}
+
+/// Read thermometers, one per line
+/// # Errors
+/// Returns SudokuError::InvalidClue for a line that can't be read or isn't a path,
+/// numbering thermometers from 0
+pub fn parse_thermometers(s: &str) -> Result<Vec<Thermometer>, SudokuError> {
+    parse_lines(s, ClueKind::Thermometer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{count_solutions, solve_with_constraints};
+    use crate::fixtures::{assert_only_easy_solution, easy_board, easy_solution_without_rows};
+
+    #[test]
+    fn test_parse_thermometers() {
+        let thermometers = std::fs::read_to_string("tests/test_thermometers.txt").unwrap();
+        let thermometers = parse_thermometers(&thermometers).unwrap();
+        assert_eq!(thermometers.len(), 2);
+        assert_eq!(thermometers[1].cells()[1], Index(1, 3));
+        assert_eq!(thermometers[0].to_string(), "r2c1 r2c2 r2c3");
+
+        let invalid = |line: &str| parse_thermometers(line).unwrap_err();
+        assert_eq!(
+            invalid("r1c1 r1c3"),
+            SudokuError::InvalidClue {
+                kind: ClueKind::Thermometer,
+                line: 0,
+                reason: "r1c3 doesn't touch r1c1".to_string()
+            }
+        );
+        assert!(matches!(
+            invalid("r1c1 r1c2\nr1c1 r1c1"),
+            SudokuError::InvalidClue {
+                kind: ClueKind::Thermometer,
+                line: 1,
+                ..
+            }
+        ));
+        assert!(matches!(
+            invalid("r1c1 x"),
+            SudokuError::InvalidClue {
+                kind: ClueKind::Thermometer,
+                line: 0,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_allows() {
+        let thermometer: Thermometer = "r1c1 r1c2 r1c3 r1c4".parse().unwrap();
+        let mut board = Board::try_new(&[[0; 9]; 9]).unwrap();
+        // the bulb leaves room for three digits above it
+        assert!(thermometer.allows(&board, &Index(0, 0), 6));
+        assert!(!thermometer.allows(&board, &Index(0, 0), 7));
+        assert!(!thermometer.allows(&board, &Index(0, 2), 2));
+        // a 5 on the second cell needs the tip to be at least 7
+        board.set(0, 1, 5).unwrap();
+        assert!(!thermometer.allows(&board, &Index(0, 3), 6));
+        assert!(thermometer.allows(&board, &Index(0, 3), 7));
+        assert!(!thermometer.allows(&board, &Index(0, 0), 5));
+        assert!(thermometer.allows(&board, &Index(1, 0), 9));
+        assert!(thermometer.is_satisfied(&board));
+    }
+
+    #[test]
+    fn test_solve() {
+        let thermometers = std::fs::read_to_string("tests/test_thermometers.txt").unwrap();
+        let thermometers = parse_thermometers(&thermometers).unwrap();
+        // clearing the first two rows leaves four classic solutions,
+        // and only the thermometers through row 2 tell them apart
+        let puzzle = easy_solution_without_rows(&[0, 1]);
+        assert_eq!(count_solutions(&puzzle, None), 4);
+        assert_only_easy_solution(&puzzle, &thermometers.as_slice());
+    }
+
+    #[test]
+    fn test_decreasing_clues() {
+        // the clues in r1c2 and r1c3 are 7 and 3, which don't increase
+        let blocked: Thermometer = "r1c2 r1c3".parse().unwrap();
+        assert!(!blocked.is_satisfied(&easy_board()));
+        assert_eq!(solve_with_constraints(&mut easy_board(), &[&blocked]), None);
+    }
+
+    #[test]
+    fn test_full_length() {
+        // a thermometer through 9 cells can only hold 1 to 9 in order
+        let mut empty = Board::try_new(&[[0; 9]; 9]).unwrap();
+        let long: Thermometer = "r1c1 r2c2 r3c3 r4c4 r5c5 r6c6 r7c7 r8c8 r9c9"
+            .parse()
+            .unwrap();
+        let solved = solve_with_constraints(&mut empty, &[&long]).unwrap();
+        assert!((0..9).all(|i| solved.get(i, i) == Ok(Some(i as u8 + 1))));
+    }
+}
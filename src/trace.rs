@@ -0,0 +1,70 @@
+//! This module serializes the steps taken by the solver to JSON
+//! so that frontends can play back the solving process
+//! Each step is an object of the form
+//! `{"action": "place", "index": [row, column], "value": 4, "technique": "guess"}`
+//! Actions are `place` or `remove`, techniques are `single`, `guess` or `backtrack`
+
+use crate::board::{solve_steps, Board, Step};
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct TraceStep {
+    action: &'static str,
+    index: [usize; 2],
+    value: u8,
+    technique: &'static str,
+}
+
+impl From<Step> for TraceStep {
+    fn from(step: Step) -> Self {
+        let (action, technique) = match (step.value, step.is_guess) {
+            (0, _) => ("remove", "backtrack"),
+            (_, true) => ("place", "guess"),
+            (_, false) => ("place", "single"),
+        };
+        TraceStep {
+            action,
+            index: [step.index.0, step.index.1],
+            value: step.value,
+            technique,
+        }
+    }
+}
+
+/// Solve the board and serialize every step taken as a JSON array
+pub fn trace_json(board: &Board) -> String {
+    let steps: Vec<TraceStep> = solve_steps(board)
+        .into_iter()
+        .map(TraceStep::from)
+        .collect();
+    serde_json::to_string(&steps).expect("trace steps are always serializable")
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_trace_json() {
+        let board = Board::from_csv("tests/test_board_pass.csv").unwrap();
+        let trace: serde_json::Value = serde_json::from_str(&trace_json(&board)).unwrap();
+        let steps = trace.as_array().unwrap();
+
+        // replay the trace as a frontend would
+        let mut grid = [[0; 9]; 9];
+        for (index, value, _) in board.diff(&Board::from_digits(&[[0; 9]; 9])) {
+            grid[index.0][index.1] = value.unwrap();
+        }
+        for step in steps {
+            let index = step["index"].as_array().unwrap();
+            let row = index[0].as_u64().unwrap() as usize;
+            let column = index[1].as_u64().unwrap() as usize;
+            grid[row][column] = step["value"].as_u64().unwrap() as u8;
+            assert!(["single", "guess", "backtrack"].contains(&step["technique"].as_str().unwrap()));
+        }
+        assert_eq!(steps.last().unwrap()["action"], "place");
+        let solved = Board::from_csv("tests/test_board_pass_solution.csv").unwrap();
+        assert_eq!(Board::from_digits(&grid), solved);
+    }
+}
@@ -0,0 +1,55 @@
+//! This module exports the solver to javascript with wasm-bindgen, so web apps can solve,
+//! rate and hint puzzles client-side
+//! Puzzles are passed in as strings in any format Board parses, and come back as one line strings
+//!
+//! Enabled by the `wasm` feature
+
+use crate::board::Board;
+use crate::rating::rate;
+use wasm_bindgen::prelude::*;
+
+/// Solve the puzzle, returning the solution as a line of 81 digits
+/// Returns None if the puzzle can't be read or has no solution
+#[wasm_bindgen]
+pub fn solve_line(puzzle: &str) -> Option<String> {
+    let board: Board = puzzle.parse().ok()?;
+    board.solved().map(|solution| solution.to_line())
+}
+
+/// Rate the puzzle as easy, medium, hard or expert
+/// Returns None if the puzzle can't be read
+#[wasm_bindgen]
+pub fn rate_line(puzzle: &str) -> Option<String> {
+    let board: Board = puzzle.parse().ok()?;
+    Some(rate(&board).to_string())
+}
+
+/// Explain the next cell that can be deduced with human techniques
+/// Returns None if the puzzle can't be read or needs more than the logical solver's techniques
+#[wasm_bindgen]
+pub fn hint_line(puzzle: &str) -> Option<String> {
+    let board: Board = puzzle.parse().ok()?;
+    let hint = board.hint()?;
+    hint.steps.last().map(|step| step.explanation.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::EASY;
+
+    #[test]
+    fn test_solve_line() {
+        let solution = solve_line(EASY).unwrap();
+        assert_eq!(solution.len(), 81);
+        assert!(solution.starts_with("973854216"));
+        assert_eq!(solve_line("not a puzzle"), None);
+    }
+
+    #[test]
+    fn test_rate_and_hint_line() {
+        assert_eq!(rate_line(EASY).as_deref(), Some("easy"));
+        assert!(hint_line(EASY).unwrap().contains("must be 5"));
+        assert_eq!(hint_line("not a puzzle"), None);
+    }
+}
@@ -0,0 +1,272 @@
+//! This module contains XV marks, which sit between two orthogonally adjacent cells
+//! An X joins digits adding up to 10 and a V joins digits adding up to 5
+//!
+//! Marks are read one per line as `x` or `v` followed by the two cells in r1c1 notation,
+//! eg `x r1c1 r1c2`. Blank lines and lines starting with `#` are skipped
+//!
+//! Puzzles often use the negative constraint, where neighbours without a mark
+//! can't add up to 10 or 5. This is turned on with Marks::with_negative_constraint
+
+use crate::board::{Board, Index};
+use crate::constraint::Constraint;
+use crate::error::{ClueKind, SudokuError};
+use crate::io::parse_lines;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// The sum shown by an XV mark
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum Sum {
+    /// The digits add up to 10
+    X,
+    /// The digits add up to 5
+    V,
+}
+
+impl Sum {
+    /// Get the total the two digits add up to
+    pub fn total(self) -> u8 {
+        match self {
+            Sum::X => 10,
+            Sum::V => 5,
+        }
+    }
+}
+
+/// A mark between two orthogonally adjacent cells
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub struct Mark {
+    sum: Sum,
+    cells: [Index; 2],
+}
+
+impl Mark {
+    /// Make a mark of the sum between two cells
+    /// # Errors
+    /// Returns the reason if the cells aren't orthogonally adjacent
+    pub fn new(sum: Sum, first: Index, second: Index) -> Result<Mark, String> {
+        Ok(Mark {
+            sum,
+            cells: Index::edge(first, second)?,
+        })
+    }
+    /// Get the sum of the mark
+    pub fn sum(&self) -> Sum {
+        self.sum
+    }
+    /// Get the two cells the mark sits between
+    pub fn cells(&self) -> [Index; 2] {
+        self.cells
+    }
+    /// Check whether the mark sits between the two cells, in either order
+    fn joins(&self, first: &Index, second: &Index) -> bool {
+        self.cells == [*first, *second] || self.cells == [*second, *first]
+    }
+}
+
+/// Get the cells sharing an edge with the cell
+fn neighbours(index: &Index) -> impl Iterator<Item = Index> + '_ {
+    [(0, 1), (1, 0), (0, -1), (-1, 0)]
+        .into_iter()
+        .filter_map(|(row, column): (isize, isize)| {
+            let row = index.0.checked_add_signed(row)?;
+            let column = index.1.checked_add_signed(column)?;
+            Index::new(row, column).ok()
+        })
+}
+
+impl Constraint for Mark {
+    /// Check that the value adds up to the sum with the digit on the other side of the mark,
+    /// or with some other digit if the other cell is empty
+    fn allows(&self, board: &Board, index: &Index, value: u8) -> bool {
+        let other = match self.cells {
+            [first, other] | [other, first] if first == *index => other,
+            _ => return true,
+        };
+        let total = self.sum.total();
+        match board.get_cell(&other) {
+            Some(digit) => value + digit == total,
+            None => value < total && total - value != value && total - value <= 9,
+        }
+    }
+}
+
+/// A set of XV marks, optionally with the negative constraint
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct Marks {
+    marks: Vec<Mark>,
+    negative: bool,
+}
+
+impl Marks {
+    /// Make a set of marks, where unmarked neighbours can add up to anything
+    pub fn new(marks: Vec<Mark>) -> Self {
+        Marks {
+            marks,
+            negative: false,
+        }
+    }
+    /// Forbid neighbours without a mark from adding up to 10 or 5
+    pub fn with_negative_constraint(mut self) -> Self {
+        self.negative = true;
+        self
+    }
+    /// Get the marks
+    pub fn marks(&self) -> &[Mark] {
+        &self.marks
+    }
+    /// Check whether neighbours without a mark are forbidden from adding up to 10 or 5
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+}
+
+impl Constraint for Marks {
+    /// Check the marks next to the cell and, with the negative constraint,
+    /// that the value doesn't add up to 10 or 5 with an unmarked neighbour
+    fn allows(&self, board: &Board, index: &Index, value: u8) -> bool {
+        if !self.marks.as_slice().allows(board, index, value) {
+            return false;
+        }
+        !self.negative
+            || neighbours(index).all(|neighbour| match board.get_cell(&neighbour) {
+                Some(digit) => {
+                    ![Sum::X, Sum::V]
+                        .iter()
+                        .any(|sum| value + digit == sum.total())
+                        || self.marks.iter().any(|mark| mark.joins(index, &neighbour))
+                }
+                None => true,
+            })
+    }
+}
+
+impl FromStr for Mark {
+    type Err = String;
+
+    /// Read a mark as its sum followed by its cells, eg `x r1c1 r1c2` or `v r3c4 r4c4`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        let [sum, first, second] = parts[..] else {
+            return Err(format!("expected a sum and two cells, found {:?}", s));
+        };
+        let sum = match sum {
+            "x" | "X" => Sum::X,
+            "v" | "V" => Sum::V,
+            _ => return Err(format!("could not read {:?} as x or v", sum)),
+        };
+        let cell = |cell: &str| {
+            Index::from_r1c1(cell).ok_or_else(|| format!("could not read {:?} as a cell", cell))
+        };
+        Mark::new(sum, cell(first)?, cell(second)?)
+    }
+}
+
+impl Display for Mark {
+    /// Print the mark in the format it is read in
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sum = match self.sum {
+            Sum::X => 'x',
+            Sum::V => 'v',
+        };
+        write!(f, "{} {} {}", sum, self.cells[0], self.cells[1])
+    }
+}
+
+/// Read XV marks, one per line
+/// # Errors
+/// Returns SudokuError::InvalidClue for a line that can't be read or joins cells that aren't adjacent,
+/// numbering marks from 0
+pub fn parse_marks(s: &str) -> Result<Vec<Mark>, SudokuError> {
+    parse_lines(s, ClueKind::Mark)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{count_solutions, solve_with_constraints};
+    use crate::fixtures::{
+        assert_only_easy_solution, easy_board, easy_solution, easy_solution_without_rows,
+    };
+
+    fn marks() -> Vec<Mark> {
+        let marks = std::fs::read_to_string("tests/test_xv.txt").unwrap();
+        parse_marks(&marks).unwrap()
+    }
+
+    #[test]
+    fn test_parse_marks() {
+        let marks = marks();
+        assert_eq!(marks.len(), 20);
+        assert_eq!(marks[0].sum(), Sum::X);
+        assert_eq!(marks[3].cells(), [Index(1, 0), Index(1, 1)]);
+        assert_eq!(marks[3].to_string(), "v r2c1 r2c2");
+
+        assert_eq!(
+            parse_marks("x r1c1 r1c2\nv r1c1 r1c3"),
+            Err(SudokuError::InvalidClue {
+                kind: ClueKind::Mark,
+                line: 1,
+                reason: "r1c1 and r1c3 aren't next to each other".to_string()
+            })
+        );
+        assert!(parse_marks("w r1c1 r1c2").is_err());
+        assert!(parse_marks("x r1c1 r1c2 r1c3").is_err());
+    }
+
+    #[test]
+    fn test_allows() {
+        let x: Mark = "x r1c1 r1c2".parse().unwrap();
+        let v: Mark = "v r1c1 r2c1".parse().unwrap();
+        let mut board = Board::try_new(&[[0; 9]; 9]).unwrap();
+        // 5 can't be in an X, as the digits differ, nor in a V
+        assert!(!x.allows(&board, &Index(0, 1), 5));
+        assert!(!v.allows(&board, &Index(1, 0), 5));
+        assert!(v.allows(&board, &Index(1, 0), 4));
+        board.set(0, 0, 3).unwrap();
+        assert!(x.allows(&board, &Index(0, 1), 7));
+        assert!(!x.allows(&board, &Index(0, 1), 6));
+        assert!(v.allows(&board, &Index(1, 0), 2));
+
+        // without a mark, r1c1 and r1c2 can't add up to 10 under the negative constraint
+        let marks = Marks::new(vec![v]);
+        assert!(marks.allows(&board, &Index(0, 1), 7));
+        let marks = marks.with_negative_constraint();
+        assert!(!marks.allows(&board, &Index(0, 1), 7));
+        assert!(!marks.allows(&board, &Index(0, 1), 2));
+        assert!(marks.allows(&board, &Index(0, 1), 6));
+        // the 3 and 2 across the V are allowed
+        assert!(marks.allows(&board, &Index(1, 0), 2));
+        // cells away from the 3 are free
+        assert!(marks.allows(&board, &Index(1, 1), 7));
+    }
+
+    #[test]
+    fn test_solve() {
+        // clearing the top band leaves 120 classic solutions, and the marks pick out one of them
+        let puzzle = easy_solution_without_rows(&[0, 1, 2]);
+        assert_eq!(count_solutions(&puzzle, None), 120);
+        assert_only_easy_solution(&puzzle, &Marks::new(marks()).with_negative_constraint());
+    }
+
+    #[test]
+    fn test_missing_mark() {
+        // the clues in r1c2 and r1c3 add up to 10, so they need an X under the negative constraint
+        let mut missing = marks();
+        missing.retain(|mark| mark.to_string() != "x r1c2 r1c3");
+        let missing = Marks::new(missing);
+        assert!(missing.is_satisfied(&easy_solution()));
+        let missing = missing.with_negative_constraint();
+        assert!(!missing.is_satisfied(&easy_solution()));
+        assert_eq!(solve_with_constraints(&mut easy_board(), &[&missing]), None);
+    }
+
+    #[test]
+    fn test_empty_grid() {
+        let marks = Marks::new(marks()).with_negative_constraint();
+        let mut empty = Board::try_new(&[[0; 9]; 9]).unwrap();
+        let solved = solve_with_constraints(&mut empty, &[&marks]).unwrap();
+        assert!(solved.is_complete());
+        assert!(marks.is_satisfied(&solved));
+    }
+}
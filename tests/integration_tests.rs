@@ -1,13 +1,15 @@
 extern crate sudoku_solver_by_roy;
+use std::io::Write;
 use std::path::PathBuf;
-use sudoku_solver_by_roy::board::{solve, Board};
+use std::process::{Command, Stdio};
+use sudoku_solver_by_roy::board::{count_solutions, solve, Board};
 #[test]
 fn test_pass() {
-    let mut board = Board::from_csv(&PathBuf::from("tests/test_board_pass.csv")).unwrap();
+    let mut board = Board::from_csv("tests/test_board_pass.csv").unwrap();
     solve(&mut board);
     assert_eq!(
         board,
-        Board::new(&[
+        Board::try_from([
             [9, 7, 3, 8, 5, 4, 2, 1, 6],
             [1, 4, 6, 2, 7, 9, 5, 8, 3],
             [2, 8, 5, 6, 1, 3, 4, 9, 7],
@@ -18,5 +20,453 @@ fn test_pass() {
             [5, 6, 8, 7, 4, 2, 1, 3, 9],
             [4, 2, 1, 9, 3, 8, 7, 6, 5]
         ])
+        .unwrap()
     )
 }
+
+#[test]
+fn test_cli_expect_match() {
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+        .args([
+            "solve",
+            "tests/test_board_pass.csv",
+            "--expect",
+            "tests/test_board_pass_solution.csv",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Solution matches"));
+}
+
+#[test]
+fn test_cli_expect_mismatch() {
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+        .args([
+            "solve",
+            "tests/test_board_pass.csv",
+            "--expect",
+            "tests/test_board_pass_wrong_solution.csv",
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Solution does not match"));
+    assert!(stdout.contains("r1c7: found 2, expected 1"));
+}
+
+#[test]
+fn test_cli_quiet() {
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+        .args(["solve", "--quiet", "tests/test_board_pass.csv"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0].len(), 81);
+    assert!(lines[0].starts_with("973854216"));
+}
+
+#[test]
+fn test_cli_quiet_failure() {
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+        .args(["solve", "--quiet", "tests/test_board_invalid_sudoku.csv"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    // a solution not matching the expected one isn't printed either
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+        .args([
+            "solve",
+            "--quiet",
+            "tests/test_board_pass.csv",
+            "--expect",
+            "tests/test_board_pass_wrong_solution.csv",
+        ])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn test_cli_exit_codes() {
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+        .args(["solve", "tests/does_not_exist.csv"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+        .args(["solve", "tests/test_invalid_non_int.csv"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+        .args(["solve", "tests/test_board_invalid_sudoku.csv"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(4));
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+        .args([
+            "solve",
+            "tests/test_board_pass.csv",
+            "--expect",
+            "tests/test_board_pass_wrong_solution.csv",
+        ])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_cli_batch_tag_difficulty() {
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+        .args(["solve", "--batch", "tests/batch", "--tag-difficulty"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            "easy.csv: trivial",
+            "hard.csv: hard (over 1000 nodes)",
+            "medium.csv: moderate"
+        ]
+    );
+}
+
+#[test]
+fn test_cli_batch_solve() {
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+        .args(["solve", "--batch", "tests/batch"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("easy.csv: 973854216"));
+}
+
+#[test]
+fn test_cli_batch_failures() {
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+        .args(["solve", "--batch", "tests/batch_failures"])
+        .output()
+        .unwrap();
+    // a board breaking the rules is worse than one without a solution
+    assert_eq!(output.status.code(), Some(4));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].starts_with("easy.csv: 973854216"));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("invalid.csv: Error: Invalid Board"));
+    assert!(stderr.contains("unsolvable.csv: No solution found"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+        .args(["solve", "--quiet", "--batch", "tests/batch_failures"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(4));
+    assert_eq!(String::from_utf8(output.stdout).unwrap().lines().count(), 1);
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn test_cli_missing_file() {
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+        .args(["solve", "tests/does_not_exist.csv"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stdout.starts_with("Error: Could not read input"));
+    assert!(!stderr.contains("panicked"));
+}
+
+#[test]
+fn test_cli_generate() {
+    let run = || {
+        Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+            .args([
+                "generate",
+                "--difficulty",
+                "easy",
+                "--seed",
+                "42",
+                "--quiet",
+            ])
+            .output()
+            .unwrap()
+    };
+    let output = run();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let puzzle = Board::from_line(stdout.trim()).unwrap();
+    assert_eq!(count_solutions(&puzzle, Some(2)), 1);
+    // the same seed gives the same puzzle
+    assert_eq!(String::from_utf8(run().stdout).unwrap(), stdout);
+}
+
+#[test]
+fn test_cli_validate() {
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+        .args(["validate", "tests/test_board_pass.csv"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.trim(), "Valid puzzle with a unique solution");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+        .args(["validate", "tests/test_board_invalid_sudoku.csv"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("Error: Invalid Board"));
+}
+
+#[test]
+fn test_cli_rate_and_hint() {
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+        .args(["rate", "tests/test_board_pass.csv"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap().trim(), "easy");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+        .args(["hint", "tests/test_board_pass.csv"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            "r1c5 is 5 (hidden single)",
+            "r1c5 must be 5: it is the only place left for 5 in row 1"
+        ]
+    );
+}
+
+#[test]
+fn test_cli_convert() {
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+        .args(["convert", "tests/test_board_pass.csv", "--to", "line"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let line = String::from_utf8(output.stdout).unwrap();
+    assert!(line.starts_with(".738.4216"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+        .args(["convert", "tests/test_board_pass.csv", "--to", "csv"])
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        std::fs::read_to_string("tests/test_board_pass.csv").unwrap()
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+        .args(["convert", "tests/test_board_pass.ss", "--to", "sdm"])
+        .output()
+        .unwrap();
+    let sdm = String::from_utf8(output.stdout).unwrap();
+    assert!(sdm.starts_with("073804216000209500"));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_cli_convert_json() {
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+        .args(["convert", "tests/test_board_pass.csv", "--to", "json"])
+        .output()
+        .unwrap();
+    let json = String::from_utf8(output.stdout).unwrap();
+    assert!(json.starts_with("[[0,7,3,8,0,4,2,1,6],[0,0,0,2,0,9,5,0,0],"));
+    assert!(json.ends_with("[4,2,0,0,3,0,0,6,0]]\n"));
+}
+
+#[test]
+fn test_cli_solve_output() {
+    let output_path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("solution.csv");
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+        .args(["solve", "tests/test_board_pass.csv", "-o"])
+        .arg(&output_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert_eq!(
+        std::fs::read_to_string(&output_path).unwrap(),
+        std::fs::read_to_string("tests/test_board_pass_solution.csv").unwrap()
+    );
+}
+
+#[test]
+fn test_cli_solve_pretty() {
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+        .args([
+            "solve",
+            "tests/test_board_pass.csv",
+            "--pretty",
+            "--no-color",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines[0], "┏━━━┯━━━┯━━━┳━━━┯━━━┯━━━┳━━━┯━━━┯━━━┓");
+    assert_eq!(lines[1], "┃ 9 │ 7 │ 3 ┃ 8 │ 5 │ 4 ┃ 2 │ 1 │ 6 ┃");
+    // no blank line after the bottom border
+    assert!(stdout.ends_with("┛\n"));
+}
+
+#[test]
+fn test_cli_solve_jigsaw() {
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+        .args([
+            "solve",
+            "tests/test_board_pass.csv",
+            "--regions",
+            "tests/test_jigsaw_regions.txt",
+            "-q",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let solution = std::fs::read_to_string("tests/test_board_pass_solution.csv").unwrap();
+    let solution: String = solution.chars().filter(char::is_ascii_digit).collect();
+    assert_eq!(String::from_utf8(output.stdout).unwrap().trim(), solution);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+        .args([
+            "solve",
+            "tests/test_board_pass.csv",
+            "--regions",
+            "tests/test_invalid_column.csv",
+        ])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+}
+
+#[test]
+fn test_cli_solve_stdin() {
+    for args in [vec!["solve", "-q"], vec!["solve", "-", "-q"]] {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(b".738.4216...2.95..2856.3.97...3...7475....3.1..4.2.....9756.......7..1..42..3..6.\n")
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.starts_with("973854216"));
+    }
+}
+
+#[test]
+fn test_cli_solve_multi() {
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+        .args(["solve", "--multi", "tests/test_collection.sdm"])
+        .output()
+        .unwrap();
+    // every line is still printed, and the unreadable one sets the exit code
+    assert_eq!(output.status.code(), Some(3));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("973854216"));
+    assert!(lines[1].starts_with("812753649"));
+    assert!(lines[2].starts_with("Error: Invalid dimensions"));
+}
+
+#[test]
+fn test_cli_solve_multi_jobs() {
+    let run = |jobs: &str| {
+        Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+            .args([
+                "solve",
+                "--multi",
+                "tests/test_collection.sdm",
+                "--jobs",
+                jobs,
+            ])
+            .output()
+            .unwrap()
+    };
+    let sequential = run("1");
+    for jobs in ["2", "8"] {
+        let output = run(jobs);
+        assert_eq!(output.status.code(), sequential.status.code());
+        assert_eq!(output.stdout, sequential.stdout);
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_cli_solve_json() {
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+        .args(["solve", "tests/test_board_pass.csv", "--format", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with(
+        "{\"puzzle\":\".738.4216...2.95..2856.3.97...3...7475....3.1..4.2.....9756.......7..1..42..3..6.\",\"solution\":\"973854216"
+    ));
+    assert!(stdout.contains("\"valid\":true,\"stats\":{\"nodes\":"));
+
+    // valid by the rules, but with no solution
+    let mut child = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+        .args(["solve", "--format", "json"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(
+            b"51..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79\n",
+        )
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(output.status.code(), Some(5));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("\"solution\":null,\"valid\":false"));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_cli_convert_fpuzzles() {
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku_solver_by_roy"))
+        .args(["convert", "tests/test_board_pass.csv", "--to", "fpuzzles"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let url = String::from_utf8(output.stdout).unwrap();
+    assert!(url.starts_with("https://www.f-puzzles.com/?load=N4Ig"));
+}